@@ -174,8 +174,8 @@ fn testnet_genesis(
 	chain_id: u64,
 ) -> RuntimeGenesisConfig {
 	use tenet_runtime::{
-		AuraConfig, BalancesConfig, EVMChainIdConfig, EVMConfig, GrandpaConfig, SudoConfig,
-		SystemConfig,
+		AuraConfig, BalancesConfig, EVMChainIdConfig, EVMConfig, EthereumConfig, GrandpaConfig,
+		SudoConfig, SystemConfig,
 	};
 
 	RuntimeGenesisConfig {
@@ -296,7 +296,15 @@ fn testnet_genesis(
 			},
 			..Default::default()
 		},
-		ethereum: Default::default(),
+		ethereum: EthereumConfig {
+			// PoC, PoM and state diff generation can be switched on later through `Sudo`.
+			enable_poc: false,
+			enable_pom: false,
+			enable_state_diff: false,
+			// PoCs are signed off chain by the development enclave key.
+			poc_signer: crate::enclave::dev_enclave_address(),
+			..Default::default()
+		},
 		dynamic_fee: Default::default(),
 		base_fee: Default::default(),
 	}
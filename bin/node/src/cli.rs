@@ -51,6 +51,9 @@ pub enum Subcommand {
 	/// Import blocks.
 	ImportBlocks(sc_cli::ImportBlocksCmd),
 
+	/// Import Ethereum blocks as pre-logs.
+	ImportEthereumBlocks(tc_cli::ImportEthereumBlocksCmd),
+
 	/// Remove the whole chain.
 	PurgeChain(sc_cli::PurgeChainCmd),
 
@@ -59,4 +62,10 @@ pub enum Subcommand {
 
 	/// Db meta columns information.
 	FrontierDb(tc_cli::FrontierDbCmd),
+
+	/// Verify exported proofs of computation.
+	Verify(tc_cli::VerifyCmd),
+
+	/// Add a new state master key to the keystore.
+	RotateStateKey(tc_cli::RotateStateKeyCmd),
 }
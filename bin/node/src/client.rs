@@ -14,12 +14,18 @@ pub type FullClient<RuntimeApi, Executor> =
 
 pub type Client = FullClient<tenet_runtime::RuntimeApi, TemplateRuntimeExecutor>;
 
+/// Tenet host functions, so that a Wasm runtime encrypts and decrypts exactly like the native
+/// one.
+pub type TenetHostFunctions = (tp_io::crypto::HostFunctions, tp_io::state::HostFunctions);
 /// Only enable the benchmarking host functions when we actually want to benchmark.
 #[cfg(feature = "runtime-benchmarks")]
-pub type HostFunctions = frame_benchmarking::benchmarking::HostFunctions;
-/// Otherwise we use empty host functions for ext host functions.
+pub type HostFunctions = (
+	frame_benchmarking::benchmarking::HostFunctions,
+	TenetHostFunctions,
+);
+/// Otherwise only the Tenet host functions extend the Substrate ones.
 #[cfg(not(feature = "runtime-benchmarks"))]
-pub type HostFunctions = ();
+pub type HostFunctions = TenetHostFunctions;
 
 pub struct TemplateRuntimeExecutor;
 impl NativeExecutionDispatch for TemplateRuntimeExecutor {
@@ -19,8 +19,11 @@ use futures::TryFutureExt;
 // Substrate
 use sc_cli::{ChainSpec, SubstrateCli};
 use sc_service::DatabaseSource;
+use sp_core::U256;
 // Frontier
 use tc_db::kv::frontier_database_dir;
+// Runtime
+use tenet_runtime::opaque::Block;
 
 use crate::{
 	chain_spec,
@@ -112,6 +115,26 @@ pub fn run() -> sc_cli::Result<()> {
 				Ok((cmd.run(client, import_queue), task_manager))
 			})
 		}
+		Some(Subcommand::ImportEthereumBlocks(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			let target_gas_price = cli.eth.target_gas_price;
+			runner.async_run(|mut config| {
+				let (client, _, _, task_manager, _) =
+					service::new_chain_ops(&mut config, &cli.eth)?;
+				Ok((
+					cmd.run::<Block, _, _, _, _>(client.clone(), client, move |block| {
+						// Execute each Ethereum block at its own timestamp.
+						let timestamp = sp_timestamp::InherentDataProvider::new(
+							(block.header.timestamp * 1000).into(),
+						);
+						let dynamic_fee =
+							fp_dynamic_fee::InherentDataProvider(U256::from(target_gas_price));
+						(timestamp, dynamic_fee)
+					}),
+					task_manager,
+				))
+			})
+		}
 		Some(Subcommand::PurgeChain(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
 			runner.sync_run(|config| {
@@ -187,6 +210,8 @@ pub fn run() -> sc_cli::Result<()> {
 				cmd.run(client, frontier_backend)
 			})
 		}
+		Some(Subcommand::Verify(cmd)) => cmd.run(),
+		Some(Subcommand::RotateStateKey(cmd)) => cmd.run(&cli),
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
 			runner.run_node_until_exit(|config| async move {
@@ -0,0 +1,93 @@
+//! The node keys of confidential execution.
+//!
+//! The enclave key confidential transactions are encrypted to is a secp256k1 pair stored in
+//! the node keystore under [`ENCLAVE_KEY_TYPE`]; the state master key the state encryption
+//! subkeys are derived from is stored under [`STATE_KEY_TYPE`]. Both can be created or
+//! imported with the usual key commands, e.g.
+//! `key insert --key-type tenc --scheme ecdsa --suri <secret>`.
+
+use sc_client_api::execution_extensions::ExtensionsFactory;
+use sc_keystore::LocalKeystore;
+use sp_core::{crypto::Wraps, H160};
+use sp_externalities::Extensions;
+use sp_keystore::Keystore;
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+use tp_io::{
+	enclave, state_key, state_master, EnclaveKey, EnclaveKeyExt, ENCLAVE_KEY_TYPE, STATE_KEY_TYPE,
+};
+
+/// Well-known enclave key seed of development chains.
+const DEV_ENCLAVE_SEED: &str = "0x57f0148f94d13095cfda539d0da0d1541304b678d8b36e243980aab4e1b7cead";
+/// Well-known state master key seed of development chains.
+const DEV_STATE_MASTER_SEED: &str = "//TenetStateMaster";
+
+/// Address of the development enclave key, the PoC signer of development chains.
+pub fn dev_enclave_address() -> H160 {
+	let key = hex::decode(&DEV_ENCLAVE_SEED[2..])
+		.ok()
+		.and_then(|seed| <[u8; 32]>::try_from(seed).ok())
+		.and_then(|seed| EnclaveKey::from_seed(&seed))
+		.expect("the dev enclave seed is a valid secret key; qed");
+	H160::from_slice(&sp_core::keccak_256(&key.public())[12..])
+}
+
+/// Load the enclave key from the keystore, if one was inserted.
+pub fn load_enclave_key(keystore: &LocalKeystore) -> Option<EnclaveKey> {
+	let public = keystore
+		.ecdsa_public_keys(ENCLAVE_KEY_TYPE)
+		.into_iter()
+		.next()?;
+	let pair = keystore.key_pair::<enclave::Pair>(&public.into()).ok()??;
+	EnclaveKey::from_seed(&pair.as_inner_ref().seed())
+}
+
+/// Load the seeds of all state master keys in the keystore.
+pub fn load_state_master_keys(keystore: &LocalKeystore) -> Vec<[u8; 32]> {
+	keystore
+		.ecdsa_public_keys(STATE_KEY_TYPE)
+		.into_iter()
+		.filter_map(|public| {
+			keystore
+				.key_pair::<state_master::Pair>(&public.into())
+				.ok()?
+		})
+		.map(|pair| pair.as_inner_ref().seed())
+		.collect()
+}
+
+/// Install the state master keys of the keystore. Which one new state is encrypted under is
+/// selected by the runtime from the on-chain state key id.
+pub fn install_state_master_keys(keystore: &LocalKeystore) {
+	let master_keys = load_state_master_keys(keystore);
+	if master_keys.is_empty() {
+		log::warn!("No state master key in the keystore, only the legacy state key is available");
+	}
+	state_key::install_master_keys(master_keys);
+}
+
+/// Add the development enclave and state master keys to the keystore unless it already holds
+/// them.
+pub fn insert_dev_keys(keystore: &LocalKeystore) -> Result<(), String> {
+	for (key_type, seed) in [
+		(ENCLAVE_KEY_TYPE, DEV_ENCLAVE_SEED),
+		(STATE_KEY_TYPE, DEV_STATE_MASTER_SEED),
+	] {
+		if keystore.ecdsa_public_keys(key_type).is_empty() {
+			keystore
+				.ecdsa_generate_new(key_type, Some(seed))
+				.map_err(|e| format!("failed to insert the dev {key_type:?} key: {e}"))?;
+		}
+	}
+	Ok(())
+}
+
+/// Makes the enclave key available to every runtime call.
+pub struct EnclaveKeyExtensions(pub EnclaveKey);
+
+impl<B: BlockT> ExtensionsFactory<B> for EnclaveKeyExtensions {
+	fn extensions_for(&self, _block_hash: B::Hash, _block_number: NumberFor<B>) -> Extensions {
+		let mut extensions = Extensions::new();
+		extensions.register(EnclaveKeyExt::from(self.0.clone()));
+		extensions
+	}
+}
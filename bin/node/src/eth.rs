@@ -6,16 +6,23 @@ use std::{
 };
 
 use futures::{future, prelude::*};
+use scale_codec::Decode;
 // Substrate
-use sc_client_api::BlockchainEvents;
+use sc_client_api::{Backend, BlockchainEvents};
 use sc_network_sync::SyncingService;
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
 use sp_api::ConstructRuntimeApi;
+use sp_core::{
+	offchain::{OffchainStorage, STORAGE_PREFIX},
+	H256,
+};
+use sp_runtime::traits::Header as HeaderT;
 use tc_executor::NativeExecutionDispatch;
 // Frontier
 pub use tc_consensus::FrontierBlockImport;
 use tc_rpc::{EthTask, OverrideHandle};
 pub use tc_rpc_core::types::{FeeHistoryCache, FeeHistoryCacheLimit, FilterPool};
+use tp_io::EnclaveKey;
 // Local
 use tenet_runtime::opaque::Block;
 
@@ -90,6 +97,11 @@ pub struct EthConfiguration {
 	/// Default value is 200MB.
 	#[arg(long, default_value = "209715200")]
 	pub frontier_sql_backend_cache_size: u64,
+
+	/// Directory the PoCs pruned by the runtime are exported to, one JSON file per block.
+	/// Requires `--enable-offchain-indexing true`.
+	#[arg(long)]
+	pub poc_archive_path: Option<PathBuf>,
 }
 
 pub struct FrontierPartialComponents {
@@ -113,6 +125,7 @@ pub trait EthCompatRuntimeApiCollection:
 	sp_api::ApiExt<Block>
 	+ tp_rpc::ConvertTransactionRuntimeApi<Block>
 	+ tp_rpc::EthereumRuntimeRPCApi<Block>
+	+ tp_rpc::EthereumTracingRuntimeApi<Block>
 {
 }
 
@@ -120,6 +133,7 @@ impl<Api> EthCompatRuntimeApiCollection for Api where
 	Api: sp_api::ApiExt<Block>
 		+ tp_rpc::ConvertTransactionRuntimeApi<Block>
 		+ tp_rpc::EthereumRuntimeRPCApi<Block>
+		+ tp_rpc::EthereumTracingRuntimeApi<Block>
 {
 }
 
@@ -168,13 +182,14 @@ pub async fn spawn_frontier_tasks<RuntimeApi, Executor>(
 		}
 		#[cfg(feature = "sql")]
 		tc_db::Backend::Sql(b) => {
+			let b = Arc::new(b);
 			task_manager.spawn_essential_handle().spawn_blocking(
 				"frontier-mapping-sync-worker",
 				Some("frontier"),
 				tc_mapping_sync::sql::SyncWorker::run(
 					client.clone(),
 					backend,
-					Arc::new(b),
+					b.clone(),
 					client.import_notification_stream(),
 					tc_mapping_sync::sql::SyncWorkerConfig {
 						read_notification_timeout: Duration::from_secs(10),
@@ -185,6 +200,12 @@ pub async fn spawn_frontier_tasks<RuntimeApi, Executor>(
 					pubsub_notification_sinks,
 				),
 			);
+			// Spawn the call trace indexer, lagging behind the mapping sync worker.
+			task_manager.spawn_essential_handle().spawn(
+				"frontier-trace-worker",
+				Some("frontier"),
+				tc_mapping_sync::sql::TraceWorker::run(client.clone(), b, Duration::from_secs(6)),
+			);
 		}
 	}
 
@@ -211,3 +232,75 @@ pub async fn spawn_frontier_tasks<RuntimeApi, Executor>(
 		),
 	);
 }
+
+/// Move the PoCs the runtime archived in the off-chain index to `path`, one JSON file per
+/// block, as new best blocks are imported. The runtime stores PoCs unsigned; they are signed
+/// with `enclave_key` on export.
+pub fn spawn_poc_archive_task<RuntimeApi, Executor>(
+	task_manager: &TaskManager,
+	client: Arc<FullClient<RuntimeApi, Executor>>,
+	backend: Arc<FullBackend>,
+	path: PathBuf,
+	enclave_key: EnclaveKey,
+) where
+	RuntimeApi: ConstructRuntimeApi<Block, FullClient<RuntimeApi, Executor>>,
+	RuntimeApi: Send + Sync + 'static,
+	Executor: NativeExecutionDispatch + 'static,
+{
+	let Some(mut storage) = backend.offchain_storage() else {
+		log::warn!(target: "poc-archive", "Off-chain storage unavailable, PoCs are not archived");
+		return;
+	};
+	if let Err(err) = std::fs::create_dir_all(&path) {
+		log::warn!(target: "poc-archive", "Cannot create {}: {err}", path.display());
+		return;
+	}
+
+	let task = client
+		.import_notification_stream()
+		.for_each(move |notification| {
+			if notification.is_new_best {
+				let number = *notification.header.number();
+				let key = pallet_ethereum::poc_archive_key(number);
+				if let Some(value) = storage.get(STORAGE_PREFIX, &key) {
+					match archive_pocs(&path, number, &value, &enclave_key) {
+						Ok(()) => storage.remove(STORAGE_PREFIX, &key),
+						Err(err) => {
+							log::warn!(target: "poc-archive", "Failed to archive PoCs of #{number}: {err}")
+						}
+					}
+				}
+			}
+			future::ready(())
+		});
+	task_manager
+		.spawn_handle()
+		.spawn("poc-archive", Some("frontier"), task);
+}
+
+fn archive_pocs(
+	path: &std::path::Path,
+	number: u32,
+	value: &[u8],
+	enclave_key: &EnclaveKey,
+) -> Result<(), String> {
+	let pocs = Vec::<(H256, Vec<u8>)>::decode(&mut &value[..]).map_err(|e| e.to_string())?;
+	let signer = format!("0x04{}", hex::encode(enclave_key.public()));
+	let entries = pocs
+		.into_iter()
+		.map(|(transaction_hash, poc)| {
+			let io_hash_list = fp_poc::decode_unsigned(&poc).map_err(|e| format!("{e:?}"))?;
+			let root = fp_poc::generate_root(&io_hash_list);
+			let poc =
+				fp_poc::PoC::from_signature(io_hash_list, &enclave_key.sign(root.as_fixed_bytes()))
+					.ok_or("invalid enclave signature")?;
+			Ok(serde_json::json!({
+				"transactionHash": transaction_hash,
+				"poc": format!("0x{}", hex::encode(rlp::encode(&poc))),
+				"signer": signer,
+			}))
+		})
+		.collect::<Result<Vec<_>, String>>()?;
+	let json = serde_json::to_vec_pretty(&entries).map_err(|e| e.to_string())?;
+	std::fs::write(path.join(format!("{number}.json")), json).map_err(|e| e.to_string())
+}
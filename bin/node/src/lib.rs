@@ -11,6 +11,7 @@ mod chain_spec;
 mod cli;
 mod client;
 mod command;
+mod enclave;
 mod eth;
 mod rpc;
 mod service;
@@ -5,7 +5,7 @@ use jsonrpsee::RpcModule;
 use sc_client_api::{
 	backend::{Backend, StorageProvider},
 	client::BlockchainEvents,
-	AuxStore, UsageProvider,
+	AuxStore, BlockBackend, UsageProvider,
 };
 use sc_network::NetworkService;
 use sc_network_sync::SyncingService;
@@ -24,7 +24,10 @@ use sp_runtime::traits::Block as BlockT;
 pub use tc_rpc::{EthBlockDataCacheTask, EthConfig, OverrideHandle};
 pub use tc_rpc_core::types::{FeeHistoryCache, FeeHistoryCacheLimit, FilterPool};
 pub use tc_storage::overrides_handle;
-use tp_rpc::{ConvertTransaction, ConvertTransactionRuntimeApi, EthereumRuntimeRPCApi};
+use tp_rpc::{
+	ConvertTransaction, ConvertTransactionRuntimeApi, EthereumRuntimeRPCApi,
+	EthereumTracingRuntimeApi,
+};
 
 /// Extra dependencies for Ethereum compatibility.
 pub struct EthDeps<B: BlockT, C, P, A: ChainApi, CT, CIDP> {
@@ -65,6 +68,11 @@ pub struct EthDeps<B: BlockT, C, P, A: ChainApi, CT, CIDP> {
 	pub forced_parent_hashes: Option<BTreeMap<H256, H256>>,
 	/// Something that can create the inherent data providers for pending state
 	pub pending_create_inherent_data_providers: CIDP,
+	/// The node's enclave key for confidential transactions.
+	pub enclave_key: tp_io::EnclaveKey,
+	/// SQL backend holding the indexed call traces, serving the trace RPC.
+	#[cfg(feature = "sql")]
+	pub trace_backend: Option<Arc<tc_db::sql::Backend<B>>>,
 }
 
 /// Instantiate Ethereum-compatible RPC extensions.
@@ -84,9 +92,11 @@ where
 	C::Api: AuraApi<B, AuraId>
 		+ BlockBuilderApi<B>
 		+ ConvertTransactionRuntimeApi<B>
-		+ EthereumRuntimeRPCApi<B>,
+		+ EthereumRuntimeRPCApi<B>
+		+ EthereumTracingRuntimeApi<B>,
 	C: HeaderBackend<B> + HeaderMetadata<B, Error = BlockChainError>,
 	C: BlockchainEvents<B> + AuxStore + UsageProvider<B> + StorageProvider<B, BE> + 'static,
+	C: BlockBackend<B>,
 	BE: Backend<B> + 'static,
 	P: TransactionPool<Block = B> + 'static,
 	A: ChainApi<Block = B> + 'static,
@@ -95,10 +105,12 @@ where
 	EC: EthConfig<B, C>,
 {
 	use tc_rpc::{
-		pending::AuraConsensusDataProvider, Eth, EthApiServer, EthDevSigner, EthFilter,
-		EthFilterApiServer, EthPubSub, EthPubSubApiServer, EthSigner, Net, NetApiServer, Web3,
-		Web3ApiServer,
+		pending::AuraConsensusDataProvider, Debug, DebugApiServer, Eth, EthApiServer, EthDevSigner,
+		EthFilter, EthFilterApiServer, EthPubSub, EthPubSubApiServer, EthSigner, Net, NetApiServer,
+		Web3, Web3ApiServer,
 	};
+	#[cfg(feature = "sql")]
+	use tc_rpc::{Trace, TraceApiServer};
 	#[cfg(feature = "txpool")]
 	use tc_rpc::{TxPool, TxPoolApiServer};
 
@@ -121,6 +133,9 @@ where
 		execute_gas_limit_multiplier,
 		forced_parent_hashes,
 		pending_create_inherent_data_providers,
+		enclave_key,
+		#[cfg(feature = "sql")]
+		trace_backend,
 	} = deps;
 
 	let mut signers = Vec::new();
@@ -146,11 +161,19 @@ where
 			forced_parent_hashes,
 			pending_create_inherent_data_providers,
 			Some(Box::new(AuraConsensusDataProvider::new(client.clone()))),
+			Some(enclave_key),
 		)
 		.replace_config::<EC>()
 		.into_rpc(),
 	)?;
 
+	io.merge(Debug::new(client.clone(), frontier_backend.clone()).into_rpc())?;
+
+	#[cfg(feature = "sql")]
+	if let Some(trace_backend) = trace_backend {
+		io.merge(Trace::new(client.clone(), trace_backend).into_rpc())?;
+	}
+
 	if let Some(filter_pool) = filter_pool {
 		io.merge(
 			EthFilter::new(
@@ -5,7 +5,7 @@ use std::{cell::RefCell, sync::Arc, time::Duration};
 use futures::{channel::mpsc, prelude::*};
 // Substrate
 use prometheus_endpoint::Registry;
-use sc_client_api::BlockBackend;
+use sc_client_api::{BlockBackend, ExecutorProvider};
 use sc_consensus::BasicQueue;
 use sc_network_sync::warp::WarpSyncParams;
 use sc_service::{error::Error as ServiceError, Configuration, PartialComponents, TaskManager};
@@ -22,8 +22,9 @@ use crate::{
 	cli::Sealing,
 	client::{BaseRuntimeApiCollection, FullBackend, FullClient, RuntimeApiCollection},
 	eth::{
-		new_frontier_partial, spawn_frontier_tasks, BackendType, EthCompatRuntimeApiCollection,
-		FrontierBackend, FrontierBlockImport, FrontierPartialComponents,
+		new_frontier_partial, spawn_frontier_tasks, spawn_poc_archive_task, BackendType,
+		EthCompatRuntimeApiCollection, FrontierBackend, FrontierBlockImport,
+		FrontierPartialComponents,
 	},
 };
 pub use crate::{
@@ -101,6 +102,20 @@ where
 		)?;
 	let client = Arc::new(client);
 
+	let keystore = keystore_container.local_keystore();
+	if config.chain_spec.id() == "dev" {
+		crate::enclave::insert_dev_keys(&keystore).map_err(ServiceError::Other)?;
+	}
+	crate::enclave::install_state_master_keys(&keystore);
+	match crate::enclave::load_enclave_key(&keystore) {
+		Some(key) => client
+			.execution_extensions()
+			.set_extensions_factory(crate::enclave::EnclaveKeyExtensions(key)),
+		None => log::warn!(
+			"No enclave key in the keystore, confidential transactions cannot be processed"
+		),
+	}
+
 	let telemetry = telemetry.map(|(worker, telemetry)| {
 		task_manager
 			.spawn_handle()
@@ -287,6 +302,17 @@ where
 		other: (mut telemetry, block_import, grandpa_link, frontier_backend, overrides),
 	} = new_partial(&config, &eth_config, build_import_queue)?;
 
+	// Blocks carrying confidential transactions can only be executed with the enclave key, so
+	// a node without it would fail to import them or compute a different state.
+	let enclave_key = crate::enclave::load_enclave_key(&keystore_container.local_keystore())
+		.ok_or_else(|| {
+			ServiceError::Other(
+				"No enclave key in the keystore, insert one with \
+				 `key insert --key-type tenc --scheme ecdsa`"
+					.into(),
+			)
+		})?;
+
 	let FrontierPartialComponents {
 		filter_pool,
 		fee_history_cache,
@@ -356,6 +382,7 @@ where
 
 		let is_authority = role.is_authority();
 		let enable_dev_signer = eth_config.enable_dev_signer;
+		let enclave_key = enclave_key.clone();
 		let max_past_logs = eth_config.max_past_logs;
 		let execute_gas_limit_multiplier = eth_config.execute_gas_limit_multiplier;
 		let filter_pool = filter_pool.clone();
@@ -409,6 +436,12 @@ where
 				execute_gas_limit_multiplier,
 				forced_parent_hashes: None,
 				pending_create_inherent_data_providers,
+				enclave_key: enclave_key.clone(),
+				#[cfg(feature = "sql")]
+				trace_backend: match frontier_backend.clone() {
+					tc_db::Backend::Sql(b) => Some(Arc::new(b)),
+					_ => None,
+				},
 			};
 			let deps = crate::rpc::FullDeps {
 				client: client.clone(),
@@ -445,6 +478,16 @@ where
 		telemetry: telemetry.as_mut(),
 	})?;
 
+	if let Some(path) = eth_config.poc_archive_path.clone() {
+		spawn_poc_archive_task(
+			&task_manager,
+			client.clone(),
+			backend.clone(),
+			path,
+			enclave_key,
+		);
+	}
+
 	spawn_frontier_tasks(
 		&task_manager,
 		client.clone(),
@@ -49,7 +49,7 @@ use pallet_ethereum::{
 use pallet_evm::{
 	Account as EVMAccount, EnsureAccountId20, FeeCalculator, IdentityAddressMapping, Runner,
 };
-use tp_rpc::TransactionStatus;
+use tp_rpc::{TracerConfig, TransactionStatus, TransactionTrace};
 
 // A few exports that help ease life for downstream crates.
 pub use frame_system::Call as SystemCall;
@@ -60,6 +60,10 @@ use pallet_transaction_payment::Multiplier;
 mod precompiles;
 use precompiles::FrontierPrecompiles;
 
+// Make the Wasm binary available.
+#[cfg(feature = "std")]
+include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
+
 /// Type of block number.
 pub type BlockNumber = u32;
 
@@ -353,11 +357,29 @@ parameter_types! {
 	pub const PostBlockAndTxnHashes: PostLogContent = PostLogContent::BlockAndTxnHashes;
 }
 
+parameter_types! {
+	/// PoCs are kept for about 30 days.
+	pub const PocRetentionPeriod: BlockNumber = 30 * DAYS;
+	/// At most a tenth of the block is spent pruning expired PoCs.
+	pub MaxPocPruneWeight: Weight = Perbill::from_percent(10) * BlockWeights::get().max_block;
+	/// Re-encryption is charged 10ns per byte, a 5MB runtime takes 50ms.
+	pub const ReencryptionWeightPerByte: Weight = Weight::from_parts(10_000, 1);
+}
+
 impl pallet_ethereum::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type StateRoot = pallet_ethereum::IntermediateStateRoot<Self>;
 	type PostLogContent = PostBlockAndTxnHashes;
 	type ExtraDataLength = ConstU32<30>;
+	type CommitWriteSet = ConstBool<true>;
+	type ProofOrigin = frame_system::EnsureRoot<AccountId>;
+	type PocRetentionPeriod = PocRetentionPeriod;
+	type MaxPocPruneWeight = MaxPocPruneWeight;
+	type ArchivePrunedPoc = ConstBool<true>;
+	type StateKeyOrigin = frame_system::EnsureRoot<AccountId>;
+	type ReencryptionWeightPerByte = ReencryptionWeightPerByte;
+	type MaxViewKeys = ConstU32<16>;
+	type PreLogOrigin = frame_system::EnsureRoot<AccountId>;
 }
 
 parameter_types! {
@@ -657,6 +679,22 @@ impl_runtime_apis! {
 			Ethereum::account_public(address)
 		}
 
+		fn enclave_public_key() -> Option<Vec<u8>> {
+			tp_io::crypto::enclave_public_key()
+		}
+
+		fn non_perceptible_log_data(transaction_hash: H256, log_index: u32) -> Option<Vec<u8>> {
+			pallet_ethereum::NonPerceptibleLogData::<Runtime>::get(transaction_hash, log_index)
+		}
+
+		fn is_view_key_granted(owner: H160, commitment: H256) -> bool {
+			Ethereum::is_view_key_granted(owner, &commitment)
+		}
+
+		fn confidentiality_policy(address: H160) -> tp_rpc::ConfidentialityPolicy {
+			Ethereum::confidentiality_policy(address)
+		}
+
 		fn author() -> H160 {
 			<pallet_evm::Pallet<Runtime>>::find_author()
 		}
@@ -696,22 +734,24 @@ impl_runtime_apis! {
 			);
 			let (weight_limit, proof_size_base_cost) = pallet_ethereum::Pallet::<Runtime>::transaction_weight(&transaction_data);
 
-			<Runtime as pallet_evm::Config>::Runner::call(
-				from,
-				to,
-				data,
-				value,
-				gas_limit.unique_saturated_into(),
-				max_fee_per_gas,
-				max_priority_fee_per_gas,
-				nonce,
-				access_list.unwrap_or_default(),
-				false,
-				true,
-				weight_limit,
-				proof_size_base_cost,
-				config.as_ref().unwrap_or(<Runtime as pallet_evm::Config>::config()),
-			).map_err(|err| err.error.into())
+			pallet_ethereum::Pallet::<Runtime>::with_transaction_origin(from, || {
+				<Runtime as pallet_evm::Config>::Runner::call(
+					from,
+					to,
+					data,
+					value,
+					gas_limit.unique_saturated_into(),
+					max_fee_per_gas,
+					max_priority_fee_per_gas,
+					nonce,
+					access_list.unwrap_or_default(),
+					false,
+					true,
+					weight_limit,
+					proof_size_base_cost,
+					config.as_ref().unwrap_or(<Runtime as pallet_evm::Config>::config()),
+				).map_err(|err| err.error.into())
+			})
 		}
 
 		fn create(
@@ -747,21 +787,23 @@ impl_runtime_apis! {
 			);
 			let (weight_limit, proof_size_base_cost) = pallet_ethereum::Pallet::<Runtime>::transaction_weight(&transaction_data);
 
-			<Runtime as pallet_evm::Config>::Runner::create(
-				from,
-				data,
-				value,
-				gas_limit.unique_saturated_into(),
-				max_fee_per_gas,
-				max_priority_fee_per_gas,
-				nonce,
-				access_list.unwrap_or_default(),
-				false,
-				true,
-				weight_limit,
-				proof_size_base_cost,
-				config.as_ref().unwrap_or(<Runtime as pallet_evm::Config>::config()),
-			).map_err(|err| err.error.into())
+			pallet_ethereum::Pallet::<Runtime>::with_transaction_origin(from, || {
+				<Runtime as pallet_evm::Config>::Runner::create(
+					from,
+					data,
+					value,
+					gas_limit.unique_saturated_into(),
+					max_fee_per_gas,
+					max_priority_fee_per_gas,
+					nonce,
+					access_list.unwrap_or_default(),
+					false,
+					true,
+					weight_limit,
+					proof_size_base_cost,
+					config.as_ref().unwrap_or(<Runtime as pallet_evm::Config>::config()),
+				).map_err(|err| err.error.into())
+			})
 		}
 
 		fn current_transaction_statuses() -> Option<Vec<TransactionStatus>> {
@@ -776,6 +818,10 @@ impl_runtime_apis! {
 			pallet_ethereum::CurrentReceipts::<Runtime>::get()
 		}
 
+		fn current_state_diffs() -> Vec<tp_rpc::StateDiff> {
+			pallet_ethereum::CurrentStateDiffs::<Runtime>::get()
+		}
+
 		fn current_all() -> (
 			Option<pallet_ethereum::Block>,
 			Option<Vec<pallet_ethereum::Receipt>>,
@@ -819,6 +865,52 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl tp_rpc::EthereumTracingRuntimeApi<Block> for Runtime {
+		fn trace_transaction(
+			extrinsics: Vec<<Block as BlockT>::Extrinsic>,
+			transaction_hash: H256,
+			tracer: TracerConfig,
+		) -> Result<TransactionTrace, sp_runtime::DispatchError> {
+			for ext in extrinsics.into_iter() {
+				if let RuntimeCall::Ethereum(transact { transaction }) = &ext.0.function {
+					if transaction.hash() == transaction_hash {
+						return Ethereum::trace_transaction(transaction, tracer);
+					}
+				}
+				let _ = Executive::apply_extrinsic(ext);
+			}
+
+			Err(sp_runtime::DispatchError::Other("Transaction not found in block"))
+		}
+
+		fn trace_block(
+			extrinsics: Vec<<Block as BlockT>::Extrinsic>,
+			tracer: TracerConfig,
+		) -> Vec<(H256, Result<TransactionTrace, sp_runtime::DispatchError>)> {
+			let mut traces = Vec::new();
+			for ext in extrinsics.into_iter() {
+				// The trace rolls its changes back, the extrinsic is then applied for real.
+				if let RuntimeCall::Ethereum(transact { transaction }) = &ext.0.function {
+					traces.push((transaction.hash(), Ethereum::trace_transaction(transaction, tracer)));
+				}
+				let _ = Executive::apply_extrinsic(ext);
+			}
+
+			traces
+		}
+
+		fn trace_call(
+			from: H160,
+			to: Option<H160>,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			tracer: TracerConfig,
+		) -> Result<TransactionTrace, sp_runtime::DispatchError> {
+			Ethereum::trace_call(from, to, data, value, gas_limit, tracer)
+		}
+	}
+
 	impl tp_rpc::ConvertTransactionRuntimeApi<Block> for Runtime {
 		fn convert_transaction(transaction: EthereumTransaction) -> <Block as BlockT>::Extrinsic {
 			UncheckedExtrinsic::new_unsigned(
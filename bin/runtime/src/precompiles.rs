@@ -1,8 +1,11 @@
+use fp_evm::{ExitError, ExitSucceed, PrecompileFailure, PrecompileOutput};
+use frame_support::traits::Get;
 use pallet_evm::{
-	IsPrecompileResult, Precompile, PrecompileHandle, PrecompileResult, PrecompileSet,
+	GasWeightMapping, IsPrecompileResult, Precompile, PrecompileHandle, PrecompileResult,
+	PrecompileSet,
 };
-use sp_core::H160;
-use sp_std::marker::PhantomData;
+use sp_core::{H160, H256};
+use sp_std::{marker::PhantomData, vec::Vec};
 
 use pallet_evm_precompile_modexp::Modexp;
 use pallet_evm_precompile_sha3fips::Sha3FIPS256;
@@ -12,12 +15,12 @@ pub struct FrontierPrecompiles<R>(PhantomData<R>);
 
 impl<R> FrontierPrecompiles<R>
 where
-	R: pallet_evm::Config,
+	R: pallet_ethereum::Config,
 {
 	pub fn new() -> Self {
 		Self(Default::default())
 	}
-	pub fn used_addresses() -> [H160; 7] {
+	pub fn used_addresses() -> [H160; 11] {
 		[
 			hash(1),
 			hash(2),
@@ -26,12 +29,16 @@ where
 			hash(5),
 			hash(1024),
 			hash(1025),
+			hash(1026),
+			hash(1027),
+			hash(1028),
+			hash(1029),
 		]
 	}
 }
 impl<R> PrecompileSet for FrontierPrecompiles<R>
 where
-	R: pallet_evm::Config,
+	R: pallet_ethereum::Config,
 {
 	fn execute(&self, handle: &mut impl PrecompileHandle) -> Option<PrecompileResult> {
 		match handle.code_address() {
@@ -44,6 +51,11 @@ where
 			// Non-Frontier specific nor Ethereum precompiles :
 			a if a == hash(1024) => Some(Sha3FIPS256::execute(handle)),
 			a if a == hash(1025) => Some(ECRecoverPublicKey::execute(handle)),
+			// Tenet specific precompiles :
+			a if a == hash(1026) => Some(PublicKeyRegistry::<R>::execute(handle)),
+			a if a == hash(1027) => Some(TransactionPocReader::<R>::execute(handle)),
+			a if a == hash(1028) => Some(ViewKeyRegistry::<R>::execute(handle)),
+			a if a == hash(1029) => Some(ConfidentialityPolicyRegistry::<R>::execute(handle)),
 			_ => None,
 		}
 	}
@@ -56,6 +68,330 @@ where
 	}
 }
 
+/// Manages the public key of the caller in `pallet_ethereum::AccountPublic`, the key the
+/// confidential data addressed to an account is sealed to.
+///
+/// Accepts the ABI encoded calls `register(bytes pubkey)`, `rotate(bytes newPubkey)`,
+/// `revoke()` and `isRegistered(address account)`, the latter returning an ABI encoded
+/// `bool`. Public keys are the 64 byte uncompressed secp256k1 points without the `0x04` tag.
+/// The registry lives at `0x0000000000000000000000000000000000000402`, address 1026.
+pub struct PublicKeyRegistry<R>(PhantomData<R>);
+
+impl<R: pallet_ethereum::Config> PublicKeyRegistry<R> {
+	const REGISTER_GAS_COST: u64 = 5_000;
+	const ROTATE_GAS_COST: u64 = 5_000;
+	const REVOKE_GAS_COST: u64 = 2_000;
+	const IS_REGISTERED_GAS_COST: u64 = 200;
+	/// `register(bytes)`
+	const REGISTER_SELECTOR: [u8; 4] = [0x82, 0xfb, 0xdc, 0x9c];
+	/// `rotate(bytes)`
+	const ROTATE_SELECTOR: [u8; 4] = [0x85, 0xe0, 0x02, 0x6f];
+	/// `revoke()`
+	const REVOKE_SELECTOR: [u8; 4] = [0xb6, 0x54, 0x9f, 0x75];
+	/// `isRegistered(address)`
+	const IS_REGISTERED_SELECTOR: [u8; 4] = [0xc3, 0xc5, 0xa5, 0x47];
+
+	fn record_cost(
+		handle: &mut impl PrecompileHandle,
+		base: u64,
+		writes: u64,
+	) -> Result<(), PrecompileFailure> {
+		let access_cost = <R as pallet_evm::Config>::GasWeightMapping::weight_to_gas(
+			<R as frame_system::Config>::DbWeight::get().reads_writes(1, writes),
+		);
+		handle.record_cost(base.saturating_add(access_cost))?;
+		Ok(())
+	}
+
+	/// Decode the `bytes` argument of `register` and `rotate`, which must hold a public key.
+	fn public_key(arguments: &[u8]) -> Result<Vec<u8>, PrecompileFailure> {
+		let mut offset = [0u8; 32];
+		offset[31] = 32;
+		let mut length = [0u8; 32];
+		length[31] = 64;
+		if arguments.len() != 32 + 32 + 64
+			|| arguments[..32] != offset
+			|| arguments[32..64] != length
+		{
+			return Err(PrecompileFailure::Error {
+				exit_status: ExitError::Other("argument must be a 64 byte public key".into()),
+			});
+		}
+		Ok(arguments[64..].to_vec())
+	}
+
+	fn ensure_mutable(handle: &impl PrecompileHandle) -> Result<(), PrecompileFailure> {
+		ensure_not_delegated(handle)?;
+		if handle.is_static() {
+			return Err(PrecompileFailure::Error {
+				exit_status: ExitError::Other(
+					"public keys cannot be changed in a static call".into(),
+				),
+			});
+		}
+		Ok(())
+	}
+}
+
+impl<R: pallet_ethereum::Config> Precompile for PublicKeyRegistry<R> {
+	fn execute(handle: &mut impl PrecompileHandle) -> PrecompileResult {
+		let input = handle.input().to_vec();
+		if input.len() < 4 {
+			return Err(PrecompileFailure::Error {
+				exit_status: ExitError::Other("input must start with a selector".into()),
+			});
+		}
+		let (selector, arguments) = input.split_at(4);
+		let caller = handle.context().caller;
+
+		let result = match selector {
+			selector if selector == Self::REGISTER_SELECTOR => {
+				Self::record_cost(handle, Self::REGISTER_GAS_COST, 1)?;
+				Self::ensure_mutable(handle)?;
+				let public_key = Self::public_key(arguments)?;
+				pallet_ethereum::Pallet::<R>::register_public_key(caller, public_key)
+			}
+			selector if selector == Self::ROTATE_SELECTOR => {
+				Self::record_cost(handle, Self::ROTATE_GAS_COST, 1)?;
+				Self::ensure_mutable(handle)?;
+				let public_key = Self::public_key(arguments)?;
+				pallet_ethereum::Pallet::<R>::rotate_public_key(caller, public_key)
+			}
+			selector if selector == Self::REVOKE_SELECTOR => {
+				Self::record_cost(handle, Self::REVOKE_GAS_COST, 1)?;
+				Self::ensure_mutable(handle)?;
+				pallet_ethereum::Pallet::<R>::revoke_public_key(caller)
+			}
+			selector if selector == Self::IS_REGISTERED_SELECTOR => {
+				Self::record_cost(handle, Self::IS_REGISTERED_GAS_COST, 0)?;
+				if arguments.len() != 32 {
+					return Err(PrecompileFailure::Error {
+						exit_status: ExitError::Other("argument must be an address".into()),
+					});
+				}
+				let account = H160::from_slice(&arguments[12..]);
+				let mut output = [0u8; 32];
+				output[31] = pallet_ethereum::Pallet::<R>::is_public_key_registered(account) as u8;
+				return Ok(PrecompileOutput {
+					exit_status: ExitSucceed::Returned,
+					output: output.to_vec(),
+				});
+			}
+			_ => {
+				return Err(PrecompileFailure::Error {
+					exit_status: ExitError::Other("unknown selector".into()),
+				})
+			}
+		};
+		result.map_err(|e| PrecompileFailure::Error {
+			exit_status: ExitError::Other(Into::<&'static str>::into(e).into()),
+		})?;
+
+		Ok(PrecompileOutput {
+			exit_status: ExitSucceed::Returned,
+			output: Vec::new(),
+		})
+	}
+}
+
+/// Reads the PoC of a transaction from `pallet_ethereum::TransactionPoc`.
+///
+/// Input is the 32 byte transaction hash. Output is the ABI encoded `(bytes32 root,
+/// address signer)`, both zero if the transaction has no PoC. The PoC is signed off chain, so
+/// `signer` is the address of the enclave key registered in `pallet_ethereum::PocSigner`.
+pub struct TransactionPocReader<R>(PhantomData<R>);
+
+impl<R: pallet_ethereum::Config> TransactionPocReader<R> {
+	const BASE_GAS_COST: u64 = 200;
+}
+
+impl<R: pallet_ethereum::Config> Precompile for TransactionPocReader<R> {
+	fn execute(handle: &mut impl PrecompileHandle) -> PrecompileResult {
+		let read_cost = <R as pallet_evm::Config>::GasWeightMapping::weight_to_gas(
+			<R as frame_system::Config>::DbWeight::get().reads(2),
+		);
+		handle.record_cost(Self::BASE_GAS_COST.saturating_add(read_cost))?;
+
+		let input = handle.input();
+		if input.len() != 32 {
+			return Err(PrecompileFailure::Error {
+				exit_status: ExitError::Other("input must be a 32 byte transaction hash".into()),
+			});
+		}
+
+		let (root, signer) =
+			pallet_ethereum::Pallet::<R>::transaction_poc_root(H256::from_slice(input))
+				.unwrap_or_default();
+
+		let mut output = Vec::with_capacity(64);
+		output.extend_from_slice(root.as_bytes());
+		output.extend_from_slice(H256::from(signer).as_bytes());
+
+		Ok(PrecompileOutput {
+			exit_status: ExitSucceed::Returned,
+			output,
+		})
+	}
+}
+
+/// Grants and revokes the view keys of the caller in `pallet_ethereum::ViewKeys`.
+///
+/// Accepts the ABI encoded calls `grantViewKey(bytes32 commitment)` and
+/// `revokeViewKey(bytes32 commitment)`, where `commitment` is the keccak256 of the 64-byte
+/// public key of the view key. The holder of the view key proves it by signing view requests.
+pub struct ViewKeyRegistry<R>(PhantomData<R>);
+
+impl<R: pallet_ethereum::Config> ViewKeyRegistry<R> {
+	const BASE_GAS_COST: u64 = 2_000;
+	/// `grantViewKey(bytes32)`
+	const GRANT_SELECTOR: [u8; 4] = [0xae, 0x06, 0x8a, 0x75];
+	/// `revokeViewKey(bytes32)`
+	const REVOKE_SELECTOR: [u8; 4] = [0x68, 0x84, 0x83, 0x0d];
+}
+
+impl<R: pallet_ethereum::Config> Precompile for ViewKeyRegistry<R> {
+	fn execute(handle: &mut impl PrecompileHandle) -> PrecompileResult {
+		let access_cost = <R as pallet_evm::Config>::GasWeightMapping::weight_to_gas(
+			<R as frame_system::Config>::DbWeight::get().reads_writes(1, 1),
+		);
+		handle.record_cost(Self::BASE_GAS_COST.saturating_add(access_cost))?;
+
+		ensure_not_delegated(handle)?;
+		if handle.is_static() {
+			return Err(PrecompileFailure::Error {
+				exit_status: ExitError::Other(
+					"view keys cannot be changed in a static call".into(),
+				),
+			});
+		}
+
+		let input = handle.input();
+		if input.len() != 4 + 32 {
+			return Err(PrecompileFailure::Error {
+				exit_status: ExitError::Other("input must be a selector and a bytes32".into()),
+			});
+		}
+		let commitment = H256::from_slice(&input[4..]);
+		let owner = handle.context().caller;
+
+		let result = match &input[..4] {
+			selector if selector == Self::GRANT_SELECTOR => {
+				pallet_ethereum::Pallet::<R>::grant_view_key(owner, commitment)
+			}
+			selector if selector == Self::REVOKE_SELECTOR => {
+				pallet_ethereum::Pallet::<R>::revoke_view_key(owner, commitment)
+			}
+			_ => {
+				return Err(PrecompileFailure::Error {
+					exit_status: ExitError::Other("unknown selector".into()),
+				})
+			}
+		};
+		result.map_err(|e| PrecompileFailure::Error {
+			exit_status: ExitError::Other(Into::<&'static str>::into(e).into()),
+		})?;
+
+		Ok(PrecompileOutput {
+			exit_status: ExitSucceed::Returned,
+			output: Vec::new(),
+		})
+	}
+}
+
+/// Declares the confidentiality policy of the caller in
+/// `pallet_ethereum::ConfidentialityPolicies`.
+///
+/// Accepts the ABI encoded calls `declare(uint8 policy)`, made by a contract constructor with
+/// `0` for public, `1` for confidential inputs and `2` for confidential state, and
+/// `policyOf(address contract)`, returning the policy as an ABI encoded `uint8`.
+pub struct ConfidentialityPolicyRegistry<R>(PhantomData<R>);
+
+impl<R: pallet_ethereum::Config> ConfidentialityPolicyRegistry<R> {
+	const DECLARE_GAS_COST: u64 = 2_000;
+	const POLICY_OF_GAS_COST: u64 = 200;
+	/// `declare(uint8)`
+	const DECLARE_SELECTOR: [u8; 4] = [0x4b, 0x50, 0xcc, 0xe8];
+	/// `policyOf(address)`
+	const POLICY_OF_SELECTOR: [u8; 4] = [0xf5, 0x14, 0xd6, 0xe5];
+}
+
+impl<R: pallet_ethereum::Config> Precompile for ConfidentialityPolicyRegistry<R> {
+	fn execute(handle: &mut impl PrecompileHandle) -> PrecompileResult {
+		let input = handle.input().to_vec();
+		if input.len() != 4 + 32 {
+			return Err(PrecompileFailure::Error {
+				exit_status: ExitError::Other("input must be a selector and a word".into()),
+			});
+		}
+		let (selector, argument) = input.split_at(4);
+
+		match selector {
+			selector if selector == Self::DECLARE_SELECTOR => {
+				let access_cost = <R as pallet_evm::Config>::GasWeightMapping::weight_to_gas(
+					<R as frame_system::Config>::DbWeight::get().reads_writes(2, 1),
+				);
+				handle.record_cost(Self::DECLARE_GAS_COST.saturating_add(access_cost))?;
+				ensure_not_delegated(handle)?;
+				if handle.is_static() {
+					return Err(PrecompileFailure::Error {
+						exit_status: ExitError::Other(
+							"policies cannot be declared in a static call".into(),
+						),
+					});
+				}
+				let policy = argument[..31]
+					.iter()
+					.all(|byte| *byte == 0)
+					.then(|| pallet_ethereum::ConfidentialityPolicy::from_u8(argument[31]))
+					.flatten()
+					.ok_or_else(|| PrecompileFailure::Error {
+						exit_status: ExitError::Other("unknown policy".into()),
+					})?;
+				pallet_ethereum::Pallet::<R>::declare_confidentiality_policy(
+					handle.context().caller,
+					policy,
+				)
+				.map_err(|e| PrecompileFailure::Error {
+					exit_status: ExitError::Other(Into::<&'static str>::into(e).into()),
+				})?;
+
+				Ok(PrecompileOutput {
+					exit_status: ExitSucceed::Returned,
+					output: Vec::new(),
+				})
+			}
+			selector if selector == Self::POLICY_OF_SELECTOR => {
+				let read_cost = <R as pallet_evm::Config>::GasWeightMapping::weight_to_gas(
+					<R as frame_system::Config>::DbWeight::get().reads(1),
+				);
+				handle.record_cost(Self::POLICY_OF_GAS_COST.saturating_add(read_cost))?;
+				let contract = H160::from_slice(&argument[12..]);
+				let mut output = [0u8; 32];
+				output[31] = pallet_ethereum::Pallet::<R>::confidentiality_policy(contract) as u8;
+
+				Ok(PrecompileOutput {
+					exit_status: ExitSucceed::Returned,
+					output: output.to_vec(),
+				})
+			}
+			_ => Err(PrecompileFailure::Error {
+				exit_status: ExitError::Other("unknown selector".into()),
+			}),
+		}
+	}
+}
+
+/// Reject a `DELEGATECALL` or `CALLCODE` into a registry, which would run it on behalf of
+/// the caller of the delegating contract.
+fn ensure_not_delegated(handle: &impl PrecompileHandle) -> Result<(), PrecompileFailure> {
+	if handle.code_address() != handle.context().address {
+		return Err(PrecompileFailure::Error {
+			exit_status: ExitError::Other("registries cannot be called by delegation".into()),
+		});
+	}
+	Ok(())
+}
+
 fn hash(a: u64) -> H160 {
 	H160::from_low_u64_be(a)
 }
@@ -0,0 +1,172 @@
+use std::{fs, path::PathBuf, sync::Arc};
+
+use scale_codec::{Decode, Encode};
+// Substrate
+use sc_block_builder::BlockBuilderBuilder;
+use sc_cli::{CliConfiguration, ImportParams, SharedParams};
+use sc_consensus::{
+	BlockImport, BlockImportParams, ForkChoiceStrategy, ImportResult, StateAction, StorageChanges,
+};
+use sp_api::{CallApiAt, ProvideRuntimeApi};
+use sp_block_builder::BlockBuilder as BlockBuilderApi;
+use sp_blockchain::HeaderBackend;
+use sp_consensus::BlockOrigin;
+use sp_core::hashing::twox_128;
+use sp_inherents::InherentDataProvider;
+use sp_runtime::{
+	generic::{Digest, DigestItem},
+	traits::{Block as BlockT, Header as HeaderT},
+};
+// Frontier
+use fp_consensus::{PreLog, FRONTIER_ENGINE_ID};
+use fp_storage::{ETHEREUM_CURRENT_TRANSACTION_STATUS, PALLET_ETHEREUM};
+// Tenet
+use tp_ethereum::BlockV2 as EthereumBlock;
+use tp_rpc::TransactionStatus;
+
+/// Import Ethereum blocks as pre-logs, replaying their transactions to migrate the state of
+/// another EVM chain.
+///
+/// Each Ethereum block is executed by a new block on top of the best block. Blocks are
+/// imported without a consensus seal, so the command is meant to bootstrap a chain before its
+/// authorities start. Import stops before the first Ethereum block whose transactions the
+/// runtime rolls back, which includes transactions signed for another chain than the one set
+/// by `set_pre_log_source_chain_id`.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct ImportEthereumBlocksCmd {
+	/// Path to a file of concatenated RLP encoded Ethereum blocks, as written by `geth export`.
+	#[arg(long, required = true)]
+	pub input: PathBuf,
+
+	#[allow(missing_docs)]
+	#[command(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[command(flatten)]
+	pub import_params: ImportParams,
+}
+
+impl ImportEthereumBlocksCmd {
+	/// `inherent_data_providers` provides the inherents of the block executing an Ethereum
+	/// block, such as a timestamp taken from the Ethereum block.
+	pub async fn run<B, C, BI, F, IDP>(
+		&self,
+		client: Arc<C>,
+		mut block_import: BI,
+		inherent_data_providers: F,
+	) -> sc_cli::Result<()>
+	where
+		B: BlockT,
+		C: CallApiAt<B> + ProvideRuntimeApi<B> + HeaderBackend<B>,
+		C::Api: BlockBuilderApi<B>,
+		BI: BlockImport<B>,
+		F: Fn(&EthereumBlock) -> IDP,
+		IDP: InherentDataProvider,
+	{
+		for ethereum_block in decode_blocks(&fs::read(&self.input)?)? {
+			let number = ethereum_block.header.number;
+			let transactions = ethereum_block.transactions.len();
+			let inherent_data = inherent_data_providers(&ethereum_block)
+				.create_inherent_data()
+				.await
+				.map_err(|e| format!("Ethereum block {}: invalid inherent data: {}", number, e))?;
+
+			let chain = client.info();
+			let mut builder = BlockBuilderBuilder::new(&*client)
+				.on_parent_block(chain.best_hash)
+				.with_parent_block_number(chain.best_number)
+				.with_inherent_digests(Digest {
+					logs: vec![DigestItem::PreRuntime(
+						FRONTIER_ENGINE_ID,
+						PreLog::Block(ethereum_block).encode(),
+					)],
+				})
+				.build()?;
+			for extrinsic in builder.create_inherents(inherent_data)? {
+				builder.push(extrinsic)?;
+			}
+			let built = builder.build()?;
+
+			// A rejected pre-log leaves the block without Ethereum transactions, so it is not
+			// imported.
+			let applied = applied_transactions(&built.storage_changes.main_storage_changes);
+			if applied != transactions {
+				return Err(format!(
+					"Ethereum block {} rejected, {} of its {} transactions were applied",
+					number, applied, transactions
+				)
+				.into());
+			}
+
+			let (header, body) = built.block.deconstruct();
+			let hash = header.hash();
+			let mut params = BlockImportParams::new(BlockOrigin::File, header);
+			params.body = Some(body);
+			params.state_action =
+				StateAction::ApplyChanges(StorageChanges::Changes(built.storage_changes));
+			params.fork_choice = Some(ForkChoiceStrategy::LongestChain);
+			match block_import.import_block(params).await {
+				Ok(ImportResult::Imported(_)) => {}
+				result => {
+					return Err(
+						format!("Ethereum block {}: import failed: {:?}", number, result).into(),
+					)
+				}
+			}
+
+			println!(
+				"Imported Ethereum block {} with {} transactions in block {:?}",
+				number, transactions, hash
+			);
+		}
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ImportEthereumBlocksCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+}
+
+/// The number of Ethereum transactions executed by a built block, read from its storage
+/// changes since the block is not imported yet.
+fn applied_transactions(changes: &[(Vec<u8>, Option<Vec<u8>>)]) -> usize {
+	let key = [
+		twox_128(PALLET_ETHEREUM),
+		twox_128(ETHEREUM_CURRENT_TRANSACTION_STATUS),
+	]
+	.concat();
+	changes
+		.iter()
+		.find(|(changed, _)| *changed == key)
+		.and_then(|(_, value)| value.as_deref())
+		.and_then(|mut value| Vec::<TransactionStatus>::decode(&mut value).ok())
+		.map_or(0, |statuses| statuses.len())
+}
+
+fn decode_blocks(bytes: &[u8]) -> sc_cli::Result<Vec<EthereumBlock>> {
+	let mut blocks = Vec::new();
+	let mut offset = 0;
+	while offset < bytes.len() {
+		let info = rlp::Rlp::new(&bytes[offset..])
+			.payload_info()
+			.map_err(|e| format!("Invalid block at byte {}: {:?}", offset, e))?;
+		let end = offset + info.header_len + info.value_len;
+		let item = bytes
+			.get(offset..end)
+			.ok_or_else(|| format!("Truncated block at byte {}", offset))?;
+		blocks.push(
+			rlp::decode(item).map_err(|e| format!("Invalid block at byte {}: {:?}", offset, e))?,
+		);
+		offset = end;
+	}
+
+	Ok(blocks)
+}
@@ -19,5 +19,11 @@
 #![warn(unused_crate_dependencies)]
 
 mod frontier_db_cmd;
+mod import_ethereum_blocks_cmd;
+mod rotate_state_key_cmd;
+mod verify_cmd;
 
-pub use self::frontier_db_cmd::FrontierDbCmd;
+pub use self::{
+	frontier_db_cmd::FrontierDbCmd, import_ethereum_blocks_cmd::ImportEthereumBlocksCmd,
+	rotate_state_key_cmd::RotateStateKeyCmd, verify_cmd::VerifyCmd,
+};
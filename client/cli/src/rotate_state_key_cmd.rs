@@ -0,0 +1,73 @@
+// Substrate
+use sc_cli::{CliConfiguration, KeystoreParams, SharedParams, SubstrateCli};
+use sc_keystore::LocalKeystore;
+use sc_service::config::{BasePath, KeystoreConfig};
+use sp_core::{crypto::Pair as _, ecdsa};
+use sp_keystore::Keystore;
+// Tenet
+use tp_io::{state_key, STATE_KEY_TYPE};
+
+/// Add a new state master key to the keystore.
+///
+/// Register the printed id on chain with the `register_state_key` call, along with the accounts
+/// of the nodes that must hold it. Every such node reports the key with `report_state_key`
+/// once it was restarted to load it, after which the `reencrypt_state` call switches to it: new
+/// state is encrypted under it from the next block on and the existing state is re-encrypted in
+/// the background. State under the previous keys stays readable meanwhile, so the previous keys
+/// must remain in the keystore until re-encryption completes.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct RotateStateKeyCmd {
+	/// Secret URI of the new key. A random key is generated when omitted.
+	#[arg(long)]
+	pub suri: Option<String>,
+
+	#[allow(missing_docs)]
+	#[command(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[command(flatten)]
+	pub keystore_params: KeystoreParams,
+}
+
+impl RotateStateKeyCmd {
+	pub fn run<C: SubstrateCli>(&self, cli: &C) -> sc_cli::Result<()> {
+		let base_path = self
+			.shared_params
+			.base_path()?
+			.unwrap_or_else(|| BasePath::from_project("", "", &C::executable_name()));
+		let chain_id = self.shared_params.chain_id(self.shared_params.is_dev());
+		let chain_spec = cli.load_spec(&chain_id)?;
+		let config_dir = base_path.config_dir(chain_spec.id());
+
+		let keystore = match self.keystore_params.keystore_config(&config_dir)? {
+			KeystoreConfig::Path { path, password } => {
+				LocalKeystore::open(path, password).map_err(|e| format!("{:?}", e))?
+			}
+			_ => unreachable!("keystore_config always returns path and password; qed"),
+		};
+
+		let suri = match &self.suri {
+			Some(suri) => suri.clone(),
+			None => ecdsa::Pair::generate_with_phrase(None).1,
+		};
+		let pair = ecdsa::Pair::from_string(&suri, None)
+			.map_err(|e| format!("Invalid secret URI: {:?}", e))?;
+		keystore
+			.insert(STATE_KEY_TYPE, &suri, pair.public().as_ref())
+			.map_err(|_| "Failed to insert the state master key")?;
+
+		println!("State master key id: {}", state_key::key_id(&pair.seed()));
+		Ok(())
+	}
+}
+
+impl CliConfiguration for RotateStateKeyCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn keystore_params(&self) -> Option<&KeystoreParams> {
+		Some(&self.keystore_params)
+	}
+}
@@ -0,0 +1,108 @@
+use std::{fs, io, path::PathBuf};
+
+use ethereum_types::H256;
+use serde::Deserialize;
+use serde_json::Deserializer;
+// Substrate
+use sp_core::Bytes;
+// Tenet
+use tp_ethereum::{EnvelopedDecodable, Receipt, TransactionV2 as Transaction};
+use tp_verifier::{LogProof, PoC};
+
+/// Verify exported proofs of computation without running a node.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct VerifyCmd {
+	/// Path to the exported proofs.
+	///
+	/// The file holds one or more JSON objects with the hex encoded `poc`, `signer`,
+	/// `transaction` and `receipt`, an optional `writeSetDigest` and optional `logProofs`.
+	/// Reads from stdin when omitted.
+	#[arg(long)]
+	pub input: Option<PathBuf>,
+}
+
+/// A single exported proof.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportedProof {
+	/// RLP encoded PoC.
+	poc: Bytes,
+	/// Serialized secp256k1 public key of the node that signed the PoC.
+	signer: Bytes,
+	/// EIP-2718 encoded transaction.
+	transaction: Bytes,
+	/// Type prefixed RLP encoded receipt, including its logs.
+	receipt: Bytes,
+	write_set_digest: Option<H256>,
+	#[serde(default)]
+	log_proofs: Vec<ExportedLogProof>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExportedLogProof {
+	index: usize,
+	proof: Vec<Bytes>,
+}
+
+impl VerifyCmd {
+	pub fn run(&self) -> sc_cli::Result<()> {
+		let proofs = match &self.input {
+			Some(filename) => parse_proofs(fs::File::open(filename)?)?,
+			None => parse_proofs(io::stdin())?,
+		};
+
+		let mut all_valid = true;
+		for (i, proof) in proofs.iter().enumerate() {
+			let poc: PoC = rlp::decode(&proof.poc)
+				.map_err(|e| format!("Proof {}: invalid poc: {:?}", i, e))?;
+			let transaction = Transaction::decode(&proof.transaction)
+				.map_err(|e| format!("Proof {}: invalid transaction: {:?}", i, e))?;
+			let receipt = decode_receipt(&proof.receipt)
+				.map_err(|e| format!("Proof {}: invalid receipt: {:?}", i, e))?;
+			let log_proofs = proof
+				.log_proofs
+				.iter()
+				.map(|p| LogProof {
+					index: p.index,
+					proof: p.proof.iter().map(|node| node.to_vec()).collect(),
+				})
+				.collect::<Vec<_>>();
+
+			let report = tp_verifier::verify(
+				&poc,
+				&proof.signer,
+				&transaction,
+				&receipt,
+				proof.write_set_digest,
+				&log_proofs,
+			);
+
+			println!("Proof {} for transaction {:?}:", i, transaction.hash());
+			for (check, passed) in &report.checks {
+				println!("  {:?}: {}", check, if *passed { "ok" } else { "FAILED" });
+			}
+			all_valid &= report.is_valid();
+		}
+
+		if all_valid {
+			Ok(())
+		} else {
+			Err("Proof verification failed".into())
+		}
+	}
+}
+
+fn parse_proofs<I: io::Read>(input: I) -> sc_cli::Result<Vec<ExportedProof>> {
+	Deserializer::from_reader(input)
+		.into_iter::<ExportedProof>()
+		.collect::<Result<Vec<_>, _>>()
+		.map_err(|e| format!("Failed to deserialize proofs: {}", e).into())
+}
+
+fn decode_receipt(bytes: &[u8]) -> Result<Receipt, rlp::DecoderError> {
+	match bytes.first() {
+		Some(0x01) => rlp::decode(&bytes[1..]).map(Receipt::EIP2930),
+		Some(0x02) => rlp::decode(&bytes[1..]).map(Receipt::EIP1559),
+		_ => rlp::decode(bytes).map(Receipt::Legacy),
+	}
+}
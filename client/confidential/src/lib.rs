@@ -0,0 +1,421 @@
+//! Client side of Tenet confidential transactions.
+//!
+//! Wallets and dapps use this crate to seal payloads to the enclave key of a node, as published
+//! by `EthereumRuntimeRPCApi::enclave_public_key`, so that plaintexts never leave the client:
+//!
+//! - [`ConfidentialAccount::sign_transaction`] builds a signed EIP-1559 transaction whose
+//!   payload is encrypted to the enclave key, ready for `eth_sendRawTransaction`, and
+//!   [`ConfidentialAccount::seal_transaction_input`] seals the input of a Legacy or EIP-2930
+//!   transaction before it is signed, once the account registered its public key;
+//! - [`ConfidentialAccount::seal_call_request`] seals a call request for `tenet_call` and
+//!   `tenet_estimateGas`, and [`ConfidentialAccount::open_call_response`] opens their results;
+//! - [`ConfidentialAccount::open_balance`] opens the balance served by
+//!   `tenet_getEncryptedBalance`, and [`ConfidentialAccount::open_log_data`] the data of the
+//!   non-perceptible logs served by `tenet_getTransactionReceipt`.
+//!
+//! Payloads are sealed in the [`tp_envelope`] format the node decrypts, under the ECDH key of
+//! the account and the enclave key. The crate only has pure Rust dependencies and builds for
+//! `wasm32-unknown-unknown`.
+
+use aes_gcm::{
+	aead::{AeadCore, OsRng},
+	Aes256Gcm,
+};
+use ethereum::{
+	EIP1559Transaction, EIP1559TransactionMessage, EnvelopedEncodable, TransactionMethod,
+	TransactionV2, UniversalTransaction,
+};
+use ethereum_types::{H160, H256, H512, U256};
+use libsecp256k1::{Message, PublicKey, SecretKey, SharedSecret};
+use sha3::{Digest, Keccak256};
+
+pub use tp_envelope::Envelope;
+
+/// Errors of the client side sealing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+	/// The secret key of the account is invalid.
+	InvalidSecretKey,
+	/// The enclave public key is not a valid secp256k1 point.
+	InvalidEnclaveKey,
+	/// The transaction message cannot be signed.
+	InvalidMessage,
+	/// The payload cannot be sealed.
+	Encryption,
+	/// The payload is not an envelope sealed to this account.
+	Decryption,
+}
+
+/// An account sending confidential transactions.
+#[derive(Clone)]
+pub struct ConfidentialAccount {
+	secret: SecretKey,
+}
+
+impl ConfidentialAccount {
+	/// Build the account from its 32-byte secret key.
+	pub fn from_secret(secret: &[u8; 32]) -> Result<Self, Error> {
+		SecretKey::parse(secret)
+			.map(|secret| Self { secret })
+			.map_err(|_| Error::InvalidSecretKey)
+	}
+
+	/// Uncompressed public key without the `0x04` tag.
+	pub fn public_key(&self) -> [u8; 64] {
+		let mut public = [0u8; 64];
+		public.copy_from_slice(&PublicKey::from_secret_key(&self.secret).serialize()[1..]);
+		public
+	}
+
+	pub fn address(&self) -> H160 {
+		H160::from_slice(&keccak_256(&self.public_key())[12..])
+	}
+
+	/// ECDH between the account key and the enclave key, the AES-256 key of every payload
+	/// exchanged with the node.
+	pub fn shared_key(&self, enclave_public: &[u8; 64]) -> Result<[u8; 32], Error> {
+		let mut tagged_full = [0u8; 65];
+		tagged_full[0] = 0x04;
+		tagged_full[1..].copy_from_slice(enclave_public);
+		let enclave_public =
+			PublicKey::parse(&tagged_full).map_err(|_| Error::InvalidEnclaveKey)?;
+		let shared = SharedSecret::<sha2::Sha256>::new(&enclave_public, &self.secret)
+			.map_err(|_| Error::InvalidEnclaveKey)?;
+		let mut key = [0u8; 32];
+		key.copy_from_slice(shared.as_ref());
+		Ok(key)
+	}
+
+	/// Seal `msg` to the enclave key under a fresh random nonce.
+	pub fn seal(
+		&self,
+		enclave_public: &[u8; 64],
+		msg: &[u8],
+		aad: &[u8],
+	) -> Result<Vec<u8>, Error> {
+		let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+		tp_envelope::seal(&self.shared_key(enclave_public)?, nonce.into(), msg, aad)
+			.map(|envelope| envelope.to_bytes())
+			.ok_or(Error::Encryption)
+	}
+
+	/// Open an envelope sealed to this account by the node.
+	pub fn open(
+		&self,
+		enclave_public: &[u8; 64],
+		sealed: &[u8],
+		aad: &[u8],
+	) -> Result<Vec<u8>, Error> {
+		tp_envelope::open(&self.shared_key(enclave_public)?, sealed, aad).ok_or(Error::Decryption)
+	}
+
+	/// Open the balance of this account returned by `tenet_getEncryptedBalance`.
+	pub fn open_balance(&self, enclave_public: &[u8; 64], sealed: &[u8]) -> Result<U256, Error> {
+		let balance = self.open(
+			enclave_public,
+			sealed,
+			&keccak_256(self.address().as_bytes()),
+		)?;
+		if balance.len() != 32 {
+			return Err(Error::Decryption);
+		}
+		Ok(U256::from_big_endian(&balance))
+	}
+
+	/// Open the data of a non-perceptible log of the transaction `transaction_hash` returned by
+	/// `tenet_getTransactionReceipt`.
+	pub fn open_log_data(
+		&self,
+		enclave_public: &[u8; 64],
+		transaction_hash: H256,
+		sealed: &[u8],
+	) -> Result<Vec<u8>, Error> {
+		self.open(enclave_public, sealed, transaction_hash.as_bytes())
+	}
+
+	/// Sign `message` and encrypt its payload to the enclave key. The node submits such
+	/// transactions as they are.
+	pub fn sign_transaction(
+		&self,
+		message: EIP1559TransactionMessage,
+		enclave_public: &[u8; 64],
+	) -> Result<TransactionV2, Error> {
+		let signing_message =
+			Message::parse_slice(&message.hash()[..]).map_err(|_| Error::InvalidMessage)?;
+		let (signature, recid) = libsecp256k1::sign(&signing_message, &self.secret);
+		let rs = signature.serialize();
+		let transaction = TransactionV2::EIP1559(EIP1559Transaction {
+			chain_id: message.chain_id,
+			nonce: message.nonce,
+			method: TransactionMethod::Universal(UniversalTransaction {
+				max_priority_fee_per_gas: message.max_priority_fee_per_gas,
+				max_fee_per_gas: message.max_fee_per_gas,
+				gas_limit: message.gas_limit,
+				action: message.action,
+				value: message.value,
+				input: message.input,
+				access_list: message.access_list,
+			}),
+			odd_y_parity: recid.serialize() != 0,
+			r: H256::from_slice(&rs[0..32]),
+			s: H256::from_slice(&rs[32..64]),
+		});
+		transaction
+			.encrypt(|msg, aad| {
+				self.seal(enclave_public, msg, aad.as_bytes())
+					.map_err(|_| ethereum::Error::BadEncrypte)
+			})
+			.map_err(|_| Error::Encryption)
+	}
+
+	/// Seal the input of a Legacy or EIP-2930 transaction of `chain_id` and `nonce` to the
+	/// enclave key. Their signature covers the input, so it is sealed before the transaction
+	/// is signed. The node only opens it when this account registered its public key.
+	pub fn seal_transaction_input(
+		&self,
+		enclave_public: &[u8; 64],
+		chain_id: Option<u64>,
+		nonce: U256,
+		input: &[u8],
+	) -> Result<Vec<u8>, Error> {
+		self.seal(
+			enclave_public,
+			input,
+			&tp_envelope::sealed_input_aad(chain_id, nonce.low_u128()),
+		)
+	}
+
+	/// Seal the JSON encoded call request `request` for `tenet_call` or `tenet_estimateGas`,
+	/// returning the `publicKey` and `payload` fields of the encrypted request.
+	pub fn seal_call_request(
+		&self,
+		enclave_public: &[u8; 64],
+		request: &[u8],
+	) -> Result<(H512, Vec<u8>), Error> {
+		let public_key = self.public_key();
+		let payload = self.seal(enclave_public, request, &keccak_256(&public_key))?;
+		Ok((H512::from(public_key), payload))
+	}
+
+	/// Sign the challenge authenticating a `tenet_getTransactionReceipt` request for
+	/// `transaction_hash`, valid until the unix time `expiry`. The signature is the
+	/// `personal_sign` of [`receipt_challenge`].
+	pub fn sign_receipt_challenge(
+		&self,
+		transaction_hash: H256,
+		expiry: u64,
+	) -> Result<[u8; 65], Error> {
+		self.personal_sign(receipt_challenge(transaction_hash, expiry))
+	}
+
+	/// Sign, with this account as a view key, the challenge authenticating a
+	/// `tenet_viewTransaction` request for `transaction_hash`, valid until the unix time
+	/// `expiry`. The signature is the `personal_sign` of [`view_challenge`].
+	pub fn sign_view_challenge(
+		&self,
+		transaction_hash: H256,
+		expiry: u64,
+	) -> Result<[u8; 65], Error> {
+		self.personal_sign(view_challenge(transaction_hash, expiry))
+	}
+
+	/// The commitment under which the owner of confidential transactions grants this account
+	/// as a view key: the keccak256 of its public key.
+	pub fn view_key_commitment(&self) -> H256 {
+		H256(keccak_256(&self.public_key()))
+	}
+
+	fn personal_sign(&self, digest: H256) -> Result<[u8; 65], Error> {
+		let message = Message::parse_slice(digest.as_bytes()).map_err(|_| Error::InvalidMessage)?;
+		let (signature, recid) = libsecp256k1::sign(&message, &self.secret);
+		let mut signed = [0u8; 65];
+		signed[..64].copy_from_slice(&signature.serialize());
+		signed[64] = 27 + recid.serialize();
+		Ok(signed)
+	}
+
+	/// Open the result of a call request sealed as `payload`.
+	pub fn open_call_response(
+		&self,
+		enclave_public: &[u8; 64],
+		payload: &[u8],
+		response: &[u8],
+	) -> Result<Vec<u8>, Error> {
+		self.open(enclave_public, response, &keccak_256(payload))
+	}
+}
+
+/// The digest signed to authenticate a receipt request: the EIP-191 hash of
+/// `keccak256("tenet-receipt" ++ transaction_hash ++ expiry)`, `expiry` being big endian.
+pub fn receipt_challenge(transaction_hash: H256, expiry: u64) -> H256 {
+	challenge(b"tenet-receipt", transaction_hash, expiry)
+}
+
+/// The digest signed by a view key to authenticate a view request: the EIP-191 hash of
+/// `keccak256("tenet-view" ++ transaction_hash ++ expiry)`, `expiry` being big endian.
+pub fn view_challenge(transaction_hash: H256, expiry: u64) -> H256 {
+	challenge(b"tenet-view", transaction_hash, expiry)
+}
+
+fn challenge(domain: &[u8], transaction_hash: H256, expiry: u64) -> H256 {
+	let challenge =
+		keccak_256(&[domain, transaction_hash.as_bytes(), &expiry.to_be_bytes()].concat());
+	H256(keccak_256(
+		&[&b"\x19Ethereum Signed Message:\n32"[..], &challenge].concat(),
+	))
+}
+
+/// The raw transaction bytes expected by `eth_sendRawTransaction`.
+pub fn encode_transaction(transaction: &TransactionV2) -> Vec<u8> {
+	transaction.encode().to_vec()
+}
+
+fn keccak_256(data: &[u8]) -> [u8; 32] {
+	Keccak256::digest(data).into()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tp_io::EnclaveKey;
+
+	const ENCLAVE_SEED: [u8; 32] = [0x57; 32];
+
+	#[test]
+	fn node_opens_client_envelopes() {
+		let enclave = EnclaveKey::from_seed(&ENCLAVE_SEED).unwrap();
+		let account = ConfidentialAccount::from_secret(&[0x11; 32]).unwrap();
+		let aad = keccak_256(b"aad");
+
+		let sealed = account
+			.seal(&enclave.public(), b"hello world", &aad)
+			.unwrap();
+		assert_eq!(
+			enclave
+				.decrypt(&sealed, &aad, &account.public_key())
+				.unwrap(),
+			b"hello world"
+		);
+
+		let response = enclave
+			.encrypt(b"output", &aad, &account.public_key())
+			.unwrap();
+		assert_eq!(
+			account.open(&enclave.public(), &response, &aad).unwrap(),
+			b"output"
+		);
+		assert_eq!(
+			account.open(&enclave.public(), &response, &[0u8; 32]),
+			Err(Error::Decryption)
+		);
+	}
+
+	#[test]
+	fn node_opens_sealed_transaction_inputs() {
+		let enclave = EnclaveKey::from_seed(&ENCLAVE_SEED).unwrap();
+		let account = ConfidentialAccount::from_secret(&[0x11; 32]).unwrap();
+
+		let sealed = account
+			.seal_transaction_input(&enclave.public(), Some(42), U256::from(3), b"input")
+			.unwrap();
+		assert_eq!(
+			enclave
+				.decrypt(
+					&sealed,
+					&tp_envelope::sealed_input_aad(Some(42), 3),
+					&account.public_key()
+				)
+				.unwrap(),
+			b"input"
+		);
+		// The input is bound to the nonce of its transaction.
+		assert!(enclave
+			.decrypt(
+				&sealed,
+				&tp_envelope::sealed_input_aad(Some(42), 4),
+				&account.public_key()
+			)
+			.is_err());
+	}
+
+	#[test]
+	fn balance_opens() {
+		let enclave = EnclaveKey::from_seed(&ENCLAVE_SEED).unwrap();
+		let account = ConfidentialAccount::from_secret(&[0x11; 32]).unwrap();
+		let mut balance = [0u8; 32];
+		U256::from(1_000_000u64).to_big_endian(&mut balance);
+
+		let sealed = enclave
+			.encrypt(
+				&balance,
+				&keccak_256(account.address().as_bytes()),
+				&account.public_key(),
+			)
+			.unwrap();
+		assert_eq!(
+			account.open_balance(&enclave.public(), &sealed),
+			Ok(U256::from(1_000_000u64))
+		);
+	}
+
+	#[test]
+	fn log_data_opens() {
+		let enclave = EnclaveKey::from_seed(&ENCLAVE_SEED).unwrap();
+		let account = ConfidentialAccount::from_secret(&[0x11; 32]).unwrap();
+		let transaction_hash = H256::repeat_byte(0x22);
+
+		let sealed = enclave
+			.encrypt(
+				b"log data",
+				transaction_hash.as_fixed_bytes(),
+				&account.public_key(),
+			)
+			.unwrap();
+		assert_eq!(
+			account.open_log_data(&enclave.public(), transaction_hash, &sealed),
+			Ok(b"log data".to_vec())
+		);
+		assert_eq!(
+			account.open_log_data(&enclave.public(), H256::zero(), &sealed),
+			Err(Error::Decryption)
+		);
+	}
+
+	#[test]
+	fn view_challenge_recovers_the_view_key_commitment() {
+		let view_key = ConfidentialAccount::from_secret(&[0x33; 32]).unwrap();
+		let transaction_hash = H256::repeat_byte(0x22);
+		let signed = view_key
+			.sign_view_challenge(transaction_hash, 1_700_000_000)
+			.unwrap();
+
+		let message =
+			Message::parse_slice(view_challenge(transaction_hash, 1_700_000_000).as_bytes())
+				.unwrap();
+		let signature = libsecp256k1::Signature::parse_standard_slice(&signed[..64]).unwrap();
+		let recid = libsecp256k1::RecoveryId::parse_rpc(signed[64]).unwrap();
+		let public = libsecp256k1::recover(&message, &signature, &recid).unwrap();
+		assert_eq!(
+			H256(keccak_256(&public.serialize()[1..])),
+			view_key.view_key_commitment()
+		);
+		// A receipt challenge of the same transaction is a different message.
+		assert_ne!(
+			view_challenge(transaction_hash, 1_700_000_000),
+			receipt_challenge(transaction_hash, 1_700_000_000)
+		);
+	}
+
+	#[test]
+	fn address_matches_public_key() {
+		let account = ConfidentialAccount::from_secret(&[0x11; 32]).unwrap();
+		// The well-known address of the `0x11..11` development key.
+		assert_eq!(
+			account.address(),
+			H160::from_slice(&[
+				0x19, 0xe7, 0xe3, 0x76, 0xe7, 0xc2, 0x13, 0xb7, 0xe7, 0xe7, 0xe4, 0x6c, 0xc7, 0x0a,
+				0x5d, 0xd0, 0x86, 0xda, 0xff, 0x2a,
+			])
+		);
+	}
+}
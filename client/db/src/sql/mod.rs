@@ -42,6 +42,8 @@ use fc_storage::OverrideHandle;
 use fp_consensus::{FindLogError, Hashes, Log as ConsensusLog, PostLog, PreLog};
 use fp_rpc::EthereumRuntimeRPCApi;
 use fp_storage::{EthereumStorageSchema, PALLET_ETHEREUM_SCHEMA};
+// Tenet
+use tp_rpc::FlatCallFrame;
 
 /// Maximum number to topics allowed to be filtered upon
 const MAX_TOPIC_COUNT: u16 = 4;
@@ -59,6 +61,17 @@ pub struct Log {
 	pub substrate_block_hash: Vec<u8>,
 }
 
+/// Represents an indexed call trace, with the transaction and block it belongs to.
+#[derive(Debug, Eq, PartialEq)]
+pub struct FilteredTrace {
+	pub substrate_block_hash: H256,
+	pub ethereum_block_hash: H256,
+	pub block_number: u32,
+	pub transaction_hash: H256,
+	pub transaction_index: u32,
+	pub trace: FlatCallFrame,
+}
+
 /// Represents the block metadata.
 #[derive(Eq, PartialEq)]
 struct BlockMetadata {
@@ -701,6 +714,166 @@ where
 		))
 	}
 
+	/// Index the flattened call traces of a block, given per transaction in block order.
+	/// Transactions without a trace are skipped but the block is marked as traced.
+	pub async fn index_block_traces(
+		&self,
+		block_hash: H256,
+		traces: Vec<(H256, Vec<FlatCallFrame>)>,
+	) -> Result<(), Error> {
+		let mut tx = self.pool().begin().await?;
+		for (transaction_index, (transaction_hash, calls)) in traces.into_iter().enumerate() {
+			for call in calls {
+				sqlx::query(
+					"INSERT OR IGNORE INTO traces(
+						substrate_block_hash,
+						transaction_hash,
+						transaction_index,
+						trace_address,
+						from_address,
+						to_address,
+						trace)
+					VALUES (?, ?, ?, ?, ?, ?, ?)",
+				)
+				.bind(block_hash.as_bytes())
+				.bind(transaction_hash.as_bytes())
+				.bind(transaction_index as i32)
+				.bind(call.trace_address.encode())
+				.bind(call.from.as_bytes())
+				.bind(call.to.as_bytes())
+				.bind(call.encode())
+				.execute(&mut *tx)
+				.await?;
+			}
+		}
+
+		sqlx::query(
+			"INSERT OR IGNORE INTO trace_status(substrate_block_hash, status) VALUES (?, 1)",
+		)
+		.bind(block_hash.as_bytes())
+		.execute(&mut *tx)
+		.await?;
+
+		log::debug!(target: "frontier-sql", "[Traces] Ready to commit");
+		tx.commit().await
+	}
+
+	/// Mark a block whose runtime has no tracing API, so that it is not retried. The block gets
+	/// no call traces.
+	pub async fn mark_untraceable_block(&self, block_hash: H256) -> Result<(), Error> {
+		sqlx::query(
+			"INSERT OR IGNORE INTO trace_status(substrate_block_hash, status) VALUES (?, 0)",
+		)
+		.bind(block_hash.as_bytes())
+		.execute(self.pool())
+		.await
+		.map(|_| ())
+	}
+
+	/// Retrieves the first canonical block with Ethereum transactions, in increasing order,
+	/// that hasn't had its traces indexed yet.
+	pub async fn get_first_untraced_canon_block(&self) -> Option<H256> {
+		match sqlx::query(
+			"SELECT b.substrate_block_hash FROM blocks AS b
+			WHERE b.is_canon = 1
+			AND EXISTS (
+				SELECT 1 FROM transactions AS t
+				WHERE t.substrate_block_hash = b.substrate_block_hash
+			)
+			AND NOT EXISTS (
+				SELECT 1 FROM trace_status AS s
+				WHERE s.substrate_block_hash = b.substrate_block_hash
+			)
+			ORDER BY b.block_number LIMIT 1",
+		)
+		.fetch_optional(self.pool())
+		.await
+		{
+			Ok(result) => {
+				if let Some(row) = result {
+					let block_hash_bytes: Vec<u8> = row.first();
+					return Some(H256::from_slice(&block_hash_bytes[..]));
+				}
+			}
+			Err(err) => {
+				log::debug!(target: "frontier-sql", "Failed retrieving untraced block {err:?}");
+			}
+		}
+
+		None
+	}
+
+	/// Retrieves the indexed call traces of canonical blocks in the given range, matching any
+	/// of `from_addresses` and any of `to_addresses`. Empty address lists match any address.
+	/// The first `offset` matching traces are skipped and at most `limit` are returned.
+	pub async fn filter_traces(
+		&self,
+		from_block: u64,
+		to_block: u64,
+		from_addresses: Vec<H160>,
+		to_addresses: Vec<H160>,
+		offset: usize,
+		limit: Option<usize>,
+	) -> Result<Vec<FilteredTrace>, String> {
+		let mut qb = QueryBuilder::new("");
+		build_trace_query(
+			&mut qb,
+			TraceQuery::Range {
+				from_block,
+				to_block,
+				from_addresses,
+				to_addresses,
+				offset,
+				limit,
+			},
+		);
+		self.fetch_traces(qb).await
+	}
+
+	/// Retrieves the indexed call traces of a transaction in its canonical block.
+	pub async fn transaction_traces(
+		&self,
+		transaction_hash: H256,
+	) -> Result<Vec<FilteredTrace>, String> {
+		let mut qb = QueryBuilder::new("");
+		build_trace_query(&mut qb, TraceQuery::Transaction(transaction_hash));
+		self.fetch_traces(qb).await
+	}
+
+	async fn fetch_traces(
+		&self,
+		mut qb: QueryBuilder<'_, Sqlite>,
+	) -> Result<Vec<FilteredTrace>, String> {
+		let rows = qb
+			.build()
+			.fetch_all(self.pool())
+			.await
+			.map_err(|err| format!("Failed to query sql db: {err:?}"))?;
+
+		rows.iter()
+			.map(|row| {
+				let trace = FlatCallFrame::decode(
+					&mut &row.try_get::<Vec<u8>, _>(5).unwrap_or_default()[..],
+				)
+				.map_err(|_| "Cannot decode call trace".to_string())?;
+				Ok(FilteredTrace {
+					substrate_block_hash: H256::from_slice(
+						&row.try_get::<Vec<u8>, _>(0).unwrap_or_default()[..],
+					),
+					ethereum_block_hash: H256::from_slice(
+						&row.try_get::<Vec<u8>, _>(1).unwrap_or_default()[..],
+					),
+					block_number: row.try_get::<i32, _>(2).unwrap_or_default() as u32,
+					transaction_hash: H256::from_slice(
+						&row.try_get::<Vec<u8>, _>(3).unwrap_or_default()[..],
+					),
+					transaction_index: row.try_get::<i32, _>(4).unwrap_or_default() as u32,
+					trace,
+				})
+			})
+			.collect()
+	}
+
 	/// Create the Sqlite database if it does not already exist.
 	async fn create_database_if_not_exists(pool: &SqlitePool) -> Result<SqliteQueryResult, Error> {
 		sqlx::query(
@@ -752,6 +925,29 @@ where
 					substrate_block_hash
 				)
 			);
+			CREATE TABLE IF NOT EXISTS traces (
+				id INTEGER PRIMARY KEY,
+				substrate_block_hash BLOB NOT NULL,
+				transaction_hash BLOB NOT NULL,
+				transaction_index INTEGER NOT NULL,
+				trace_address BLOB NOT NULL,
+				from_address BLOB NOT NULL,
+				to_address BLOB NOT NULL,
+				trace BLOB NOT NULL,
+				UNIQUE (
+					substrate_block_hash,
+					transaction_index,
+					trace_address
+				)
+			);
+			CREATE TABLE IF NOT EXISTS trace_status (
+				id INTEGER PRIMARY KEY,
+				substrate_block_hash BLOB NOT NULL,
+				status INTEGER NOT NULL,
+				UNIQUE (
+					substrate_block_hash
+				)
+			);
 			COMMIT;",
 		)
 		.execute(pool)
@@ -788,6 +984,18 @@ where
 				ethereum_block_hash,
 				ethereum_transaction_index
 			);
+			CREATE INDEX IF NOT EXISTS traces_from_idx ON traces (
+				from_address
+			);
+			CREATE INDEX IF NOT EXISTS traces_to_idx ON traces (
+				to_address
+			);
+			CREATE INDEX IF NOT EXISTS traces_substrate_index ON traces (
+				substrate_block_hash
+			);
+			CREATE INDEX IF NOT EXISTS traces_tx_hash_idx ON traces (
+				transaction_hash
+			);
 			COMMIT;",
 		)
 		.execute(pool)
@@ -1029,6 +1237,85 @@ LIMIT 10001",
 	qb.build()
 }
 
+/// Which indexed call traces a trace query selects.
+enum TraceQuery {
+	Range {
+		from_block: u64,
+		to_block: u64,
+		from_addresses: Vec<H160>,
+		to_addresses: Vec<H160>,
+		offset: usize,
+		limit: Option<usize>,
+	},
+	Transaction(H256),
+}
+
+/// Build a SQL query to retrieve a list of call traces of canonical blocks.
+fn build_trace_query(qb: &mut QueryBuilder<Sqlite>, query: TraceQuery) {
+	qb.push(
+		"
+SELECT
+	t.substrate_block_hash,
+	b.ethereum_block_hash,
+	b.block_number,
+	t.transaction_hash,
+	t.transaction_index,
+	t.trace
+FROM traces AS t
+INNER JOIN blocks AS b
+ON b.substrate_block_hash = t.substrate_block_hash
+AND b.is_canon = 1
+WHERE 1",
+	);
+
+	let page = match query {
+		TraceQuery::Range {
+			from_block,
+			to_block,
+			from_addresses,
+			to_addresses,
+			offset,
+			limit,
+		} => {
+			qb.push(" AND b.block_number BETWEEN ")
+				.push_bind(from_block as i64)
+				.push(" AND ")
+				.push_bind(to_block as i64);
+			for (column, addresses) in [
+				("from_address", from_addresses),
+				("to_address", to_addresses),
+			] {
+				if !addresses.is_empty() {
+					qb.push(format!(" AND t.{column} IN ("));
+					let mut qb_addr = qb.separated(", ");
+					addresses.iter().for_each(|addr| {
+						qb_addr.push_bind(addr.as_bytes().to_owned());
+					});
+					qb_addr.push_unseparated(")");
+				}
+			}
+			Some((offset, limit))
+		}
+		TraceQuery::Transaction(transaction_hash) => {
+			qb.push(" AND t.transaction_hash = ")
+				.push_bind(transaction_hash.as_bytes().to_owned());
+			None
+		}
+	};
+
+	qb.push(
+		"
+ORDER BY b.block_number ASC, t.transaction_index ASC, t.id ASC",
+	);
+	if let Some((offset, limit)) = page {
+		// SQLite reads a negative limit as no limit.
+		qb.push(" LIMIT ")
+			.push_bind(limit.map_or(-1, |limit| limit as i64))
+			.push(" OFFSET ")
+			.push_bind(offset as i64);
+	}
+}
+
 #[cfg(test)]
 mod test {
 	use super::*;
@@ -1807,6 +2094,132 @@ mod test {
 		.await;
 	}
 
+	#[tokio::test]
+	async fn test_filter_traces_returns_canon_calls_in_order() {
+		let TestData {
+			backend,
+			alice,
+			bob,
+			substrate_hash_1,
+			substrate_hash_2,
+			ethereum_hash_2,
+			..
+		} = prepare().await;
+		let call = |trace_address: Vec<u32>, from: H160, to: H160| FlatCallFrame {
+			trace_address,
+			subtraces: 0,
+			call_type: tp_rpc::CallType::Call,
+			from,
+			to,
+			value: Default::default(),
+			gas: 0,
+			gas_used: 0,
+			input: vec![],
+			output: vec![],
+			error: None,
+		};
+		let tx_1 = H256::repeat_byte(0x11);
+		let tx_2 = H256::repeat_byte(0x12);
+		let tx_3 = H256::repeat_byte(0x13);
+
+		backend
+			.index_block_traces(
+				substrate_hash_1,
+				vec![(tx_1, vec![call(vec![], alice, bob)])],
+			)
+			.await
+			.expect("must succeed");
+		backend
+			.index_block_traces(
+				substrate_hash_2,
+				vec![
+					(
+						tx_2,
+						vec![call(vec![], bob, alice), call(vec![0], alice, bob)],
+					),
+					(tx_3, vec![call(vec![], alice, alice)]),
+				],
+			)
+			.await
+			.expect("must succeed");
+
+		let traces = backend
+			.filter_traces(2, 2, vec![alice], vec![], 0, None)
+			.await
+			.expect("must succeed");
+		assert_eq!(
+			traces
+				.iter()
+				.map(|t| (
+					t.transaction_hash,
+					t.transaction_index,
+					t.trace.trace_address.clone()
+				))
+				.collect::<Vec<_>>(),
+			vec![(tx_2, 0, vec![0]), (tx_3, 1, vec![])]
+		);
+		assert_eq!(traces[0].ethereum_block_hash, ethereum_hash_2);
+		assert_eq!(traces[0].block_number, 2);
+
+		// Pages are cut by the query.
+		let page = backend
+			.filter_traces(1, 2, vec![], vec![], 1, Some(2))
+			.await
+			.expect("must succeed");
+		assert_eq!(
+			page.iter()
+				.map(|t| (t.transaction_hash, t.trace.trace_address.clone()))
+				.collect::<Vec<_>>(),
+			vec![(tx_2, vec![]), (tx_2, vec![0])]
+		);
+
+		// Traces of non canonical blocks are ignored.
+		sqlx::query("UPDATE blocks SET is_canon = 0 WHERE substrate_block_hash = ?")
+			.bind(substrate_hash_1.as_bytes())
+			.execute(backend.pool())
+			.await
+			.expect("sql query must succeed");
+		assert!(backend
+			.transaction_traces(tx_1)
+			.await
+			.expect("must succeed")
+			.is_empty());
+	}
+
+	#[tokio::test]
+	async fn test_untraceable_blocks_are_not_retried() {
+		let TestData {
+			backend,
+			substrate_hash_1,
+			ethereum_hash_1,
+			..
+		} = prepare().await;
+		sqlx::query(
+			"INSERT INTO transactions(
+				ethereum_transaction_hash,
+				substrate_block_hash,
+				ethereum_block_hash,
+				ethereum_transaction_index)
+			VALUES (?, ?, ?, 0)",
+		)
+		.bind(H256::repeat_byte(0x11).as_bytes())
+		.bind(substrate_hash_1.as_bytes())
+		.bind(ethereum_hash_1.as_bytes())
+		.execute(backend.pool())
+		.await
+		.expect("sql query must succeed");
+		assert_eq!(
+			backend.get_first_untraced_canon_block().await,
+			Some(substrate_hash_1)
+		);
+
+		backend
+			.mark_untraceable_block(substrate_hash_1)
+			.await
+			.expect("must succeed");
+		assert_eq!(backend.get_first_untraced_canon_block().await, None);
+	}
+
 	#[test]
 	fn test_query_should_be_generated_correctly() {
 		use sqlx::Execute;
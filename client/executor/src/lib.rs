@@ -211,3 +211,65 @@ impl<D: NativeExecutionDispatch> sp_core::traits::ReadRuntimeVersion for NativeE
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use sc_executor_common::runtime_blob::RuntimeBlob;
+	use scale_codec::Encode;
+	use sp_core::{storage::StateVersion, Blake2Hasher};
+	use sp_runtime::{traits::Header as _, BuildStorage};
+	use sp_state_machine::TestExternalities;
+	use sp_wasm_interface::ExtendedHostFunctions;
+
+	use super::*;
+
+	type HostFunctions = ExtendedHostFunctions<
+		sp_io::SubstrateHostFunctions,
+		(tp_io::crypto::HostFunctions, tp_io::state::HostFunctions),
+	>;
+
+	fn genesis_ext() -> TestExternalities<Blake2Hasher> {
+		let storage = tenet_runtime::RuntimeGenesisConfig::default()
+			.build_storage()
+			.unwrap();
+		TestExternalities::new(storage)
+	}
+
+	fn storage_root(ext: &mut TestExternalities<Blake2Hasher>) -> Vec<u8> {
+		ext.execute_with(|| sp_io::storage::root(StateVersion::V1))
+	}
+
+	#[test]
+	fn wasm_runtime_storage_root_matches_native() {
+		// Values are encrypted by the trie backend of the host, so the Wasm build of the runtime
+		// must write the same state and get the same storage root as the native one.
+		let header = tenet_runtime::Header::new(
+			1,
+			Default::default(),
+			Default::default(),
+			Default::default(),
+			Default::default(),
+		)
+		.encode();
+
+		let mut native = genesis_ext();
+		native.execute_with(|| {
+			tenet_runtime::api::dispatch("Core_initialize_block", &header).unwrap();
+		});
+
+		let mut wasm = genesis_ext();
+		let blob = tenet_runtime::WASM_BINARY.expect("the Wasm binary of the runtime is built");
+		WasmExecutor::<HostFunctions>::builder()
+			.build()
+			.uncached_call(
+				RuntimeBlob::uncompress_if_needed(blob).unwrap(),
+				&mut wasm.ext(),
+				true,
+				"Core_initialize_block",
+				&header,
+			)
+			.unwrap();
+
+		assert_eq!(storage_root(&mut wasm), storage_root(&mut native));
+	}
+}
@@ -31,6 +31,9 @@ use tp_rpc::EthereumRuntimeRPCApi;
 
 use crate::{EthereumBlockNotification, EthereumBlockNotificationSinks, SyncStrategy};
 
+mod trace;
+pub use self::trace::TraceWorker;
+
 /// Defines the commands for the sync worker.
 #[derive(Debug)]
 pub enum WorkerCommand {
@@ -0,0 +1,121 @@
+use std::{sync::Arc, time::Duration};
+
+// Substrate
+use sc_client_api::BlockBackend;
+use sp_api::{ApiExt, Core, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_core::H256;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+// Tenet
+use tp_rpc::{EthereumTracingRuntimeApi, FlatCallFrame, TracerConfig, TransactionTrace};
+
+/// Exponent of the longest delay before tracing a block again, in check intervals.
+const MAX_BACKOFF_EXPONENT: u32 = 6;
+
+/// Implements an indexer that re-executes the indexed canonical blocks and stores the
+/// flattened call traces of their transactions, so that trace queries over block ranges
+/// do not re-execute anything.
+pub struct TraceWorker<Block, Client> {
+	_phantom: std::marker::PhantomData<(Block, Client)>,
+}
+
+impl<Block, Client> TraceWorker<Block, Client>
+where
+	Block: BlockT<Hash = H256>,
+	Client: ProvideRuntimeApi<Block>,
+	Client::Api: EthereumTracingRuntimeApi<Block>,
+	Client: BlockBackend<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+{
+	/// Start the worker. Blocks are traced once the sync worker indexed their transactions,
+	/// the backlog is checked every `interval`. A block whose tracing fails is retried after a
+	/// delay doubling with each consecutive failure, up to `2^MAX_BACKOFF_EXPONENT` intervals.
+	pub async fn run(
+		client: Arc<Client>,
+		indexer_backend: Arc<tc_db::sql::Backend<Block>>,
+		interval: Duration,
+	) {
+		let mut failures = 0u32;
+		loop {
+			let mut delay = interval;
+			while let Some(block_hash) = indexer_backend.get_first_untraced_canon_block().await {
+				let traces = {
+					let client = client.clone();
+					tokio::task::spawn_blocking(move || Self::trace_block(&client, block_hash))
+						.await
+				};
+				let indexed = match traces {
+					Ok(Ok(Some(traces))) => {
+						indexer_backend.index_block_traces(block_hash, traces).await
+					}
+					// The runtime of the block has no tracing API, so the block is recorded as
+					// untraceable and not retried.
+					Ok(Ok(None)) => {
+						log::debug!(target: "frontier-sql", "Runtime of block {block_hash:?} cannot trace it");
+						indexer_backend.mark_untraceable_block(block_hash).await
+					}
+					// Other failures, such as a database error, may be transient.
+					Ok(Err(err)) => {
+						failures = failures.saturating_add(1);
+						delay = interval.saturating_mul(1 << failures.min(MAX_BACKOFF_EXPONENT));
+						log::error!(target: "frontier-sql", "Failed tracing block {block_hash:?}, retrying in {delay:?}: {err}");
+						break;
+					}
+					Err(_) => {
+						log::error!(target: "frontier-sql", "tokio blocking task failed");
+						break;
+					}
+				};
+				if let Err(err) = indexed {
+					log::error!(target: "frontier-sql", "Failed indexing traces of {block_hash:?}: {err}");
+					break;
+				}
+				failures = 0;
+				log::debug!(target: "frontier-sql", "Traced block {block_hash:?}");
+			}
+			futures_timer::Delay::new(delay).await;
+		}
+	}
+
+	/// Re-execute the block on top of its parent and flatten the call tree of each
+	/// transaction, or `None` if the runtime of the block has no tracing API. Transactions the
+	/// runtime refuses to trace get no call traces.
+	#[allow(clippy::type_complexity)]
+	fn trace_block(
+		client: &Client,
+		block_hash: H256,
+	) -> Result<Option<Vec<(H256, Vec<FlatCallFrame>)>>, String> {
+		let header = client
+			.header(block_hash)
+			.map_err(|err| format!("{err:?}"))?
+			.ok_or("block header not found")?;
+		let extrinsics = client
+			.block_body(block_hash)
+			.map_err(|err| format!("{err:?}"))?
+			.ok_or("block body not found")?;
+		let parent_hash = *header.parent_hash();
+
+		let api = client.runtime_api();
+		if api
+			.api_version::<dyn EthereumTracingRuntimeApi<Block>>(parent_hash)
+			.map_err(|err| format!("{err:?}"))?
+			.is_none()
+		{
+			return Ok(None);
+		}
+		api.initialize_block(parent_hash, &header)
+			.map_err(|err| format!("{err:?}"))?;
+		let traces = api
+			.trace_block(parent_hash, extrinsics, TracerConfig::CallTracer)
+			.map_err(|err| format!("{err:?}"))?;
+
+		Ok(Some(
+			traces
+				.into_iter()
+				.map(|(transaction_hash, trace)| match trace {
+					Ok(TransactionTrace::CallTracer(frame)) => (transaction_hash, frame.flatten()),
+					_ => (transaction_hash, Vec::new()),
+				})
+				.collect(),
+		))
+	}
+}
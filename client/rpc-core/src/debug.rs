@@ -0,0 +1,40 @@
+//! Debug rpc interface.
+
+use ethereum_types::H256;
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+
+use crate::types::*;
+
+/// Debug rpc interface, tracing transactions as Geth does.
+#[rpc(server)]
+#[async_trait]
+pub trait DebugApi {
+	/// Re-executes the transaction of given hash in its block and returns its trace.
+	/// Confidential transactions and executions touching a contract that did not declare
+	/// itself public are not traced.
+	#[method(name = "debug_traceTransaction")]
+	async fn trace_transaction(
+		&self,
+		transaction_hash: H256,
+		options: Option<TraceOptions>,
+	) -> RpcResult<TransactionTrace>;
+
+	/// Executes a call on top of given block, without creating a transaction, and returns
+	/// its trace. Calls touching a contract that did not declare itself public are not traced.
+	#[method(name = "debug_traceCall")]
+	async fn trace_call(
+		&self,
+		request: CallRequest,
+		number_or_hash: Option<BlockNumberOrHash>,
+		options: Option<TraceOptions>,
+	) -> RpcResult<TransactionTrace>;
+
+	/// Re-executes the block of given number and returns the trace of each of its
+	/// transactions.
+	#[method(name = "debug_traceBlockByNumber")]
+	async fn trace_block_by_number(
+		&self,
+		number: BlockNumberOrHash,
+		options: Option<TraceOptions>,
+	) -> RpcResult<Vec<BlockTransactionTrace>>;
+}
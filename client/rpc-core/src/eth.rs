@@ -140,7 +140,8 @@ pub trait EthApi {
 		index: Index,
 	) -> RpcResult<Option<Transaction>>;
 
-	/// Returns transaction receipt by transaction hash.
+	/// Returns transaction receipt by transaction hash. `from` is ignored, logs addressed to
+	/// an account are only returned by `tenet_getTransactionReceipt`.
 	#[method(name = "eth_getTransactionReceipt")]
 	async fn transaction_receipt(
 		&self,
@@ -148,11 +149,49 @@ pub trait EthApi {
 		from: Option<H160>,
 	) -> RpcResult<Option<Receipt>>;
 
+	/// Returns transaction receipt by transaction hash, including the logs addressed to the
+	/// account that signed `challenge`. The data of its non-perceptible logs is sealed to the
+	/// public key the account registered, and empty if it registered none.
+	#[method(name = "tenet_getTransactionReceipt")]
+	async fn authenticated_transaction_receipt(
+		&self,
+		hash: H256,
+		challenge: ReceiptChallenge,
+	) -> RpcResult<Option<Receipt>>;
+
+	/// Returns the proof that the transaction is included in the transactions root of its
+	/// block, by transaction hash.
+	#[method(name = "tenet_getTransactionProof")]
+	async fn transaction_proof(&self, hash: H256) -> RpcResult<Option<InclusionProof>>;
+
+	/// Returns the proof that the transaction receipt is included in the receipts root of its
+	/// block, by transaction hash.
+	#[method(name = "tenet_getReceiptProof")]
+	async fn receipt_proof(&self, hash: H256) -> RpcResult<Option<InclusionProof>>;
+
+	/// Returns the proof that `receiver` is a receiver of the non-perceptible log at
+	/// `log_index` of the transaction, by transaction hash.
+	#[method(name = "tenet_getLogReceiverProof")]
+	async fn log_receiver_proof(
+		&self,
+		hash: H256,
+		log_index: Index,
+		receiver: H160,
+	) -> RpcResult<Option<ReceiverProof>>;
+
+	/// Returns the state changed by the transaction, by transaction hash. Only available for
+	/// transactions executed while state diff capture is enabled, and never for confidential
+	/// transactions or executions touching a contract that did not declare itself public.
+	#[method(name = "tenet_getStateDiff")]
+	async fn state_diff(&self, hash: H256) -> RpcResult<Option<StateDiff>>;
+
 	// ########################################################################
 	// State
 	// ########################################################################
 
-	/// Returns balance of the given account.
+	/// Returns balance of the given account. The balance of an account that registered a
+	/// public key is confidential, querying it fails with a pointer to
+	/// `tenet_getEncryptedBalance`.
 	#[method(name = "eth_getBalance")]
 	async fn balance(
 		&self,
@@ -160,6 +199,16 @@ pub trait EthApi {
 		number_or_hash: Option<BlockNumberOrHash>,
 	) -> RpcResult<U256>;
 
+	/// Returns the balance of the given account sealed to its registered public key, as a
+	/// 32-byte big endian word in an envelope with `keccak256(address)` as associated data.
+	/// Returns `None` if the account registered no public key.
+	#[method(name = "tenet_getEncryptedBalance")]
+	async fn encrypted_balance(
+		&self,
+		address: H160,
+		number_or_hash: Option<BlockNumberOrHash>,
+	) -> RpcResult<Option<Bytes>>;
+
 	/// Returns content of the storage at given address.
 	#[method(name = "eth_getStorageAt")]
 	async fn storage_at(
@@ -185,6 +234,25 @@ pub trait EthApi {
 		number_or_hash: Option<BlockNumberOrHash>,
 	) -> RpcResult<Bytes>;
 
+	/// Returns the confidentiality policy of the contract at given address at given time
+	/// (block number).
+	#[method(name = "tenet_getConfidentialityPolicy")]
+	async fn confidentiality_policy(
+		&self,
+		address: H160,
+		number_or_hash: Option<BlockNumberOrHash>,
+	) -> RpcResult<ConfidentialityPolicy>;
+
+	/// Returns the account and storage proofs of given address at given time (block number),
+	/// in the Tenet format of `AccountProof`.
+	#[method(name = "eth_getProof")]
+	async fn proof(
+		&self,
+		address: H160,
+		storage_keys: Vec<H256>,
+		number_or_hash: Option<BlockNumberOrHash>,
+	) -> RpcResult<AccountProof>;
+
 	// ########################################################################
 	// Execute
 	// ########################################################################
@@ -206,6 +274,25 @@ pub trait EthApi {
 		number_or_hash: Option<BlockNumberOrHash>,
 	) -> RpcResult<U256>;
 
+	/// Call contract with a request sealed to the enclave key, returning the output data
+	/// sealed to the caller.
+	#[method(name = "tenet_call")]
+	async fn encrypted_call(
+		&self,
+		request: EncryptedCallRequest,
+		number_or_hash: Option<BlockNumberOrHash>,
+		state_overrides: Option<BTreeMap<H160, CallStateOverride>>,
+	) -> RpcResult<Bytes>;
+
+	/// Estimate gas needed for execution of a request sealed to the enclave key, returning
+	/// the estimate as a 32 byte big endian word sealed to the caller.
+	#[method(name = "tenet_estimateGas")]
+	async fn encrypted_estimate_gas(
+		&self,
+		request: EncryptedCallRequest,
+		number_or_hash: Option<BlockNumberOrHash>,
+	) -> RpcResult<Bytes>;
+
 	// ########################################################################
 	// Fee
 	// ########################################################################
@@ -264,6 +351,19 @@ pub trait EthApi {
 	/// Sends signed transaction, returning its hash.
 	#[method(name = "eth_sendRawTransaction")]
 	async fn send_raw_transaction(&self, bytes: Bytes) -> RpcResult<H256>;
+
+	// ########################################################################
+	// Disclosure
+	// ########################################################################
+
+	/// Returns a confidential transaction with its decrypted input and receipt, given a
+	/// challenge signed by a view key its sender granted.
+	#[method(name = "tenet_viewTransaction")]
+	async fn view_transaction(
+		&self,
+		hash: H256,
+		challenge: ViewChallenge,
+	) -> RpcResult<Option<DisclosedTransaction>>;
 }
 
 /// Eth filters rpc api (polling).
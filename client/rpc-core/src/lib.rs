@@ -1,11 +1,15 @@
 pub use fc_rpc_core::{NetApiServer, Web3ApiServer};
 
+mod debug;
 mod eth;
 mod eth_pubsub;
+mod trace;
 #[cfg(feature = "txpool")]
 mod txpool;
 pub mod types;
 #[cfg(feature = "txpool")]
 pub use self::txpool::TxPoolApiServer;
+pub use debug::DebugApiServer;
 pub use eth::{EthApiServer, EthFilterApiServer};
 pub use eth_pubsub::EthPubSubApiServer;
+pub use trace::TraceApiServer;
@@ -0,0 +1,27 @@
+//! Trace rpc interface.
+
+use ethereum_types::H256;
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+
+use crate::types::*;
+
+/// Trace rpc interface, serving the call traces indexed by the SQL backend in the format of
+/// the Parity `trace` module.
+#[rpc(server)]
+#[async_trait]
+pub trait TraceApi {
+	/// Returns the call traces of the transactions of given block.
+	#[method(name = "trace_block")]
+	async fn trace_block(
+		&self,
+		number: BlockNumberOrHash,
+	) -> RpcResult<Option<Vec<LocalizedTrace>>>;
+
+	/// Returns the call traces of the transaction of given hash.
+	#[method(name = "trace_transaction")]
+	async fn trace_transaction(&self, hash: H256) -> RpcResult<Option<Vec<LocalizedTrace>>>;
+
+	/// Returns the call traces matching given filter.
+	#[method(name = "trace_filter")]
+	async fn trace_filter(&self, filter: TraceFilter) -> RpcResult<Vec<LocalizedTrace>>;
+}
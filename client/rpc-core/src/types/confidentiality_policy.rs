@@ -0,0 +1,13 @@
+use serde::Serialize;
+
+/// Confidentiality policy a contract declared when it was deployed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfidentialityPolicy {
+	/// Plaintext and encrypted calls are both accepted.
+	Public,
+	/// Calls must be encrypted.
+	ConfidentialInputs,
+	/// Calls must be encrypted and the state is only read through encrypted calls.
+	ConfidentialState,
+}
@@ -0,0 +1,21 @@
+use ethereum_types::H512;
+use serde::{Deserialize, Serialize};
+
+use crate::types::Bytes;
+
+/// Call request sealed to the enclave key of the node.
+///
+/// `payload` is an envelope of the JSON encoded `CallRequest`, encrypted under the ECDH key of
+/// the caller and the enclave key with `keccak256(public_key)` as additional data. Sealing the
+/// payload requires the secret half of `public_key`, so the call is executed from the address
+/// of `public_key` whatever `from` the request names. The result is sealed to the caller under
+/// the same key with `keccak256(payload)` as additional data.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptedCallRequest {
+	/// Uncompressed public key of the caller, without the `0x04` tag
+	pub public_key: H512,
+	/// Sealed call request
+	pub payload: Bytes,
+}
@@ -11,20 +11,33 @@ pub use fc_rpc_core::types::{
 pub use self::txpool::{Summary, TransactionMap, TxPoolResult};
 pub use self::{
 	block::{Block, BlockTransactions, Header, Rich, RichBlock, RichHeader},
+	confidentiality_policy::ConfidentialityPolicy,
+	encrypted_call_request::EncryptedCallRequest,
 	filter::{
 		Filter, FilterAddress, FilterChanges, FilterPool, FilterPoolItem, FilterType,
 		FilteredParams,
 	},
 	log::Log,
-	receipt::Receipt,
-	transaction::{RichRawTransaction, Transaction},
+	proof::{AccountProof, InclusionProof, ReceiverProof, StorageSlotProof},
+	receipt::{Receipt, ReceiptChallenge},
+	state_diff::{AccountDiff, Change, StateDiff},
+	trace::{
+		BlockTransactionTrace, CallTrace, LocalizedTrace, PrestateAccount, StructLog,
+		StructLogsTrace, TraceAction, TraceFilter, TraceOptions, TraceResult, TransactionTrace,
+	},
+	transaction::{DisclosedTransaction, RichRawTransaction, Transaction, ViewChallenge},
 	transaction_request::{TransactionMessage, TransactionRequest},
 };
 mod block;
+mod confidentiality_policy;
+mod encrypted_call_request;
 mod filter;
 mod log;
+mod proof;
 pub mod pubsub;
 mod receipt;
+mod state_diff;
+mod trace;
 mod transaction;
 mod transaction_request;
 #[cfg(feature = "txpool")]
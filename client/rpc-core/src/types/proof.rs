@@ -0,0 +1,83 @@
+use ethereum_types::{H160, H256, U256};
+use serde::Serialize;
+
+use crate::types::Bytes;
+
+/// Tenet format of the `eth_getProof` result.
+///
+/// Proofs are Substrate trie proofs against `stateRoot`, the state root of the block header,
+/// rather than Merkle-Patricia proofs against an Ethereum account trie. `accountProof` proves
+/// the `System.Account` and `EVM.AccountCodes` entries of the address, each `storageProof`
+/// proves one `EVM.AccountStorages` entry. Trie values are encrypted with the state key, so
+/// a proof only shows which ciphertext is committed under a key. It proves neither `balance`,
+/// `nonce` nor `codeHash`, which are reported by the node.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountProof {
+	/// Address
+	pub address: H160,
+	/// Balance as returned by `eth_getBalance`, `None` if the account registered a public key
+	/// and keeps its balance confidential. Not proven by `accountProof`.
+	pub balance: Option<U256>,
+	/// Nonce, not proven by `accountProof`
+	pub nonce: U256,
+	/// Keccak hash of the code, not proven by `accountProof`
+	pub code_hash: H256,
+	/// State root the proofs are checked against
+	pub state_root: H256,
+	/// Trie nodes proving the account entries
+	pub account_proof: Vec<Bytes>,
+	/// Proofs of the requested storage slots
+	pub storage_proof: Vec<StorageSlotProof>,
+}
+
+/// Proof of a storage slot in the Tenet format of `eth_getProof`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageSlotProof {
+	/// Storage slot
+	pub key: H256,
+	/// Value as stored in the trie, `None` if the slot is empty
+	pub value: Option<Bytes>,
+	/// Trie nodes proving the slot
+	pub proof: Vec<Bytes>,
+}
+
+/// Proof that a transaction or its receipt is included in an Ethereum block.
+///
+/// `value` is the enveloped encoding of the transaction or receipt, stored in the ordered trie
+/// under the RLP encoded transaction index, as checked by `tp_ethereum::verify_transaction_proof`
+/// and `tp_ethereum::verify_receipt_proof`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InclusionProof {
+	/// Block hash
+	pub block_hash: H256,
+	/// Block number
+	pub block_number: U256,
+	/// Transaction Index
+	pub transaction_index: U256,
+	/// Transactions root or receipts root of the block header
+	pub root: H256,
+	/// Enveloped encoding of the transaction or receipt
+	pub value: Bytes,
+	/// Trie nodes from the root down to the value
+	pub proof: Vec<Bytes>,
+}
+
+/// Proof that an account is a receiver of a non-perceptible log.
+///
+/// The receivers of the log are stored in an ordered trie under their RLP encoded position,
+/// as checked by `tp_ethereum::verify_receiver_proof`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiverProof {
+	/// Receiver
+	pub receiver: H160,
+	/// Receivers root committed to by the log
+	pub receivers_root: H256,
+	/// Position of the receiver in the trie
+	pub index: U256,
+	/// Trie nodes from the root down to the receiver
+	pub proof: Vec<Bytes>,
+}
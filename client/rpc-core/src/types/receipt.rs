@@ -1,7 +1,7 @@
 use ethereum_types::{Bloom as H2048, H160, H256, U256, U64};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use super::log::Log;
+use super::{log::Log, Bytes};
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Receipt {
@@ -41,3 +41,16 @@ pub struct Receipt {
 	#[serde(rename = "type")]
 	pub transaction_type: U256,
 }
+
+/// Proof that a receipt request comes from an account, allowing it to see the logs addressed
+/// to it.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiptChallenge {
+	/// Unix time in seconds after which the signature is rejected
+	pub expiry: U64,
+	/// `personal_sign` of `keccak256("tenet-receipt" ++ transactionHash ++ expiry)`, the
+	/// expiry being 8 bytes big endian
+	pub signature: Bytes,
+}
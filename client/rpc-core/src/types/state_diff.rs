@@ -0,0 +1,43 @@
+use std::collections::BTreeMap;
+
+use ethereum_types::{H160, H256, U256};
+use serde::Serialize;
+
+use crate::types::Bytes;
+
+/// Result of `tenet_getStateDiff`, the accounts a transaction changed.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateDiff {
+	/// Transaction hash
+	pub transaction_hash: H256,
+	/// Changed accounts, by address
+	pub accounts: BTreeMap<H160, AccountDiff>,
+}
+
+/// How a transaction changed an account. Unchanged fields are omitted.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountDiff {
+	/// Balance, also omitted if the account registered a public key
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub balance: Option<Change<U256>>,
+	/// Nonce
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub nonce: Option<Change<U256>>,
+	/// Code
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub code: Option<Change<Bytes>>,
+	/// Changed storage slots
+	#[serde(skip_serializing_if = "BTreeMap::is_empty")]
+	pub storage: BTreeMap<H256, Change<H256>>,
+}
+
+/// A value before and after a transaction.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct Change<T> {
+	/// Value before the transaction
+	pub from: T,
+	/// Value after the transaction
+	pub to: T,
+}
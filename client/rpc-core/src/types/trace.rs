@@ -0,0 +1,250 @@
+use std::collections::BTreeMap;
+
+use ethereum_types::{H160, H256, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::types::{BlockNumberOrHash, Bytes};
+
+/// Options of the `debug_trace*` methods.
+///
+/// Without `tracer` the default struct logger runs, the `callTracer` and `prestateTracer`
+/// built-in tracers are supported as well.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TraceOptions {
+	/// Name of the built-in tracer
+	pub tracer: Option<String>,
+	/// Omit the storage of struct logs
+	pub disable_storage: bool,
+	/// Omit the stack of struct logs
+	pub disable_stack: bool,
+	/// Include the memory of struct logs
+	pub enable_memory: bool,
+}
+
+/// Trace of a transaction, in the format of the tracer that produced it.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum TransactionTrace {
+	/// Default struct logger
+	StructLogs(StructLogsTrace),
+	/// `callTracer`
+	CallTracer(CallTrace),
+	/// `prestateTracer`
+	PrestateTracer(BTreeMap<H160, PrestateAccount>),
+}
+
+/// Trace of a transaction of a block, as returned by `debug_traceBlockByNumber`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockTransactionTrace {
+	/// Transaction hash
+	pub tx_hash: H256,
+	/// Trace of the transaction
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub result: Option<TransactionTrace>,
+	/// Why the transaction could not be traced
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLogsTrace {
+	/// Gas used by the transaction
+	pub gas: u64,
+	/// Whether the transaction failed
+	pub failed: bool,
+	/// Returned data, hex encoded without prefix
+	pub return_value: String,
+	/// One entry per executed opcode
+	pub struct_logs: Vec<StructLog>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLog {
+	/// Program counter
+	pub pc: u64,
+	/// Opcode name
+	pub op: String,
+	/// Gas remaining before the opcode runs
+	pub gas: u64,
+	/// Gas charged for the opcode
+	pub gas_cost: u64,
+	/// Call depth, starting at 1
+	pub depth: u32,
+	/// Stack, bottom first
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub stack: Option<Vec<U256>>,
+	/// Memory as 32 bytes words, hex encoded without prefix
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub memory: Option<Vec<String>>,
+	/// Slots of the executing contract touched so far, hex encoded without prefix
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub storage: Option<BTreeMap<String, String>>,
+}
+
+/// Frame of the `callTracer` call tree.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallTrace {
+	/// `CALL`, `STATICCALL`, `DELEGATECALL`, `CALLCODE`, `CREATE`, `CREATE2` or `SELFDESTRUCT`
+	#[serde(rename = "type")]
+	pub call_type: String,
+	/// Caller
+	pub from: H160,
+	/// Callee, or created contract
+	pub to: H160,
+	/// Transferred value
+	pub value: U256,
+	/// Gas available to the frame
+	pub gas: U256,
+	/// Gas used by the frame
+	pub gas_used: U256,
+	/// Call data, or init code
+	pub input: Bytes,
+	/// Returned data
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub output: Option<Bytes>,
+	/// Why the frame failed
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+	/// Frames of the inner calls
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub calls: Vec<CallTrace>,
+}
+
+/// Account touched by a transaction, as it was before the transaction.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrestateAccount {
+	/// Balance, omitted if the account registered a public key
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub balance: Option<U256>,
+	/// Nonce
+	pub nonce: U256,
+	/// Code
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub code: Option<Bytes>,
+	/// Touched storage slots
+	#[serde(skip_serializing_if = "BTreeMap::is_empty")]
+	pub storage: BTreeMap<H256, H256>,
+}
+
+/// Filter of `trace_filter`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TraceFilter {
+	/// First block of the range, the latest block if omitted
+	pub from_block: Option<BlockNumberOrHash>,
+	/// Last block of the range, the latest block if omitted
+	pub to_block: Option<BlockNumberOrHash>,
+	/// Callers to match, any if omitted
+	pub from_address: Option<Vec<H160>>,
+	/// Callees to match, any if omitted
+	pub to_address: Option<Vec<H160>>,
+	/// Number of matching traces to skip
+	pub after: Option<usize>,
+	/// Maximum number of traces to return, at most 10000
+	pub count: Option<usize>,
+}
+
+/// Call trace in the format of the Parity `trace` module.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalizedTrace {
+	/// What the call did
+	pub action: TraceAction,
+	/// What the call returned, omitted if it failed
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub result: Option<TraceResult>,
+	/// Why the call failed
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+	/// Number of direct inner calls
+	pub subtraces: u32,
+	/// Indices of the calls leading from the root call to this one
+	pub trace_address: Vec<u32>,
+	/// Transaction hash
+	pub transaction_hash: H256,
+	/// Transaction index
+	pub transaction_position: u32,
+	/// Block hash
+	pub block_hash: H256,
+	/// Block number
+	pub block_number: u32,
+	/// `call`, `create` or `suicide`
+	#[serde(rename = "type")]
+	pub trace_type: String,
+}
+
+/// Action of a `LocalizedTrace`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum TraceAction {
+	/// Call
+	#[serde(rename_all = "camelCase")]
+	Call {
+		/// `call`, `staticcall`, `delegatecall` or `callcode`
+		call_type: String,
+		/// Caller
+		from: H160,
+		/// Callee
+		to: H160,
+		/// Transferred value
+		value: U256,
+		/// Gas available to the call
+		gas: U256,
+		/// Call data
+		input: Bytes,
+	},
+	/// Contract creation
+	#[serde(rename_all = "camelCase")]
+	Create {
+		/// `create` or `create2`
+		creation_method: String,
+		/// Creator
+		from: H160,
+		/// Endowment
+		value: U256,
+		/// Gas available to the creation
+		gas: U256,
+		/// Init code
+		init: Bytes,
+	},
+	/// Self destruction
+	#[serde(rename_all = "camelCase")]
+	Suicide {
+		/// Destroyed contract
+		address: H160,
+		/// Beneficiary of the balance
+		refund_address: H160,
+		/// Balance of the contract
+		balance: U256,
+	},
+}
+
+/// Result of a `LocalizedTrace`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum TraceResult {
+	/// Call
+	#[serde(rename_all = "camelCase")]
+	Call {
+		/// Gas used by the call
+		gas_used: U256,
+		/// Returned data
+		output: Bytes,
+	},
+	/// Contract creation
+	#[serde(rename_all = "camelCase")]
+	Create {
+		/// Gas used by the creation
+		gas_used: U256,
+		/// Created contract
+		address: H160,
+		/// Deployed code
+		code: Bytes,
+	},
+}
@@ -1,9 +1,9 @@
 use tp_ethereum::{AccessListItem, TransactionAction, TransactionV2 as EthereumTransaction};
 
 use ethereum_types::{H160, H256, H512, U256, U64};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::types::{BuildFrom, Bytes};
+use crate::types::{BuildFrom, Bytes, Receipt};
 
 /// Transaction
 #[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
@@ -195,3 +195,28 @@ pub struct RichRawTransaction {
 	#[serde(rename = "tx")]
 	pub transaction: Transaction,
 }
+
+/// A confidential transaction disclosed to the holder of a view key.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisclosedTransaction {
+	/// Transaction details, with the input as sent on chain
+	pub transaction: Transaction,
+	/// Decrypted input of the transaction
+	pub input: Bytes,
+	/// Receipt, including the logs only perceptible to the sender
+	pub receipt: Option<Receipt>,
+}
+
+/// Proof that a view request comes from the holder of a view key, whose commitment is the
+/// keccak256 of its 64-byte public key.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewChallenge {
+	/// Unix time in seconds after which the signature is rejected
+	pub expiry: U64,
+	/// `personal_sign` by the view key of `keccak256("tenet-view" ++ transactionHash ++ expiry)`,
+	/// the expiry being 8 bytes big endian
+	pub signature: Bytes,
+}
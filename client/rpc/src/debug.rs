@@ -0,0 +1,376 @@
+use std::sync::Arc;
+
+use ethereum_types::{H256, U256};
+use jsonrpsee::core::{async_trait, RpcResult};
+// Substrate
+use sc_client_api::BlockBackend;
+use sp_api::{ApiError, ApiRef, Core, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{
+	traits::{Block as BlockT, Header as HeaderT},
+	DispatchError,
+};
+// Frontier
+use tc_rpc_core::{types::*, DebugApiServer};
+use tp_rpc::{EthereumRuntimeRPCApi, EthereumTracingRuntimeApi, TracerConfig};
+
+use crate::{frontier_backend_client, internal_err};
+
+/// Debug API implementation, tracing transactions by re-executing them in the runtime.
+pub struct Debug<B: BlockT, C> {
+	client: Arc<C>,
+	backend: Arc<dyn fc_api::Backend<B>>,
+}
+
+impl<B: BlockT, C> Debug<B, C> {
+	pub fn new(client: Arc<C>, backend: Arc<dyn fc_api::Backend<B>>) -> Self {
+		Self { client, backend }
+	}
+}
+
+impl<B, C> Debug<B, C>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B>,
+	C::Api: EthereumRuntimeRPCApi<B> + EthereumTracingRuntimeApi<B>,
+	C: BlockBackend<B> + HeaderBackend<B> + 'static,
+{
+	/// Initialize the block of given hash on top of its parent, then hand its extrinsics to
+	/// `trace`.
+	fn replay<R>(
+		&self,
+		substrate_hash: B::Hash,
+		trace: impl FnOnce(
+			&ApiRef<C::Api>,
+			B::Hash,
+			Vec<B::Extrinsic>,
+		) -> Result<Result<R, DispatchError>, ApiError>,
+	) -> RpcResult<R> {
+		let header = self
+			.client
+			.header(substrate_hash)
+			.map_err(|err| internal_err(format!("Fetch block header failed: {err}")))?
+			.ok_or_else(|| internal_err("Block header not found"))?;
+		let extrinsics = self
+			.client
+			.block_body(substrate_hash)
+			.map_err(|err| internal_err(format!("Fetch block body failed: {err}")))?
+			.ok_or_else(|| internal_err("Block body not found"))?;
+		let parent_hash = *header.parent_hash();
+
+		let api = self.client.runtime_api();
+		api.initialize_block(parent_hash, &header)
+			.map_err(|err| internal_err(format!("runtime error: {err}")))?;
+		trace(&api, parent_hash, extrinsics)
+			.map_err(|err| internal_err(format!("runtime error: {err}")))?
+			.map_err(|err| internal_err(format!("trace failed: {err:?}")))
+	}
+}
+
+#[async_trait]
+impl<B, C> DebugApiServer for Debug<B, C>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B>,
+	C::Api: EthereumRuntimeRPCApi<B> + EthereumTracingRuntimeApi<B>,
+	C: BlockBackend<B> + HeaderBackend<B> + 'static,
+{
+	async fn trace_transaction(
+		&self,
+		transaction_hash: H256,
+		options: Option<TraceOptions>,
+	) -> RpcResult<TransactionTrace> {
+		let (eth_block_hash, _) = frontier_backend_client::load_transactions::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			transaction_hash,
+			true,
+		)
+		.await?
+		.ok_or_else(|| internal_err("Transaction not found"))?;
+		let substrate_hash = frontier_backend_client::load_hash::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			eth_block_hash,
+		)
+		.await?
+		.ok_or_else(|| internal_err("Block not found"))?;
+
+		let tracer = tracer_config(options)?;
+		let trace = self.replay(substrate_hash, |api, parent_hash, extrinsics| {
+			api.trace_transaction(parent_hash, extrinsics, transaction_hash, tracer)
+		})?;
+		Ok(transaction_trace(trace))
+	}
+
+	async fn trace_call(
+		&self,
+		request: CallRequest,
+		number_or_hash: Option<BlockNumberOrHash>,
+		options: Option<TraceOptions>,
+	) -> RpcResult<TransactionTrace> {
+		let id = frontier_backend_client::native_block_id::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			number_or_hash,
+		)
+		.await?
+		.ok_or_else(|| internal_err("debug_traceCall is not supported on the pending block"))?;
+		let substrate_hash = self
+			.client
+			.expect_block_hash_from_id(&id)
+			.map_err(|_| internal_err(format!("Expect block number from id: {id}")))?;
+
+		let api = self.client.runtime_api();
+		let gas_limit = match request.gas {
+			Some(gas) => gas,
+			None => {
+				api.current_block(substrate_hash)
+					.map_err(|err| internal_err(format!("runtime error: {err}")))?
+					.ok_or_else(|| internal_err("block unavailable, cannot query gas limit"))?
+					.header
+					.gas_limit
+			}
+		};
+
+		let tracer = tracer_config(options)?;
+		let trace = api
+			.trace_call(
+				substrate_hash,
+				request.from.unwrap_or_default(),
+				request.to,
+				request.data.map(|data| data.0).unwrap_or_default(),
+				request.value.unwrap_or_default(),
+				gas_limit,
+				tracer,
+			)
+			.map_err(|err| internal_err(format!("runtime error: {err}")))?
+			.map_err(|err| internal_err(format!("trace failed: {err:?}")))?;
+		Ok(transaction_trace(trace))
+	}
+
+	async fn trace_block_by_number(
+		&self,
+		number: BlockNumberOrHash,
+		options: Option<TraceOptions>,
+	) -> RpcResult<Vec<BlockTransactionTrace>> {
+		let id = frontier_backend_client::native_block_id::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			Some(number),
+		)
+		.await?
+		.ok_or_else(|| {
+			internal_err("debug_traceBlockByNumber is not supported on the pending block")
+		})?;
+		let substrate_hash = self
+			.client
+			.expect_block_hash_from_id(&id)
+			.map_err(|_| internal_err(format!("Expect block number from id: {id}")))?;
+
+		let tracer = tracer_config(options)?;
+		let traces = self.replay(substrate_hash, |api, parent_hash, extrinsics| {
+			api.trace_block(parent_hash, extrinsics, tracer).map(Ok)
+		})?;
+		Ok(traces
+			.into_iter()
+			.map(|(tx_hash, trace)| match trace {
+				Ok(trace) => BlockTransactionTrace {
+					tx_hash,
+					result: Some(transaction_trace(trace)),
+					error: None,
+				},
+				Err(err) => BlockTransactionTrace {
+					tx_hash,
+					result: None,
+					error: Some(format!("{err:?}")),
+				},
+			})
+			.collect())
+	}
+}
+
+fn tracer_config(options: Option<TraceOptions>) -> RpcResult<TracerConfig> {
+	let options = options.unwrap_or_default();
+	Ok(match options.tracer.as_deref() {
+		None => TracerConfig::StructLogs {
+			disable_storage: options.disable_storage,
+			disable_memory: !options.enable_memory,
+			disable_stack: options.disable_stack,
+		},
+		Some("callTracer") => TracerConfig::CallTracer,
+		Some("prestateTracer") => TracerConfig::PrestateTracer,
+		Some(tracer) => return Err(internal_err(format!("unsupported tracer: {tracer}"))),
+	})
+}
+
+fn transaction_trace(trace: tp_rpc::TransactionTrace) -> TransactionTrace {
+	match trace {
+		tp_rpc::TransactionTrace::StructLogs {
+			gas,
+			failed,
+			return_value,
+			struct_logs,
+		} => TransactionTrace::StructLogs(StructLogsTrace {
+			gas,
+			failed,
+			return_value: hex::encode(return_value),
+			struct_logs: struct_logs
+				.into_iter()
+				.map(|log| StructLog {
+					pc: log.pc,
+					op: opcode_name(log.op),
+					gas: log.gas,
+					gas_cost: log.gas_cost,
+					depth: log.depth,
+					stack: log.stack.map(|stack| {
+						stack
+							.iter()
+							.map(|word| U256::from_big_endian(word.as_bytes()))
+							.collect()
+					}),
+					memory: log
+						.memory
+						.map(|memory| memory.chunks(32).map(hex::encode).collect()),
+					storage: log.storage.map(|storage| {
+						storage
+							.into_iter()
+							.map(|(key, value)| (hex::encode(key), hex::encode(value)))
+							.collect()
+					}),
+				})
+				.collect(),
+		}),
+		tp_rpc::TransactionTrace::CallTracer(frame) => {
+			TransactionTrace::CallTracer(call_trace(frame))
+		}
+		tp_rpc::TransactionTrace::PrestateTracer(accounts) => TransactionTrace::PrestateTracer(
+			accounts
+				.into_iter()
+				.map(|account| {
+					(
+						account.address,
+						PrestateAccount {
+							balance: account.balance,
+							nonce: account.nonce,
+							code: (!account.code.is_empty()).then_some(Bytes(account.code)),
+							storage: account.storage.into_iter().collect(),
+						},
+					)
+				})
+				.collect(),
+		),
+	}
+}
+
+fn call_trace(frame: tp_rpc::CallFrame) -> CallTrace {
+	let call_type = match frame.call_type {
+		tp_rpc::CallType::Call => "CALL",
+		tp_rpc::CallType::StaticCall => "STATICCALL",
+		tp_rpc::CallType::DelegateCall => "DELEGATECALL",
+		tp_rpc::CallType::CallCode => "CALLCODE",
+		tp_rpc::CallType::Create => "CREATE",
+		tp_rpc::CallType::Create2 => "CREATE2",
+		tp_rpc::CallType::SelfDestruct => "SELFDESTRUCT",
+	};
+	CallTrace {
+		call_type: call_type.to_string(),
+		from: frame.from,
+		to: frame.to,
+		value: frame.value,
+		gas: frame.gas.into(),
+		gas_used: frame.gas_used.into(),
+		input: Bytes(frame.input),
+		output: (!frame.output.is_empty()).then_some(Bytes(frame.output)),
+		error: frame
+			.error
+			.map(|error| String::from_utf8_lossy(&error).into_owned()),
+		calls: frame.calls.into_iter().map(call_trace).collect(),
+	}
+}
+
+fn opcode_name(opcode: u8) -> String {
+	let name = match opcode {
+		0x00 => "STOP",
+		0x01 => "ADD",
+		0x02 => "MUL",
+		0x03 => "SUB",
+		0x04 => "DIV",
+		0x05 => "SDIV",
+		0x06 => "MOD",
+		0x07 => "SMOD",
+		0x08 => "ADDMOD",
+		0x09 => "MULMOD",
+		0x0a => "EXP",
+		0x0b => "SIGNEXTEND",
+		0x10 => "LT",
+		0x11 => "GT",
+		0x12 => "SLT",
+		0x13 => "SGT",
+		0x14 => "EQ",
+		0x15 => "ISZERO",
+		0x16 => "AND",
+		0x17 => "OR",
+		0x18 => "XOR",
+		0x19 => "NOT",
+		0x1a => "BYTE",
+		0x1b => "SHL",
+		0x1c => "SHR",
+		0x1d => "SAR",
+		0x20 => "KECCAK256",
+		0x30 => "ADDRESS",
+		0x31 => "BALANCE",
+		0x32 => "ORIGIN",
+		0x33 => "CALLER",
+		0x34 => "CALLVALUE",
+		0x35 => "CALLDATALOAD",
+		0x36 => "CALLDATASIZE",
+		0x37 => "CALLDATACOPY",
+		0x38 => "CODESIZE",
+		0x39 => "CODECOPY",
+		0x3a => "GASPRICE",
+		0x3b => "EXTCODESIZE",
+		0x3c => "EXTCODECOPY",
+		0x3d => "RETURNDATASIZE",
+		0x3e => "RETURNDATACOPY",
+		0x3f => "EXTCODEHASH",
+		0x40 => "BLOCKHASH",
+		0x41 => "COINBASE",
+		0x42 => "TIMESTAMP",
+		0x43 => "NUMBER",
+		0x44 => "DIFFICULTY",
+		0x45 => "GASLIMIT",
+		0x46 => "CHAINID",
+		0x47 => "SELFBALANCE",
+		0x48 => "BASEFEE",
+		0x50 => "POP",
+		0x51 => "MLOAD",
+		0x52 => "MSTORE",
+		0x53 => "MSTORE8",
+		0x54 => "SLOAD",
+		0x55 => "SSTORE",
+		0x56 => "JUMP",
+		0x57 => "JUMPI",
+		0x58 => "PC",
+		0x59 => "MSIZE",
+		0x5a => "GAS",
+		0x5b => "JUMPDEST",
+		0x5f => "PUSH0",
+		0x60..=0x7f => return format!("PUSH{}", opcode - 0x5f),
+		0x80..=0x8f => return format!("DUP{}", opcode - 0x7f),
+		0x90..=0x9f => return format!("SWAP{}", opcode - 0x8f),
+		0xa0..=0xa4 => return format!("LOG{}", opcode - 0xa0),
+		0xf0 => "CREATE",
+		0xf1 => "CALL",
+		0xf2 => "CALLCODE",
+		0xf3 => "RETURN",
+		0xf4 => "DELEGATECALL",
+		0xf5 => "CREATE2",
+		0xfa => "STATICCALL",
+		0xfd => "REVERT",
+		0xfe => "INVALID",
+		0xff => "SELFDESTRUCT",
+		_ => return format!("opcode {opcode:#04x} not defined"),
+	};
+	name.to_string()
+}
@@ -30,7 +30,7 @@ use sp_block_builder::BlockBuilder as BlockBuilderApi;
 use sp_blockchain::HeaderBackend;
 use sp_externalities::Extensions;
 use sp_inherents::CreateInherentDataProviders;
-use sp_io::hashing::{blake2_128, twox_128};
+use sp_io::hashing::{blake2_128, keccak_256, twox_128};
 use sp_runtime::{
 	traits::{Block as BlockT, HashingFor},
 	DispatchError, SaturatedConversion,
@@ -961,6 +961,136 @@ where
 
 		Ok(overlayed_changes)
 	}
+
+	pub async fn encrypted_call(
+		&self,
+		request: EncryptedCallRequest,
+		number_or_hash: Option<BlockNumberOrHash>,
+		state_overrides: Option<BTreeMap<H160, CallStateOverride>>,
+	) -> RpcResult<Bytes> {
+		let call_request = self.open_call_request(&request)?;
+		match self
+			.call(call_request, number_or_hash, state_overrides)
+			.await
+		{
+			Ok(output) => self.seal_call_response(&request, &output.0).map(Bytes),
+			Err(err) => Err(self.seal_call_error(&request, err)),
+		}
+	}
+
+	pub async fn encrypted_estimate_gas(
+		&self,
+		request: EncryptedCallRequest,
+		number_or_hash: Option<BlockNumberOrHash>,
+	) -> RpcResult<Bytes> {
+		let call_request = self.open_call_request(&request)?;
+		match self.estimate_gas(call_request, number_or_hash).await {
+			Ok(gas) => {
+				let mut word = [0u8; 32];
+				gas.to_big_endian(&mut word);
+				self.seal_call_response(&request, &word).map(Bytes)
+			}
+			Err(err) => Err(self.seal_call_error(&request, err)),
+		}
+	}
+
+	/// Reject plaintext calls to contracts whose state is only read through encrypted calls,
+	/// under the policy of the block the call runs on.
+	pub async fn ensure_public_state(
+		&self,
+		request: &CallRequest,
+		number_or_hash: Option<BlockNumberOrHash>,
+	) -> RpcResult<()> {
+		let Some(to) = request.to else {
+			return Ok(());
+		};
+		let (substrate_hash, api) = match frontier_backend_client::native_block_id::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			number_or_hash,
+		)
+		.await?
+		{
+			Some(id) => {
+				let hash = self
+					.client
+					.expect_block_hash_from_id(&id)
+					.map_err(|_| crate::err(JSON_RPC_ERROR_DEFAULT, "header not found", None))?;
+				(hash, self.client.runtime_api())
+			}
+			None => {
+				// Not mapped in the db, assume pending.
+				let (hash, api) = self.pending_runtime_api().await.map_err(|err| {
+					internal_err(format!("Create pending runtime api error: {err}"))
+				})?;
+				(hash, api)
+			}
+		};
+		let policy = api
+			.confidentiality_policy(substrate_hash, to)
+			.unwrap_or_default();
+		if policy == tp_rpc::ConfidentialityPolicy::ConfidentialState {
+			return Err(internal_err(
+				"contract state is confidential, use tenet_call or tenet_estimateGas",
+			));
+		}
+		Ok(())
+	}
+
+	/// Decrypt a sealed call request. The call is made from the address of the key the
+	/// request was sealed with.
+	fn open_call_request(&self, request: &EncryptedCallRequest) -> RpcResult<CallRequest> {
+		let enclave_key = self
+			.enclave_key
+			.as_ref()
+			.ok_or_else(|| internal_err("enclave key unavailable"))?;
+		let public_key = request.public_key.to_fixed_bytes();
+		let plaintext = enclave_key
+			.decrypt(&request.payload.0, &keccak_256(&public_key), &public_key)
+			.map_err(|_| internal_err("cannot decrypt call request"))?;
+		let mut call_request: CallRequest = serde_json::from_slice(&plaintext)
+			.map_err(|err| internal_err(format!("invalid call request: {err}")))?;
+		call_request.from = Some(H160::from(H256::from(keccak_256(&public_key))));
+		Ok(call_request)
+	}
+
+	fn seal_call_response(
+		&self,
+		request: &EncryptedCallRequest,
+		output: &[u8],
+	) -> RpcResult<Vec<u8>> {
+		self.enclave_key
+			.as_ref()
+			.ok_or_else(|| internal_err("enclave key unavailable"))?
+			.encrypt(
+				output,
+				&keccak_256(&request.payload.0),
+				request.public_key.as_fixed_bytes(),
+			)
+			.map_err(|_| internal_err("cannot encrypt call response"))
+	}
+
+	/// Execution errors carry the revert data, so they are sealed as well: the data of the
+	/// returned error is the sealed JSON error object.
+	fn seal_call_error(
+		&self,
+		request: &EncryptedCallRequest,
+		err: jsonrpsee::core::Error,
+	) -> jsonrpsee::core::Error {
+		let details = match &err {
+			jsonrpsee::core::Error::Call(jsonrpsee::types::error::CallError::Custom(object)) => {
+				serde_json::to_vec(object)
+			}
+			_ => serde_json::to_vec(&err.to_string()),
+		};
+		match details
+			.map_err(|_| internal_err("cannot encode call error"))
+			.and_then(|details| self.seal_call_response(request, &details))
+		{
+			Ok(sealed) => crate::internal_err_with_data("encrypted call failed", &sealed),
+			Err(err) => err,
+		}
+	}
 }
 
 pub fn error_on_execution_failure(reason: &ExitReason, data: &[u8]) -> RpcResult<()> {
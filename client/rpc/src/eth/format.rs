@@ -37,6 +37,15 @@ impl Geth {
 				InvalidTransaction::Stale => "nonce too low".into(),
 				InvalidTransaction::Payment => "insufficient funds for gas * price + value".into(),
 				InvalidTransaction::ExhaustsResources => "exceeds block gas limit".into(),
+				InvalidTransaction::Custom(tp_ethereum::DECRYPTION_FAILED) => {
+					"confidential transaction cannot be decrypted".into()
+				}
+				InvalidTransaction::Custom(
+					tp_ethereum::PLAINTEXT_CALL_TO_CONFIDENTIAL_CONTRACT,
+				) => "contract only accepts encrypted calls".into(),
+				InvalidTransaction::Custom(
+					tp_ethereum::PLAINTEXT_TRANSACTION_FROM_REGISTERED_ACCOUNT,
+				) => "account only sends encrypted transactions".into(),
 				InvalidTransaction::Custom(inner) => match inner.into() {
 					VError::UnknownError => "unknown error".into(),
 					VError::InvalidChainId => "invalid chain id".into(),
@@ -23,6 +23,7 @@ mod fee;
 mod filter;
 mod mining;
 pub mod pending;
+mod proof;
 mod state;
 mod submit;
 mod transaction;
@@ -92,6 +93,8 @@ pub struct Eth<B: BlockT, C, P, CT, BE, A: ChainApi, CIDP, EC> {
 	/// Something that can create the inherent data providers for pending state.
 	pending_create_inherent_data_providers: CIDP,
 	pending_consensus_data_provider: Option<Box<dyn pending::ConsensusDataProvider<B>>>,
+	/// The node's enclave key, used to encrypt confidential transactions and balances.
+	enclave_key: Option<tp_io::EnclaveKey>,
 	_marker: PhantomData<(BE, EC)>,
 }
 
@@ -121,6 +124,7 @@ where
 		forced_parent_hashes: Option<BTreeMap<H256, H256>>,
 		pending_create_inherent_data_providers: CIDP,
 		pending_consensus_data_provider: Option<Box<dyn pending::ConsensusDataProvider<B>>>,
+		enclave_key: Option<tp_io::EnclaveKey>,
 	) -> Self {
 		Self {
 			client,
@@ -139,6 +143,7 @@ where
 			forced_parent_hashes,
 			pending_create_inherent_data_providers,
 			pending_consensus_data_provider,
+			enclave_key,
 			_marker: PhantomData,
 		}
 	}
@@ -282,6 +287,7 @@ where
 			forced_parent_hashes,
 			pending_create_inherent_data_providers,
 			pending_consensus_data_provider,
+			enclave_key,
 			_marker: _,
 		} = self;
 
@@ -302,6 +308,7 @@ where
 			forced_parent_hashes,
 			pending_create_inherent_data_providers,
 			pending_consensus_data_provider,
+			enclave_key,
 			_marker: PhantomData,
 		}
 	}
@@ -435,14 +442,44 @@ where
 	async fn transaction_receipt(
 		&self,
 		hash: H256,
-		from: Option<H160>,
+		_from: Option<H160>,
 	) -> RpcResult<Option<Receipt>> {
 		let (block_info, index) = self.block_info_by_eth_transaction_hash(hash).await?;
 
-		self.transaction_receipt(&block_info, hash, index, from)
+		self.transaction_receipt(&block_info, hash, index, None)
 			.await
 	}
 
+	async fn authenticated_transaction_receipt(
+		&self,
+		hash: H256,
+		challenge: ReceiptChallenge,
+	) -> RpcResult<Option<Receipt>> {
+		self.authenticated_transaction_receipt(hash, challenge)
+			.await
+	}
+
+	async fn transaction_proof(&self, hash: H256) -> RpcResult<Option<InclusionProof>> {
+		self.transaction_proof(hash).await
+	}
+
+	async fn receipt_proof(&self, hash: H256) -> RpcResult<Option<InclusionProof>> {
+		self.receipt_proof(hash).await
+	}
+
+	async fn log_receiver_proof(
+		&self,
+		hash: H256,
+		log_index: Index,
+		receiver: H160,
+	) -> RpcResult<Option<ReceiverProof>> {
+		self.log_receiver_proof(hash, log_index, receiver).await
+	}
+
+	async fn state_diff(&self, hash: H256) -> RpcResult<Option<StateDiff>> {
+		self.state_diff(hash).await
+	}
+
 	// ########################################################################
 	// State
 	// ########################################################################
@@ -455,6 +492,14 @@ where
 		self.balance(address, number_or_hash).await
 	}
 
+	async fn encrypted_balance(
+		&self,
+		address: H160,
+		number_or_hash: Option<BlockNumberOrHash>,
+	) -> RpcResult<Option<Bytes>> {
+		self.encrypted_balance(address, number_or_hash).await
+	}
+
 	async fn storage_at(
 		&self,
 		_address: H160,
@@ -482,6 +527,23 @@ where
 		self.code_at(address, number_or_hash).await
 	}
 
+	async fn confidentiality_policy(
+		&self,
+		address: H160,
+		number_or_hash: Option<BlockNumberOrHash>,
+	) -> RpcResult<ConfidentialityPolicy> {
+		self.confidentiality_policy(address, number_or_hash).await
+	}
+
+	async fn proof(
+		&self,
+		address: H160,
+		storage_keys: Vec<H256>,
+		number_or_hash: Option<BlockNumberOrHash>,
+	) -> RpcResult<AccountProof> {
+		self.proof(address, storage_keys, number_or_hash).await
+	}
+
 	// ########################################################################
 	// Execute
 	// ########################################################################
@@ -492,6 +554,7 @@ where
 		number_or_hash: Option<BlockNumberOrHash>,
 		state_overrides: Option<BTreeMap<H160, CallStateOverride>>,
 	) -> RpcResult<Bytes> {
+		self.ensure_public_state(&request, number_or_hash).await?;
 		self.call(request, number_or_hash, state_overrides).await
 	}
 
@@ -500,9 +563,28 @@ where
 		request: CallRequest,
 		number_or_hash: Option<BlockNumberOrHash>,
 	) -> RpcResult<U256> {
+		self.ensure_public_state(&request, number_or_hash).await?;
 		self.estimate_gas(request, number_or_hash).await
 	}
 
+	async fn encrypted_call(
+		&self,
+		request: EncryptedCallRequest,
+		number_or_hash: Option<BlockNumberOrHash>,
+		state_overrides: Option<BTreeMap<H160, CallStateOverride>>,
+	) -> RpcResult<Bytes> {
+		self.encrypted_call(request, number_or_hash, state_overrides)
+			.await
+	}
+
+	async fn encrypted_estimate_gas(
+		&self,
+		request: EncryptedCallRequest,
+		number_or_hash: Option<BlockNumberOrHash>,
+	) -> RpcResult<Bytes> {
+		self.encrypted_estimate_gas(request, number_or_hash).await
+	}
+
 	// ########################################################################
 	// Fee
 	// ########################################################################
@@ -560,6 +642,18 @@ where
 	async fn send_raw_transaction(&self, bytes: Bytes) -> RpcResult<H256> {
 		self.send_raw_transaction(bytes).await
 	}
+
+	// ########################################################################
+	// Disclosure
+	// ########################################################################
+
+	async fn view_transaction(
+		&self,
+		hash: H256,
+		challenge: ViewChallenge,
+	) -> RpcResult<Option<DisclosedTransaction>> {
+		self.view_transaction(hash, challenge).await
+	}
 }
 
 fn rich_block_build(
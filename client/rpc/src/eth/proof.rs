@@ -0,0 +1,222 @@
+use ethereum_types::{H160, H256, U256};
+use jsonrpsee::core::RpcResult;
+use tp_ethereum::{BlockV2 as EthereumBlock, EnvelopedEncodable};
+// Substrate
+use sc_client_api::backend::{Backend, StorageProvider};
+use sc_transaction_pool::ChainApi;
+use sc_transaction_pool_api::TransactionPool;
+use sp_api::{CallApiAt, ProvideRuntimeApi};
+use sp_block_builder::BlockBuilder as BlockBuilderApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::hashing::keccak_256;
+use sp_inherents::CreateInherentDataProviders;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+// Frontier
+use tc_rpc_core::types::*;
+use tp_rpc::EthereumRuntimeRPCApi;
+
+use crate::{eth::Eth, frontier_backend_client, internal_err};
+
+impl<B, C, P, CT, BE, A, CIDP, EC> Eth<B, C, P, CT, BE, A, CIDP, EC>
+where
+	B: BlockT,
+	C: CallApiAt<B> + ProvideRuntimeApi<B>,
+	C::Api: BlockBuilderApi<B> + EthereumRuntimeRPCApi<B>,
+	C: HeaderBackend<B> + StorageProvider<B, BE> + 'static,
+	BE: Backend<B> + 'static,
+	P: TransactionPool<Block = B> + 'static,
+	A: ChainApi<Block = B>,
+	CIDP: CreateInherentDataProviders<B, ()> + Send + 'static,
+{
+	pub async fn proof(
+		&self,
+		address: H160,
+		storage_keys: Vec<H256>,
+		number_or_hash: Option<BlockNumberOrHash>,
+	) -> RpcResult<AccountProof> {
+		let number_or_hash = number_or_hash.unwrap_or(BlockNumberOrHash::Latest);
+		if number_or_hash == BlockNumberOrHash::Pending {
+			return Err(internal_err(
+				"eth_getProof is not supported on the pending block",
+			));
+		}
+
+		let id = frontier_backend_client::native_block_id::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			Some(number_or_hash),
+		)
+		.await?
+		.ok_or_else(|| internal_err("Block not found"))?;
+		let substrate_hash = self
+			.client
+			.expect_block_hash_from_id(&id)
+			.map_err(|_| internal_err(format!("Expect block number from id: {id}")))?;
+		let header = self
+			.client
+			.header(substrate_hash)
+			.map_err(|err| internal_err(format!("Fetch block header failed: {err}")))?
+			.ok_or_else(|| internal_err("Block header not found"))?;
+		let state_root = H256::from_slice(header.state_root().as_ref());
+
+		let account_keys = [
+			tp_ethereum::system_account_key(address),
+			tp_ethereum::evm_code_key(address),
+		];
+		let account_proof = self.prove_read(substrate_hash, &account_keys)?;
+
+		let mut storage_proof = Vec::with_capacity(storage_keys.len());
+		for index in storage_keys {
+			let key = tp_ethereum::evm_storage_key(address, index);
+			let proof = self.prove_read(substrate_hash, &[&key])?;
+			// The backend decrypts what it reads, so take the value as committed in the trie
+			// from the proof itself.
+			let value = tp_ethereum::verify_state_proof(
+				state_root,
+				proof.iter().map(|node| node.0.clone()).collect(),
+				&key,
+			)
+			.map_err(|err| internal_err(format!("Proof does not match the state root: {err}")))?;
+			storage_proof.push(StorageSlotProof {
+				key: index,
+				value: value.map(Bytes),
+				proof,
+			});
+		}
+
+		let api = self.client.runtime_api();
+		let nonce = api
+			.account_basic(substrate_hash, address)
+			.map_err(|err| internal_err(format!("Fetch account nonce failed: {err}")))?
+			.nonce;
+		let code = api
+			.account_code_at(substrate_hash, address)
+			.map_err(|err| internal_err(format!("Fetch account code failed: {err}")))?;
+
+		// `has_account_public_key` is true when `address` did not register a public key.
+		let balance = if api
+			.has_account_public_key(substrate_hash, address)
+			.map_err(|err| internal_err(format!("Fetch account public key failed: {err}")))?
+		{
+			Some(self.balance(address, Some(number_or_hash)).await?)
+		} else {
+			None
+		};
+
+		Ok(AccountProof {
+			address,
+			balance,
+			nonce,
+			code_hash: H256::from(keccak_256(&code)),
+			state_root,
+			account_proof,
+			storage_proof,
+		})
+	}
+
+	pub async fn transaction_proof(&self, hash: H256) -> RpcResult<Option<InclusionProof>> {
+		let (block_info, index) = self.block_info_by_eth_transaction_hash(hash).await?;
+		let Some(block) = &block_info.block else {
+			return Ok(None);
+		};
+
+		inclusion_proof(
+			block,
+			index,
+			block.header.transactions_root,
+			block.transactions.iter().map(EnvelopedEncodable::encode),
+		)
+	}
+
+	pub async fn receipt_proof(&self, hash: H256) -> RpcResult<Option<InclusionProof>> {
+		let (block_info, index) = self.block_info_by_eth_transaction_hash(hash).await?;
+		let (Some(block), Some(receipts)) = (&block_info.block, &block_info.receipts) else {
+			return Ok(None);
+		};
+
+		inclusion_proof(
+			block,
+			index,
+			block.header.receipts_root,
+			receipts.iter().map(EnvelopedEncodable::encode),
+		)
+	}
+
+	pub async fn log_receiver_proof(
+		&self,
+		hash: H256,
+		log_index: Index,
+		receiver: H160,
+	) -> RpcResult<Option<ReceiverProof>> {
+		let (block_info, index) = self.block_info_by_eth_transaction_hash(hash).await?;
+		let Some(receipt) = block_info
+			.receipts
+			.as_ref()
+			.and_then(|receipts| receipts.get(index))
+		else {
+			return Ok(None);
+		};
+		let (tp_ethereum::Receipt::Legacy(data)
+		| tp_ethereum::Receipt::EIP2930(data)
+		| tp_ethereum::Receipt::EIP1559(data)) = receipt;
+		let Some(log) = data.logs.get(log_index.value()) else {
+			return Ok(None);
+		};
+		let (Some(receivers_root), Some((index, proof))) =
+			(log.receivers_root, log.receiver_proof(receiver))
+		else {
+			return Ok(None);
+		};
+
+		Ok(Some(ReceiverProof {
+			receiver,
+			receivers_root,
+			index: U256::from(index),
+			proof: proof.into_iter().map(Bytes).collect(),
+		}))
+	}
+
+	fn prove_read<K: AsRef<[u8]>>(&self, hash: B::Hash, keys: &[K]) -> RpcResult<Vec<Bytes>> {
+		let state = self
+			.client
+			.state_at(hash)
+			.map_err(|err| internal_err(format!("Fetch state failed: {err}")))?;
+		let proof = sp_state_machine::prove_read(state, keys)
+			.map_err(|err| internal_err(format!("Generate state proof failed: {err}")))?;
+
+		Ok(proof.into_iter_nodes().map(Bytes).collect())
+	}
+}
+
+/// Prove the value at `index` of the ordered trie of `values`, which must have `root` in the
+/// header of `block`.
+fn inclusion_proof<V: AsRef<[u8]>>(
+	block: &EthereumBlock,
+	index: usize,
+	root: H256,
+	values: impl Iterator<Item = V>,
+) -> RpcResult<Option<InclusionProof>> {
+	let values = values.collect::<Vec<_>>();
+	let Some(value) = values.get(index) else {
+		return Ok(None);
+	};
+	let value = Bytes(value.as_ref().to_vec());
+	let (proven_root, proof) =
+		tp_ethereum::order_generate_proof::<sp_core::KeccakHasher, _, _>(&values, index)
+			.map_err(|err| internal_err(format!("Generate inclusion proof failed: {err}")))?;
+	// The proof is useless unless the stored block data is what the header commits to.
+	if proven_root != root {
+		return Err(internal_err(
+			"Inclusion proof does not match the block header",
+		));
+	}
+
+	Ok(Some(InclusionProof {
+		block_hash: block.header.hash(),
+		block_number: block.header.number,
+		transaction_index: U256::from(index),
+		root,
+		value,
+		proof: proof.into_iter().map(Bytes).collect(),
+	}))
+}
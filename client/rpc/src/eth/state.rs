@@ -34,6 +34,10 @@ use tp_rpc::EthereumRuntimeRPCApi;
 
 use crate::{eth::Eth, frontier_backend_client, internal_err};
 
+/// JSON-RPC error code of `eth_getBalance` for an account that registered a public key, whose
+/// balance is only served sealed by `tenet_getEncryptedBalance`.
+pub const CONFIDENTIAL_BALANCE_ERROR_CODE: i32 = -32011;
+
 impl<B, C, P, CT, BE, A, CIDP, EC> Eth<B, C, P, CT, BE, A, CIDP, EC>
 where
 	B: BlockT,
@@ -45,26 +49,59 @@ where
 	A: ChainApi<Block = B>,
 	CIDP: CreateInherentDataProviders<B, ()> + Send + 'static,
 {
+	/// The balance of `address`. Accounts that registered a public key keep their balance
+	/// confidential, it is only served sealed by `tenet_getEncryptedBalance`.
 	pub async fn balance(
 		&self,
 		address: H160,
 		number_or_hash: Option<BlockNumberOrHash>,
 	) -> RpcResult<U256> {
-		let return_func = |balance: U256, nonce: &U256, pubkey: &Vec<u8>| -> RpcResult<U256> {
-			let balance = balance.as_u128();
-			let nonce: u64 = nonce.as_u64();
-			let mut bytes = [0u8; 64];
-			bytes.copy_from_slice(pubkey);
-			let res = tp_io::crypto::encrypted(
-				&balance.to_be_bytes(),
-				&sp_io::hashing::keccak_256(&nonce.to_be_bytes()),
-				&bytes,
-			)
-			.map_err(|_| internal_err("Can't encrypted"))?;
+		match self.account_balance(address, number_or_hash).await? {
+			Some((balance, pubkey)) if pubkey.is_empty() => Ok(balance),
+			Some(_) => Err(crate::err(
+				CONFIDENTIAL_BALANCE_ERROR_CODE,
+				"balance is confidential, query it with tenet_getEncryptedBalance",
+				None,
+			)),
+			None => Ok(U256::zero()),
+		}
+	}
 
-			Ok(U256::from_big_endian(res.as_ref()))
+	/// The balance of `address` sealed to its registered public key in an envelope under a
+	/// fresh random nonce, with `keccak256(address)` as associated data. `None` if the account
+	/// registered no public key.
+	pub async fn encrypted_balance(
+		&self,
+		address: H160,
+		number_or_hash: Option<BlockNumberOrHash>,
+	) -> RpcResult<Option<Bytes>> {
+		let Some((balance, pubkey)) = self.account_balance(address, number_or_hash).await? else {
+			return Ok(None);
+		};
+		let Ok(pubkey) = <[u8; 64]>::try_from(pubkey) else {
+			return Ok(None);
 		};
+		let mut balance_bytes = [0u8; 32];
+		balance.to_big_endian(&mut balance_bytes);
+		self.enclave_key
+			.as_ref()
+			.ok_or_else(|| internal_err("enclave key unavailable"))?
+			.encrypt(
+				&balance_bytes,
+				&sp_io::hashing::keccak_256(address.as_bytes()),
+				&pubkey,
+			)
+			.map(|sealed| Some(Bytes(sealed)))
+			.map_err(|_| internal_err("Can't encrypted"))
+	}
 
+	/// The plaintext balance and the registered public key of `address`, `None` if the block
+	/// is unknown.
+	async fn account_balance(
+		&self,
+		address: H160,
+		number_or_hash: Option<BlockNumberOrHash>,
+	) -> RpcResult<Option<(U256, Vec<u8>)>> {
 		let number_or_hash = number_or_hash.unwrap_or(BlockNumberOrHash::Latest);
 		if number_or_hash == BlockNumberOrHash::Pending {
 			let (hash, api) = self
@@ -80,12 +117,7 @@ where
 				.runtime_api()
 				.account_public(self.client.info().best_hash, address)
 				.unwrap_or_default();
-
-			if pubkey.is_empty() {
-				Ok(acc.balance)
-			} else {
-				return_func(acc.balance, &acc.nonce, &pubkey)
-			}
+			Ok(Some((acc.balance, pubkey)))
 		} else if let Ok(Some(id)) = frontier_backend_client::native_block_id::<B, C>(
 			self.client.as_ref(),
 			self.backend.as_ref(),
@@ -108,13 +140,9 @@ where
 				.runtime_api()
 				.account_public(substrate_hash, address)
 				.unwrap_or_default();
-			if pubkey.is_empty() {
-				Ok(acc.balance)
-			} else {
-				return_func(acc.balance, &acc.nonce, &pubkey)
-			}
+			Ok(Some((acc.balance, pubkey)))
 		} else {
-			Ok(U256::zero())
+			Ok(None)
 		}
 	}
 
@@ -211,4 +239,46 @@ where
 			Ok(Bytes(vec![]))
 		}
 	}
+
+	pub async fn confidentiality_policy(
+		&self,
+		address: H160,
+		number_or_hash: Option<BlockNumberOrHash>,
+	) -> RpcResult<ConfidentialityPolicy> {
+		let number_or_hash = number_or_hash.unwrap_or(BlockNumberOrHash::Latest);
+		let policy = if number_or_hash == BlockNumberOrHash::Pending {
+			let (hash, api) = self
+				.pending_runtime_api()
+				.await
+				.map_err(|err| internal_err(format!("Create pending runtime api error: {err}")))?;
+			api.confidentiality_policy(hash, address)
+		} else if let Ok(Some(id)) = frontier_backend_client::native_block_id::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			Some(number_or_hash),
+		)
+		.await
+		{
+			let substrate_hash = self
+				.client
+				.expect_block_hash_from_id(&id)
+				.map_err(|_| internal_err(format!("Expect block number from id: {id}")))?;
+			self.client
+				.runtime_api()
+				.confidentiality_policy(substrate_hash, address)
+		} else {
+			return Err(internal_err("block not found"));
+		};
+
+		// Runtimes without policies only have public contracts.
+		Ok(match policy.unwrap_or_default() {
+			tp_rpc::ConfidentialityPolicy::Public => ConfidentialityPolicy::Public,
+			tp_rpc::ConfidentialityPolicy::ConfidentialInputs => {
+				ConfidentialityPolicy::ConfidentialInputs
+			}
+			tp_rpc::ConfidentialityPolicy::ConfidentialState => {
+				ConfidentialityPolicy::ConfidentialState
+			}
+		})
+	}
 }
@@ -16,19 +16,25 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use ethereum_types::H256;
+use ethereum_types::{H256, U256};
 use futures::future::TryFutureExt;
 use jsonrpsee::core::RpcResult;
 // Substrate
 use sc_client_api::backend::{Backend, StorageProvider};
 use sc_transaction_pool::ChainApi;
-use sc_transaction_pool_api::TransactionPool;
+use sc_transaction_pool_api::{
+	error::{Error as PError, IntoPoolError},
+	TransactionPool,
+};
 use sp_api::{ApiExt, ProvideRuntimeApi};
 use sp_block_builder::BlockBuilder as BlockBuilderApi;
 use sp_blockchain::HeaderBackend;
 use sp_core::H160;
 use sp_inherents::CreateInherentDataProviders;
-use sp_runtime::{traits::Block as BlockT, transaction_validity::TransactionSource};
+use sp_runtime::{
+	traits::Block as BlockT,
+	transaction_validity::{InvalidTransaction, TransactionSource},
+};
 // Frontier
 use tc_rpc_core::types::*;
 use tp_rpc::{ConvertTransaction, ConvertTransactionRuntimeApi, EthereumRuntimeRPCApi};
@@ -128,6 +134,26 @@ where
 			_ => return Err(internal_err("invalid transaction parameters")),
 		};
 
+		let action = match &message {
+			TransactionMessage::Legacy(m) => m.action,
+			TransactionMessage::EIP2930(m) => m.action,
+			TransactionMessage::EIP1559(m) => m.action,
+		};
+		let confidential = self.requires_encryption(block_hash, from, action);
+		// The signature of Legacy and EIP-2930 transactions covers their input, so it is
+		// sealed before signing, while EIP-1559 transactions are encrypted once signed.
+		let message = match message {
+			TransactionMessage::Legacy(mut m) if confidential => {
+				m.input = self.seal_input(block_hash, from, m.chain_id, m.nonce, &m.input)?;
+				TransactionMessage::Legacy(m)
+			}
+			TransactionMessage::EIP2930(mut m) if confidential => {
+				m.input = self.seal_input(block_hash, from, Some(m.chain_id), m.nonce, &m.input)?;
+				TransactionMessage::EIP2930(m)
+			}
+			message => message,
+		};
+
 		let mut transaction = None;
 		for signer in &self.signers {
 			if signer.accounts().contains(&from) {
@@ -140,40 +166,29 @@ where
 		}
 
 		let transaction = match transaction {
+			Some(transaction)
+				if !confidential
+					|| !matches!(transaction, tp_ethereum::TransactionV2::EIP1559(_)) =>
+			{
+				transaction
+			}
 			Some(transaction) => {
-				let (action, is_eip1559) = match &transaction {
-					ethereum::TransactionV2::Legacy(t) => (t.action, false),
-					ethereum::TransactionV2::EIP2930(t) => (t.action, false),
-					ethereum::TransactionV2::EIP1559(t) => (t.action(), true),
+				let pubkey = match crate::public_key(&transaction) {
+					Ok(pubkey) => pubkey,
+					Err(_) => return Err(internal_err("cannot recover public key")),
 				};
-				if action == tp_ethereum::TransactionAction::Create || !is_eip1559 {
-					transaction
-				} else {
-					let pubkey = match crate::public_key(&transaction) {
-						Ok(pubkey) => pubkey,
-						Err(_) => return Err(internal_err("cannot recover public key")),
-					};
-
-					let from = H160::from(H256::from(sp_core::keccak_256(&pubkey)));
-
-					if self
-						.client
-						.runtime_api()
-						.has_account_public_key(block_hash, from)
-						.unwrap_or_default()
-					{
-						transaction
-					} else {
-						match transaction.encrypt(|msg, aad| {
-							tp_io::crypto::encrypted(msg, aad.as_fixed_bytes(), &pubkey)
-								.map_err(|_| ethereum::Error::BadEncrypte)
-						}) {
-							Ok(transaction) => transaction,
-							Err(_) => return Err(internal_err("cannot encrypted transaction")),
-						}
-					}
+				let Some(enclave_key) = self.enclave_key.as_ref() else {
+					return Err(internal_err("enclave key unavailable"));
+				};
+				match transaction.encrypt(|msg, aad| {
+					enclave_key
+						.encrypt(msg, aad.as_fixed_bytes(), &pubkey)
+						.map_err(|_| ethereum::Error::BadEncrypte)
+				}) {
+					Ok(transaction) => transaction,
+					Err(_) => return Err(internal_err("cannot encrypted transaction")),
 				}
-			},
+			}
 			None => return Err(internal_err("no signer available")),
 		};
 		let transaction_hash = transaction.hash();
@@ -183,7 +198,7 @@ where
 		self.pool
 			.submit_one(block_hash, TransactionSource::Local, extrinsic)
 			.map_ok(move |_| transaction_hash)
-			.map_err(|err| internal_err(format::Geth::pool_error(err)))
+			.map_err(pool_err)
 			.await
 	}
 
@@ -196,41 +211,39 @@ where
 
 		let transaction: tp_ethereum::TransactionV2 =
 			match tp_ethereum::EnvelopedDecodable::decode(&bytes) {
+				// Transactions encrypted by the client are submitted as they are.
+				Ok(transaction) if !transaction.is_universal() => transaction,
 				Ok(transaction) => {
-					let (action, is_eip1559) = match &transaction {
-						ethereum::TransactionV2::Legacy(t) => (t.action, false),
-						ethereum::TransactionV2::EIP2930(t) => (t.action, false),
-						ethereum::TransactionV2::EIP1559(t) => (t.action(), true),
+					let pubkey = match crate::public_key(&transaction) {
+						Ok(pubkey) => pubkey,
+						Err(_) => return Err(internal_err("cannot recover public key")),
 					};
-					if action == tp_ethereum::TransactionAction::Create || !is_eip1559 {
+
+					let from = H160::from(H256::from(sp_core::keccak_256(&pubkey)));
+
+					if !self.requires_encryption(block_hash, from, transaction.action()) {
+						transaction
+					} else if !matches!(transaction, ethereum::TransactionV2::EIP1559(_)) {
+						// The signature of Legacy and EIP-2930 transactions covers their input,
+						// so only their sender can seal it, to the public key it registered.
+						if self.registered_public_key(block_hash, from).is_none() {
+							return Err(internal_err(NO_REGISTERED_PUBLIC_KEY));
+						}
 						transaction
 					} else {
-						let pubkey = match crate::public_key(&transaction) {
-							Ok(pubkey) => pubkey,
-							Err(_) => return Err(internal_err("cannot recover public key")),
+						let Some(enclave_key) = self.enclave_key.as_ref() else {
+							return Err(internal_err("enclave key unavailable"));
 						};
-
-						let from = H160::from(H256::from(sp_core::keccak_256(&pubkey)));
-
-						if self
-							.client
-							.runtime_api()
-							.has_account_public_key(block_hash, from)
-							.unwrap_or_default()
-						{
-							transaction
-						} else {
-							match transaction.encrypt(|msg, aad| {
-								tp_io::crypto::encrypted(msg, aad.as_fixed_bytes(), &pubkey)
-									.map_err(|_| ethereum::Error::BadEncrypte)
-							}) {
-								Ok(transaction) => transaction,
-								Err(_) => return Err(internal_err("cannot encrypted transaction")),
-							}
+						match transaction.encrypt(|msg, aad| {
+							enclave_key
+								.encrypt(msg, aad.as_fixed_bytes(), &pubkey)
+								.map_err(|_| ethereum::Error::BadEncrypte)
+						}) {
+							Ok(transaction) => transaction,
+							Err(_) => return Err(internal_err("cannot encrypted transaction")),
 						}
 					}
-					
-				},
+				}
 				Err(_) => return Err(internal_err("decode transaction failed")),
 			};
 		let transaction_hash = transaction.hash();
@@ -241,10 +254,67 @@ where
 		self.pool
 			.submit_one(block_hash, TransactionSource::Local, extrinsic)
 			.map_ok(move |_| transaction_hash)
-			.map_err(|err| internal_err(format::Geth::pool_error(err)))
+			.map_err(pool_err)
 			.await
 	}
 
+	/// Whether a transaction of `from` must be encrypted, because `from` registered a public
+	/// key or the transaction calls a contract that only accepts encrypted calls.
+	fn requires_encryption(
+		&self,
+		block_hash: B::Hash,
+		from: H160,
+		action: tp_ethereum::TransactionAction,
+	) -> bool {
+		let api = self.client.runtime_api();
+		// `has_account_public_key` is true when `from` did not register a public key.
+		if !api
+			.has_account_public_key(block_hash, from)
+			.unwrap_or_default()
+		{
+			return true;
+		}
+		match action {
+			tp_ethereum::TransactionAction::Call(target) => api
+				.confidentiality_policy(block_hash, target)
+				.map(|policy| !policy.is_public())
+				.unwrap_or_default(),
+			tp_ethereum::TransactionAction::Create => false,
+		}
+	}
+
+	/// The public key registered by `from` at `block_hash`.
+	fn registered_public_key(&self, block_hash: B::Hash, from: H160) -> Option<[u8; 64]> {
+		let pubkey = self
+			.client
+			.runtime_api()
+			.account_public(block_hash, from)
+			.unwrap_or_default();
+		<[u8; 64]>::try_from(pubkey).ok()
+	}
+
+	/// Seal the input of a confidential Legacy or EIP-2930 transaction of `from` to the public
+	/// key it registered, under [`tp_io::envelope::sealed_input_aad`].
+	fn seal_input(
+		&self,
+		block_hash: B::Hash,
+		from: H160,
+		chain_id: Option<u64>,
+		nonce: U256,
+		input: &[u8],
+	) -> RpcResult<Vec<u8>> {
+		let pubkey = self
+			.registered_public_key(block_hash, from)
+			.ok_or_else(|| internal_err(NO_REGISTERED_PUBLIC_KEY))?;
+		let Some(enclave_key) = self.enclave_key.as_ref() else {
+			return Err(internal_err("enclave key unavailable"));
+		};
+		let aad = tp_io::envelope::sealed_input_aad(chain_id, nonce.low_u128());
+		enclave_key
+			.encrypt(input, &aad, &pubkey)
+			.map_err(|_| internal_err("cannot encrypted transaction"))
+	}
+
 	fn convert_transaction(
 		&self,
 		block_hash: B::Hash,
@@ -301,3 +371,24 @@ where
 		}
 	}
 }
+
+const NO_REGISTERED_PUBLIC_KEY: &str =
+	"confidential Legacy and EIP-2930 transactions require a registered public key";
+
+/// JSON-RPC error code of a confidential transaction whose payload cannot be decrypted,
+/// telling the wallet that it was not encrypted to the enclave key with the sender key.
+pub const DECRYPTION_FAILED_ERROR_CODE: i32 = -32010;
+
+fn pool_err(err: impl IntoPoolError) -> jsonrpsee::core::Error {
+	match err.into_pool_error() {
+		Ok(PError::InvalidTransaction(InvalidTransaction::Custom(
+			tp_ethereum::DECRYPTION_FAILED,
+		))) => crate::err(
+			DECRYPTION_FAILED_ERROR_CODE,
+			"confidential transaction cannot be decrypted",
+			None,
+		),
+		Ok(err) => internal_err(format::Geth::pool_error(err)),
+		Err(err) => internal_err(format::Geth::pool_error(err)),
+	}
+}
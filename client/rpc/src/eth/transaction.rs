@@ -16,9 +16,12 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use std::sync::Arc;
+use std::{
+	sync::Arc,
+	time::{SystemTime, UNIX_EPOCH},
+};
 
-use ethereum_types::{H256, U256, U64};
+use ethereum_types::{H160, H256, U256, U64};
 use jsonrpsee::core::RpcResult;
 use tp_ethereum::TransactionV2 as EthereumTransaction;
 // Substrate
@@ -205,6 +208,156 @@ where
 		}
 	}
 
+	pub async fn view_transaction(
+		&self,
+		hash: H256,
+		challenge: ViewChallenge,
+	) -> RpcResult<Option<DisclosedTransaction>> {
+		let view_public = recover_challenge_signer(
+			tc_confidential::view_challenge(hash, challenge.expiry.as_u64()),
+			challenge.expiry,
+			&challenge.signature,
+		)
+		.map_err(|err| crate::err(VIEW_KEY_ERROR_CODE, err, None))?;
+		let (block_info, index) = self.block_info_by_eth_transaction_hash(hash).await?;
+		let (Some(block), Some(statuses)) = (&block_info.block, &block_info.statuses) else {
+			return Ok(None);
+		};
+		let (transaction, status) = (&block.transactions[index], &statuses[index]);
+
+		// Check the grant at the best block so that a revocation applies at once.
+		let commitment = H256::from(keccak_256(&view_public));
+		let granted = self
+			.client
+			.runtime_api()
+			.is_view_key_granted(self.client.info().best_hash, status.from, commitment)
+			.map_err(|err| internal_err(format!("Fetch view key grant failed: {err}")))?;
+		if !granted {
+			return Err(crate::err(
+				VIEW_KEY_ERROR_CODE,
+				"view key not granted by the sender",
+				None,
+			));
+		}
+
+		// The input of Legacy and EIP-2930 transactions is sealed when their sender registered
+		// a public key.
+		let sealed_input = !matches!(transaction, EthereumTransaction::EIP1559(_))
+			&& !self
+				.client
+				.runtime_api()
+				.account_public(block_info.substrate_hash, status.from)
+				.unwrap_or_default()
+				.is_empty();
+		let essentials = if transaction.is_universal() && !sealed_input {
+			transaction.essentials()
+		} else {
+			let enclave_key = self
+				.enclave_key
+				.as_ref()
+				.ok_or_else(|| internal_err("enclave key unavailable"))?;
+			let pubkey = crate::public_key(transaction)
+				.map_err(|_| internal_err("cannot recover public key"))?;
+			if sealed_input {
+				transaction.essentials().and_then(|mut essentials| {
+					let aad = tp_io::envelope::sealed_input_aad(
+						transaction.chain_id(),
+						essentials.nonce.low_u128(),
+					);
+					essentials.input = enclave_key
+						.decrypt(&essentials.input, &aad, &pubkey)
+						.map_err(|_| ethereum::Error::BadDecrypte)?;
+					Ok(essentials)
+				})
+			} else {
+				transaction.essentials_with_decrypt(|msg, aad| {
+					enclave_key
+						.decrypt(msg, aad.as_fixed_bytes(), &pubkey)
+						.map_err(|_| ethereum::Error::BadDecrypte)
+				})
+			}
+		}
+		.map_err(|_| internal_err("cannot decrypt transaction"))?;
+
+		let receipt = self
+			.transaction_receipt(&block_info, hash, index, Some(status.from))
+			.await?;
+
+		Ok(Some(DisclosedTransaction {
+			transaction: transaction_build(
+				transaction,
+				Some(block),
+				Some(status),
+				Some(block_info.base_fee),
+			),
+			input: Bytes(essentials.input),
+			receipt,
+		}))
+	}
+
+	pub async fn authenticated_transaction_receipt(
+		&self,
+		hash: H256,
+		challenge: ReceiptChallenge,
+	) -> RpcResult<Option<Receipt>> {
+		let requester = verify_receipt_challenge(hash, &challenge)?;
+		let (block_info, index) = self.block_info_by_eth_transaction_hash(hash).await?;
+		self.transaction_receipt(&block_info, hash, index, Some(requester))
+			.await
+	}
+
+	/// Seal the data of a non-perceptible log of `transaction_hash` to the public key
+	/// `receiver` registered at `substrate_hash`, in an envelope under a fresh random nonce with
+	/// the transaction hash as associated data. Empty if the receiver registered no public key.
+	fn seal_log_data(
+		&self,
+		substrate_hash: B::Hash,
+		receiver: H160,
+		transaction_hash: H256,
+		data: &[u8],
+	) -> RpcResult<Bytes> {
+		let pubkey = self
+			.client
+			.runtime_api()
+			.account_public(substrate_hash, receiver)
+			.unwrap_or_default();
+		let Ok(pubkey) = <[u8; 64]>::try_from(pubkey) else {
+			return Ok(Bytes(Vec::new()));
+		};
+		self.enclave_key
+			.as_ref()
+			.ok_or_else(|| internal_err("enclave key unavailable"))?
+			.encrypt(data, transaction_hash.as_fixed_bytes(), &pubkey)
+			.map(Bytes)
+			.map_err(|_| internal_err("failed to seal log data"))
+	}
+
+	pub async fn state_diff(&self, hash: H256) -> RpcResult<Option<StateDiff>> {
+		let (block_info, _) = self.block_info_by_eth_transaction_hash(hash).await?;
+		if block_info.block.is_none() {
+			return Ok(None);
+		}
+
+		let api = self.client.runtime_api();
+		let substrate_hash = block_info.substrate_hash;
+		// Runtimes before version 9 never capture state diffs.
+		match api.api_version::<dyn EthereumRuntimeRPCApi<B>>(substrate_hash) {
+			Ok(Some(api_version)) if api_version >= 9 => {}
+			Ok(_) => return Ok(None),
+			Err(_) => return Err(internal_err("failed to retrieve Runtime Api version")),
+		}
+		let state_diffs = api
+			.current_state_diffs(substrate_hash)
+			.map_err(|err| internal_err(format!("Fetch state diffs failed: {err}")))?;
+
+		Ok(state_diffs
+			.into_iter()
+			.find(|state_diff| state_diff.transaction_hash == hash)
+			.map(state_diff_build))
+	}
+
+	/// Build the receipt of the transaction at `index`. The logs addressed to `from` are
+	/// included, so `from` must be authenticated by the caller.
 	pub async fn transaction_receipt(
 		&self,
 		block_info: &BlockInfo<B::Hash>,
@@ -349,38 +502,57 @@ where
 
 					if FilteredParams::topics_in_bloom(logs_bloom, &address_bloom_filter) {
 						let rlp_logs = logs.iter().map(rlp::encode);
-						output_logs.extend(_percept_logs.iter().filter_map(|(i, log)| {
-							if log.filter(_from.into()) {
-								Some(Log {
-									address: log.address,
-									topics: log.topics.clone(),
-									data: Bytes(log.data.clone()),
-									block_hash: Some(block_hash),
-									block_number: Some(block.header.number),
-									transaction_hash: Some(status.transaction_hash),
-									transaction_index: Some(status.transaction_index.into()),
-									log_index: Some(U256::from(
-										(pre_receipts_log_index.unwrap_or(0)) + *i as u32,
-									)),
-									transaction_log_index: Some(U256::from(*i)),
-									removed: false,
-									proof: Some(
-										tp_ethereum::order_generate_proof::<
-											sp_core::KeccakHasher,
-											_,
-											_,
-										>(rlp_logs.clone(), *i)
-										.unwrap()
-										.1
-										.iter()
-										.map(|v| Bytes(v.to_vec()))
-										.collect(),
-									),
-								})
+						for (i, log) in _percept_logs
+							.iter()
+							.filter(|(_, log)| log.filter(_from.into()))
+						{
+							// Non-perceptible data is only committed to in the receipt, the data
+							// itself is read from the state and sealed to the receiver.
+							let data = if log.log_type == Some(tp_ethereum::LogType::NonPerceptible)
+							{
+								let data = self
+									.client
+									.runtime_api()
+									.non_perceptible_log_data(
+										substrate_hash,
+										status.transaction_hash,
+										*i as u32,
+									)
+									.map_err(|err| {
+										internal_err(format!("Fetch log data failed: {err}"))
+									})?
+									.ok_or_else(|| internal_err("log data not found"))?;
+								self.seal_log_data(
+									substrate_hash,
+									_from,
+									status.transaction_hash,
+									&data,
+								)?
 							} else {
-								None
-							}
-						}))
+								Bytes(log.data.clone())
+							};
+							let (_, proof) = tp_ethereum::order_generate_proof::<
+								sp_core::KeccakHasher,
+								_,
+								_,
+							>(rlp_logs.clone(), *i)
+							.unwrap();
+							output_logs.push(Log {
+								address: log.address,
+								topics: log.topics.clone(),
+								data,
+								block_hash: Some(block_hash),
+								block_number: Some(block.header.number),
+								transaction_hash: Some(status.transaction_hash),
+								transaction_index: Some(status.transaction_index.into()),
+								log_index: Some(U256::from(
+									(pre_receipts_log_index.unwrap_or(0)) + *i as u32,
+								)),
+								transaction_log_index: Some(U256::from(*i)),
+								removed: false,
+								proof: Some(proof.iter().map(|v| Bytes(v.to_vec())).collect()),
+							});
+						}
 					}
 				}
 
@@ -410,3 +582,77 @@ where
 		}
 	}
 }
+
+fn state_diff_build(state_diff: tp_rpc::StateDiff) -> StateDiff {
+	fn change<T>(change: tp_rpc::Change<T>) -> Change<T> {
+		Change {
+			from: change.before,
+			to: change.after,
+		}
+	}
+
+	StateDiff {
+		transaction_hash: state_diff.transaction_hash,
+		accounts: state_diff
+			.accounts
+			.into_iter()
+			.map(|account| {
+				let diff = AccountDiff {
+					balance: account.balance.map(change),
+					nonce: account.nonce.map(change),
+					code: account.code.map(|code| Change {
+						from: Bytes(code.before),
+						to: Bytes(code.after),
+					}),
+					storage: account
+						.storage
+						.into_iter()
+						.map(|(index, slot)| (index, change(slot)))
+						.collect(),
+				};
+				(account.address, diff)
+			})
+			.collect(),
+	}
+}
+
+/// Error code of view requests whose challenge is invalid or whose view key is not granted.
+pub const VIEW_KEY_ERROR_CODE: i32 = -32012;
+
+/// Receipt and view challenges are rejected when they expire further than this many seconds
+/// ahead.
+const MAX_CHALLENGE_VALIDITY: u64 = 300;
+
+/// The account that signed `challenge` for the receipt of `hash`.
+fn verify_receipt_challenge(hash: H256, challenge: &ReceiptChallenge) -> RpcResult<H160> {
+	let pubkey = recover_challenge_signer(
+		tc_confidential::receipt_challenge(hash, challenge.expiry.as_u64()),
+		challenge.expiry,
+		&challenge.signature,
+	)
+	.map_err(internal_err)?;
+	Ok(H160::from(H256::from(keccak_256(&pubkey))))
+}
+
+/// The public key that signed the challenge `digest` expiring at `expiry`.
+fn recover_challenge_signer(
+	digest: H256,
+	expiry: U64,
+	signature: &Bytes,
+) -> Result<[u8; 64], String> {
+	let now = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map_err(|err| format!("system time error: {err}"))?
+		.as_secs();
+	let expiry = expiry.as_u64();
+	if expiry < now || expiry > now.saturating_add(MAX_CHALLENGE_VALIDITY) {
+		return Err("challenge expired or too far in the future".into());
+	}
+	let signature: [u8; 65] = signature
+		.0
+		.as_slice()
+		.try_into()
+		.map_err(|_| "challenge signature must be 65 bytes".to_string())?;
+	sp_io::crypto::secp256k1_ecdsa_recover(&signature, digest.as_fixed_bytes())
+		.map_err(|_| "invalid challenge signature".into())
+}
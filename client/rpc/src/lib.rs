@@ -27,18 +27,24 @@
 #![warn(unused_crate_dependencies)]
 
 mod cache;
+mod debug;
 mod eth;
 mod eth_pubsub;
 mod net;
 mod signer;
+#[cfg(feature = "sql")]
+mod trace;
 #[cfg(feature = "txpool")]
 mod txpool;
 mod web3;
 
+#[cfg(feature = "sql")]
+pub use self::trace::Trace;
 #[cfg(feature = "txpool")]
 pub use self::txpool::TxPool;
 pub use self::{
 	cache::{EthBlockDataCacheTask, EthTask},
+	debug::Debug,
 	eth::{format, pending, EstimateGasAdapter, Eth, EthConfig, EthFilter},
 	eth_pubsub::{EthPubSub, EthereumSubIdProvider},
 	net::Net,
@@ -48,7 +54,8 @@ pub use self::{
 #[cfg(feature = "txpool")]
 pub use tc_rpc_core::TxPoolApiServer;
 pub use tc_rpc_core::{
-	EthApiServer, EthFilterApiServer, EthPubSubApiServer, NetApiServer, Web3ApiServer,
+	DebugApiServer, EthApiServer, EthFilterApiServer, EthPubSubApiServer, NetApiServer,
+	TraceApiServer, Web3ApiServer,
 };
 pub use tc_storage::{
 	OverrideHandle, RuntimeApiStorageOverride, SchemaV3Override, StorageOverride,
@@ -0,0 +1,203 @@
+use std::sync::Arc;
+
+use ethereum_types::H256;
+use jsonrpsee::core::{async_trait, RpcResult};
+// Substrate
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::{Block as BlockT, UniqueSaturatedInto};
+// Frontier
+use tc_db::sql::FilteredTrace;
+use tc_rpc_core::{types::*, TraceApiServer};
+use tp_rpc::CallType;
+
+use crate::{frontier_backend_client, internal_err};
+
+/// Maximum number of call traces a single query may return.
+const MAX_TRACES: usize = 10000;
+
+/// Trace API implementation, serving the call traces the `TraceWorker` indexed in the SQL
+/// backend, so that no query re-executes a block.
+pub struct Trace<B: BlockT, C> {
+	client: Arc<C>,
+	backend: Arc<tc_db::sql::Backend<B>>,
+}
+
+impl<B: BlockT, C> Trace<B, C> {
+	pub fn new(client: Arc<C>, backend: Arc<tc_db::sql::Backend<B>>) -> Self {
+		Self { client, backend }
+	}
+}
+
+impl<B, C> Trace<B, C>
+where
+	B: BlockT<Hash = H256>,
+	C: HeaderBackend<B> + 'static,
+{
+	async fn block_number(&self, number: Option<BlockNumberOrHash>) -> RpcResult<Option<u64>> {
+		let Some(id) = frontier_backend_client::native_block_id::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			number,
+		)
+		.await?
+		else {
+			return Err(internal_err(
+				"trace queries are not supported on the pending block",
+			));
+		};
+		Ok(self
+			.client
+			.block_number_from_id(&id)
+			.map_err(|err| internal_err(format!("Fetch block number failed: {err}")))?
+			.map(UniqueSaturatedInto::unique_saturated_into))
+	}
+}
+
+#[async_trait]
+impl<B, C> TraceApiServer for Trace<B, C>
+where
+	B: BlockT<Hash = H256>,
+	C: HeaderBackend<B> + 'static,
+{
+	async fn trace_block(
+		&self,
+		number: BlockNumberOrHash,
+	) -> RpcResult<Option<Vec<LocalizedTrace>>> {
+		let Some(number) = self.block_number(Some(number)).await? else {
+			return Ok(None);
+		};
+		let traces = self
+			.backend
+			.filter_traces(number, number, Vec::new(), Vec::new(), 0, None)
+			.await
+			.map_err(internal_err)?;
+		Ok(Some(traces.into_iter().map(localized_trace).collect()))
+	}
+
+	async fn trace_transaction(&self, hash: H256) -> RpcResult<Option<Vec<LocalizedTrace>>> {
+		let traces = self
+			.backend
+			.transaction_traces(hash)
+			.await
+			.map_err(internal_err)?;
+		if traces.is_empty() {
+			return Ok(None);
+		}
+		Ok(Some(traces.into_iter().map(localized_trace).collect()))
+	}
+
+	async fn trace_filter(&self, filter: TraceFilter) -> RpcResult<Vec<LocalizedTrace>> {
+		let best_number: u64 = self.client.info().best_number.unique_saturated_into();
+		let from_block = self
+			.block_number(filter.from_block)
+			.await?
+			.ok_or_else(|| internal_err("fromBlock not found"))?;
+		let to_block = self
+			.block_number(filter.to_block)
+			.await?
+			.unwrap_or(best_number)
+			.min(best_number);
+
+		let count = filter.count.unwrap_or(MAX_TRACES);
+		if count > MAX_TRACES {
+			return Err(internal_err(format!("count must be at most {MAX_TRACES}")));
+		}
+		// Without a count, one more trace tells a complete result from a truncated one.
+		let limit = if filter.count.is_some() {
+			count
+		} else {
+			count + 1
+		};
+
+		let traces = self
+			.backend
+			.filter_traces(
+				from_block,
+				to_block,
+				filter.from_address.unwrap_or_default(),
+				filter.to_address.unwrap_or_default(),
+				filter.after.unwrap_or(0),
+				Some(limit),
+			)
+			.await
+			.map_err(internal_err)?;
+		if traces.len() > count {
+			return Err(internal_err(format!(
+				"query returned more than {MAX_TRACES} results, page it with after and count"
+			)));
+		}
+
+		Ok(traces.into_iter().map(localized_trace).collect())
+	}
+}
+
+fn localized_trace(filtered: FilteredTrace) -> LocalizedTrace {
+	let frame = filtered.trace;
+	let succeeded = frame.error.is_none();
+	let (trace_type, action, result) = match frame.call_type {
+		CallType::Create | CallType::Create2 => (
+			"create",
+			TraceAction::Create {
+				creation_method: if frame.call_type == CallType::Create {
+					"create".to_string()
+				} else {
+					"create2".to_string()
+				},
+				from: frame.from,
+				value: frame.value,
+				gas: frame.gas.into(),
+				init: Bytes(frame.input),
+			},
+			succeeded.then(|| TraceResult::Create {
+				gas_used: frame.gas_used.into(),
+				address: frame.to,
+				code: Bytes(frame.output),
+			}),
+		),
+		CallType::SelfDestruct => (
+			"suicide",
+			TraceAction::Suicide {
+				address: frame.from,
+				refund_address: frame.to,
+				balance: frame.value,
+			},
+			None,
+		),
+		call_type => (
+			"call",
+			TraceAction::Call {
+				call_type: match call_type {
+					CallType::StaticCall => "staticcall",
+					CallType::DelegateCall => "delegatecall",
+					CallType::CallCode => "callcode",
+					_ => "call",
+				}
+				.to_string(),
+				from: frame.from,
+				to: frame.to,
+				value: frame.value,
+				gas: frame.gas.into(),
+				input: Bytes(frame.input),
+			},
+			succeeded.then(|| TraceResult::Call {
+				gas_used: frame.gas_used.into(),
+				output: Bytes(frame.output),
+			}),
+		),
+	};
+
+	LocalizedTrace {
+		action,
+		result,
+		error: frame
+			.error
+			.map(|error| String::from_utf8_lossy(&error).into_owned()),
+		subtraces: frame.subtraces,
+		trace_address: frame.trace_address,
+		transaction_hash: filtered.transaction_hash,
+		transaction_position: filtered.transaction_index,
+		block_hash: filtered.ethereum_block_hash,
+		block_number: filtered.block_number,
+		trace_type: trace_type.to_string(),
+	}
+}
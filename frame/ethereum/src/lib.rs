@@ -3,25 +3,27 @@
 #![allow(clippy::comparison_chain, clippy::large_enum_variant)]
 #![warn(unused_crate_dependencies)]
 
-// #[cfg(all(feature = "std", test))]
-// mod mock;
-// #[cfg(all(feature = "std", test))]
-// mod tests;
+#[cfg(all(feature = "std", test))]
+mod mock;
+#[cfg(all(feature = "std", test))]
+mod tests;
+mod tracing;
 
 use ethereum_types::{Bloom, BloomInput, H160, H256, H64, U256};
 use evm::ExitReason;
 use scale_codec::{Decode, Encode, MaxEncodedLen};
 use scale_info::TypeInfo;
 pub use tp_ethereum::{
-	AccessListItem, BlockV2 as Block, LegacyTransactionMessage, Log, Receipt, TransactionAction,
-	TransactionV2 as Transaction,
+	AccessListItem, BlockV2 as Block, LegacyTransactionMessage, Log, LogType, Receipt,
+	TransactionAction, TransactionV2 as Transaction,
 };
 // Substrate
 use frame_support::{
 	dispatch::{
 		DispatchErrorWithPostInfo, DispatchInfo, DispatchResultWithPostInfo, Pays, PostDispatchInfo,
 	},
-	traits::{EnsureOrigin, Get, PalletInfoAccess, Time},
+	storage::{with_storage_layer, StoragePrefixedMap},
+	traits::{EnsureOrigin, Get, PalletInfoAccess, StorageVersion, Time},
 	weights::Weight,
 };
 use frame_system::{pallet_prelude::OriginFor, CheckWeight, WeightInfo};
@@ -31,7 +33,7 @@ use sp_runtime::{
 	transaction_validity::{
 		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransactionBuilder,
 	},
-	RuntimeDebug, SaturatedConversion,
+	DispatchError, DispatchResult, RuntimeDebug, SaturatedConversion,
 };
 use sp_std::{marker::PhantomData, prelude::*};
 // Frontier
@@ -44,10 +46,7 @@ use fp_evm::{
 use fp_storage::{EthereumStorageSchema, PALLET_ETHEREUM_SCHEMA};
 use pallet_evm::{BlockHashMapping, FeeCalculator, GasWeightMapping, Runner};
 use tenet::model::PoM;
-pub use tp_rpc::TransactionStatus;
-
-pub const ENABLE_POC: bool = false;
-pub const ENABLE_POM: bool = false;
+pub use tp_rpc::{ConfidentialityPolicy, StateDiff, TransactionStatus};
 
 #[derive(Clone, Eq, PartialEq, RuntimeDebug)]
 #[derive(Encode, Decode, MaxEncodedLen, TypeInfo)]
@@ -55,6 +54,18 @@ pub enum RawOrigin {
 	EthereumTransaction(H160),
 }
 
+/// Off-chain index prefix under which the PoCs pruned in a block are archived.
+pub const POC_ARCHIVE_PREFIX: &[u8] = b"tenet/poc-archive/";
+
+/// Off-chain index key of the PoCs pruned in block `number`, stored as a SCALE encoded
+/// `Vec<(H256, Vec<u8>)>` of transaction hashes and unsigned PoCs, the RLP encoded lists of
+/// input and output hashes.
+pub fn poc_archive_key<N: Encode>(number: N) -> Vec<u8> {
+	let mut key = POC_ARCHIVE_PREFIX.to_vec();
+	key.extend(number.encode());
+	key
+}
+
 pub fn ensure_ethereum_transaction<OuterOrigin>(o: OuterOrigin) -> Result<H160, &'static str>
 where
 	OuterOrigin: Into<Result<RawOrigin, OuterOrigin>>,
@@ -156,6 +167,26 @@ pub enum PostLogContent {
 	OnlyBlockHash,
 }
 
+/// Why the transactions of an Ethereum block imported as a pre-log were rolled back.
+#[derive(Clone, Copy, Eq, PartialEq, RuntimeDebug, Encode, Decode, TypeInfo)]
+pub enum PreLogError {
+	/// The gas limits of the transactions add up to more weight than a block allows.
+	WeightExceeded,
+	/// The signature of the transaction at `index` is invalid.
+	InvalidSignature { index: u32 },
+	/// The transaction at `index` failed validation.
+	InvalidTransaction { index: u32 },
+	/// The transaction at `index` could not be applied.
+	ApplyFailed { index: u32 },
+	/// The transaction at `index` is signed for neither this chain nor the pre-log source
+	/// chain.
+	UnexpectedChainId { index: u32 },
+}
+
+// The sender of the Ethereum transaction being executed, which a contract it creates must
+// differ from.
+environmental::environmental!(transaction_origin: H160);
+
 pub use self::pallet::*;
 
 #[frame_support::pallet]
@@ -164,7 +195,11 @@ pub mod pallet {
 	use frame_support::pallet_prelude::*;
 	use frame_system::pallet_prelude::*;
 
+	/// The current storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	#[pallet::without_storage_info]
 	pub struct Pallet<T>(PhantomData<T>);
 
@@ -181,6 +216,28 @@ pub mod pallet {
 		type PostLogContent: Get<PostLogContent>;
 		/// The maximum length of the extra data in the Executed event.
 		type ExtraDataLength: Get<u32>;
+		/// Whether the PoC output commitment includes the digest of the EVM storage written
+		/// by the transaction.
+		type CommitWriteSet: Get<bool>;
+		/// Origin allowed to switch PoC, PoM and state diff generation on and off.
+		type ProofOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+		/// Number of blocks a PoC is kept in storage before being pruned. Zero keeps PoCs
+		/// forever.
+		type PocRetentionPeriod: Get<BlockNumberFor<Self>>;
+		/// Maximum weight spent pruning expired PoCs in a single block.
+		type MaxPocPruneWeight: Get<Weight>;
+		/// Whether pruned PoCs are written to the off-chain index before removal.
+		type ArchivePrunedPoc: Get<bool>;
+		/// Origin allowed to select the state key and re-encrypt the state under it.
+		type StateKeyOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+		/// Weight of re-encrypting one byte of a state value, on top of its read and write. It
+		/// must let the largest value, usually the runtime code, fit in the idle weight of a
+		/// block or re-encryption stalls on it.
+		type ReencryptionWeightPerByte: Get<Weight>;
+		/// Maximum number of view keys an account can grant at once.
+		type MaxViewKeys: Get<u32>;
+		/// Origin allowed to set the chain whose blocks are replayed as pre-logs.
+		type PreLogOrigin: EnsureOrigin<Self::RuntimeOrigin>;
 	}
 
 	#[pallet::hooks]
@@ -209,27 +266,23 @@ pub mod pallet {
 			Pending::<T>::kill();
 		}
 
-		fn on_initialize(_: BlockNumberFor<T>) -> Weight {
-			let mut weight = T::SystemWeightInfo::kill_storage(1);
+		fn on_initialize(n: BlockNumberFor<T>) -> Weight {
+			CurrentStateDiffs::<T>::kill();
+			let mut weight = T::SystemWeightInfo::kill_storage(2);
+			// Every holder of a state key reported it before the switch, so a node missing the
+			// selected key cannot encrypt the state of the block and must not import it.
+			assert!(
+				tp_io::state::set_state_key(StateKeyId::<T>::get()),
+				"the state master key selected on chain is not installed"
+			);
+			weight = weight.saturating_add(T::DbWeight::get().reads(1));
+			weight = weight.saturating_add(Self::prune_pocs(n));
 
 			// If the digest contain an existing ethereum block(encoded as PreLog), If contains,
 			// execute the imported block firstly and disable transact dispatch function.
 			if let Ok(log) = fp_consensus::find_pre_log(&frame_system::Pallet::<T>::digest()) {
 				let PreLog::Block(block) = log;
-
-				for transaction in block.transactions {
-					let source = Self::recover_signer(&transaction).expect(
-						"pre-block transaction signature invalid; the block cannot be built",
-					);
-
-					Self::validate_transaction_in_block(source, &transaction).expect(
-						"pre-block transaction verification failed; the block cannot be built",
-					);
-					let (r, _) = Self::apply_validated_transaction(source, transaction)
-						.expect("pre-block apply transaction failed; the block cannot be built");
-
-					weight = weight.saturating_add(r.actual_weight.unwrap_or_default());
-				}
+				weight = weight.saturating_add(Self::apply_pre_log(block));
 			}
 			// Account for `on_finalize` weight:
 			//	- read: frame_system::Pallet::<T>::digest()
@@ -239,13 +292,22 @@ pub mod pallet {
 			weight.saturating_add(T::DbWeight::get().reads_writes(2, 2))
 		}
 
+		fn on_idle(_: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			Self::reencrypt_state_step(remaining_weight)
+		}
+
 		fn on_runtime_upgrade() -> Weight {
 			frame_support::storage::unhashed::put::<EthereumStorageSchema>(
 				PALLET_ETHEREUM_SCHEMA,
 				&EthereumStorageSchema::V3,
 			);
 
-			T::DbWeight::get().writes(1)
+			// read: on-chain storage version
+			let mut weight = T::DbWeight::get().reads_writes(1, 1);
+			if StorageVersion::get::<Self>() < 1 {
+				weight = weight.saturating_add(Self::migrate_to_v1());
+			}
+			weight
 		}
 	}
 
@@ -258,10 +320,15 @@ pub mod pallet {
 		#[pallet::call_index(0)]
 		#[pallet::weight({
 			let without_base_extrinsic_weight = true;
-			<T as pallet_evm::Config>::GasWeightMapping::gas_to_weight({
+			let gas_limit: u64 = {
 				let gas_limit: U256 = transaction.gas_limit();
 				gas_limit.unique_saturated_into()
-			}, without_base_extrinsic_weight)
+			};
+			<T as pallet_evm::Config>::GasWeightMapping::gas_to_weight(
+				gas_limit,
+				without_base_extrinsic_weight,
+			)
+			.saturating_add(Pallet::<T>::state_diff_weight_limit(gas_limit))
 		})]
 		pub fn transact(
 			origin: OriginFor<T>,
@@ -269,13 +336,110 @@ pub mod pallet {
 		) -> DispatchResultWithPostInfo {
 			let source = ensure_ethereum_transaction(origin)?;
 			// Disable transact functionality if PreLog exist.
-			assert!(
+			ensure!(
 				fp_consensus::find_pre_log(&frame_system::Pallet::<T>::digest()).is_err(),
-				"pre log already exists; block is invalid",
+				Error::<T>::PreLogExists,
 			);
 
 			Self::apply_validated_transaction(source, transaction).map(|(post_info, _)| post_info)
 		}
+
+		/// Switch generation of a PoC for every executed transaction on or off.
+		#[pallet::call_index(1)]
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_enable_poc(origin: OriginFor<T>, enabled: bool) -> DispatchResult {
+			T::ProofOrigin::ensure_origin(origin)?;
+			EnablePoc::<T>::put(enabled);
+			Self::deposit_event(Event::PocEnabled { enabled });
+			Ok(())
+		}
+
+		/// Switch generation of a PoM for every executed call on or off.
+		#[pallet::call_index(2)]
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_enable_pom(origin: OriginFor<T>, enabled: bool) -> DispatchResult {
+			T::ProofOrigin::ensure_origin(origin)?;
+			EnablePom::<T>::put(enabled);
+			Self::deposit_event(Event::PomEnabled { enabled });
+			Ok(())
+		}
+
+		/// Encrypt new state under the state master key `key_id` from the next block on and
+		/// re-encrypt the whole state under it, a few keys per block in the idle time of blocks.
+		/// The key must be registered with [`Pallet::register_state_key`] and reported by all of
+		/// its holders.
+		#[pallet::call_index(3)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 3))]
+		pub fn reencrypt_state(origin: OriginFor<T>, key_id: u32) -> DispatchResult {
+			T::StateKeyOrigin::ensure_origin(origin)?;
+			let (pending_id, holders) =
+				PendingStateKey::<T>::get().ok_or(Error::<T>::StateKeyNotRegistered)?;
+			ensure!(pending_id == key_id, Error::<T>::StateKeyNotRegistered);
+			ensure!(holders.is_empty(), Error::<T>::StateKeyNotHeld);
+			PendingStateKey::<T>::kill();
+			StateKeyId::<T>::put(key_id);
+			StateReencryptionCursor::<T>::put(Vec::<u8>::new());
+			Self::deposit_event(Event::StateReencryptionStarted { key_id });
+			Ok(())
+		}
+
+		/// Announce the state master key `key_id` the state will be re-encrypted under, along
+		/// with the accounts of the nodes that must report holding it before the switch.
+		#[pallet::call_index(4)]
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn register_state_key(
+			origin: OriginFor<T>,
+			key_id: u32,
+			holders: Vec<T::AccountId>,
+		) -> DispatchResult {
+			T::StateKeyOrigin::ensure_origin(origin)?;
+			PendingStateKey::<T>::put((key_id, holders));
+			Self::deposit_event(Event::StateKeyRegistered { key_id });
+			Ok(())
+		}
+
+		/// Report that the node of the signer installed the registered state master key.
+		#[pallet::call_index(5)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn report_state_key(origin: OriginFor<T>, key_id: u32) -> DispatchResult {
+			let who: T::AccountId = ensure_signed(origin)?;
+			let (pending_id, mut holders) =
+				PendingStateKey::<T>::get().ok_or(Error::<T>::StateKeyNotRegistered)?;
+			ensure!(pending_id == key_id, Error::<T>::StateKeyNotRegistered);
+			let index = holders
+				.iter()
+				.position(|holder| *holder == who)
+				.ok_or(Error::<T>::NotAStateKeyHolder)?;
+			holders.swap_remove(index);
+			let remaining = holders.len() as u32;
+			PendingStateKey::<T>::put((key_id, holders));
+			Self::deposit_event(Event::StateKeyReported { key_id, remaining });
+			Ok(())
+		}
+
+		/// Set the id of the chain whose blocks are replayed as pre-logs, or `None` to only
+		/// replay transactions signed for this chain.
+		#[pallet::call_index(6)]
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_pre_log_source_chain_id(
+			origin: OriginFor<T>,
+			chain_id: Option<u64>,
+		) -> DispatchResult {
+			T::PreLogOrigin::ensure_origin(origin)?;
+			PreLogSourceChainId::<T>::set(chain_id);
+			Self::deposit_event(Event::PreLogSourceChainIdSet { chain_id });
+			Ok(())
+		}
+
+		/// Switch capture of a state diff for every executed transaction on or off.
+		#[pallet::call_index(7)]
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_enable_state_diff(origin: OriginFor<T>, enabled: bool) -> DispatchResult {
+			T::ProofOrigin::ensure_origin(origin)?;
+			EnableStateDiff::<T>::put(enabled);
+			Self::deposit_event(Event::StateDiffEnabled { enabled });
+			Ok(())
+		}
 	}
 
 	#[pallet::event]
@@ -290,10 +454,59 @@ pub mod pallet {
 			extra_data: Vec<u8>,
 		},
 
-		RegisterPublic {
-			from: H160,
-			public_key: Vec<u8>,
+		/// An account registered the public key its confidential data is sealed to.
+		PublicKeyRegistered { account: H160, public_key: Vec<u8> },
+
+		/// PoC generation was switched on or off.
+		PocEnabled { enabled: bool },
+
+		/// PoM generation was switched on or off.
+		PomEnabled { enabled: bool },
+
+		/// Expired PoCs were removed from storage.
+		PocsPruned { count: u32 },
+
+		/// The state key was changed and re-encryption of the state under it started.
+		StateReencryptionStarted { key_id: u32 },
+
+		/// Every state value was re-encrypted under the current state key.
+		StateReencryptionCompleted,
+
+		/// A state key was registered for re-encryption, pending reports from its holders.
+		StateKeyRegistered { key_id: u32 },
+
+		/// A holder reported the registered state key, `remaining` holders have yet to.
+		StateKeyReported { key_id: u32, remaining: u32 },
+
+		/// An account granted a view key over its confidential transactions.
+		ViewKeyGranted { owner: H160, commitment: H256 },
+
+		/// An account revoked a view key.
+		ViewKeyRevoked { owner: H160, commitment: H256 },
+
+		/// An account replaced its registered public key.
+		PublicKeyRotated { account: H160, public_key: Vec<u8> },
+
+		/// An account removed its registered public key.
+		PublicKeyRevoked { account: H160 },
+
+		/// A contract declared its confidentiality policy.
+		ConfidentialityPolicyDeclared {
+			contract: H160,
+			policy: ConfidentialityPolicy,
 		},
+
+		/// The transactions of an Ethereum block imported as a pre-log were executed.
+		PreLogApplied { transactions: u32 },
+
+		/// The transactions of an Ethereum block imported as a pre-log were rolled back.
+		PreLogRejected { error: PreLogError },
+
+		/// The chain whose blocks are replayed as pre-logs was set.
+		PreLogSourceChainIdSet { chain_id: Option<u64> },
+
+		/// State diff capture was switched on or off.
+		StateDiffEnabled { enabled: bool },
 	}
 
 	#[pallet::error]
@@ -302,6 +515,39 @@ pub mod pallet {
 		InvalidSignature,
 		/// Pre-log is present, therefore transact is not allowed.
 		PreLogExists,
+		/// The state key is not the one registered for re-encryption.
+		StateKeyNotRegistered,
+		/// Some holders have not reported the registered state key yet.
+		StateKeyNotHeld,
+		/// The signer is not among the pending holders of the registered state key.
+		NotAStateKeyHolder,
+		/// The view key is already granted.
+		ViewKeyAlreadyGranted,
+		/// The account already granted `MaxViewKeys` view keys.
+		TooManyViewKeys,
+		/// The view key is not granted.
+		UnknownViewKey,
+		/// A public key must be a secp256k1 point of 64 bytes, uncompressed without the `0x04`
+		/// tag.
+		InvalidPublicKey,
+		/// The account already registered a public key.
+		PublicKeyAlreadyRegistered,
+		/// The account has no registered public key.
+		PublicKeyNotRegistered,
+		/// The public key registered by an account must be its own.
+		PublicKeyNotOfAccount,
+		/// The payload of a confidential transaction cannot be decrypted.
+		UndecryptableTransaction,
+		/// The contract already declared its confidentiality policy.
+		PolicyAlreadyDeclared,
+		/// A confidentiality policy can only be declared while the contract is deployed.
+		ContractAlreadyDeployed,
+		/// Confidential transactions and executions touching a contract that did not declare
+		/// itself public are not traced.
+		UntraceableConfidentialExecution,
+		/// Only a contract under construction can declare a confidentiality policy, not the
+		/// sender of the transaction.
+		NotUnderConstruction,
 	}
 
 	/// Current building block's transactions and receipts.
@@ -325,18 +571,94 @@ pub mod pallet {
 	#[pallet::storage]
 	pub type BlockHash<T: Config> = StorageMap<_, Twox64Concat, U256, H256, ValueQuery>;
 
-	// mapping for transaction id and poc.
+	/// Unsigned PoCs of the transactions, the RLP encoded lists of input and output hashes.
+	/// Nodes sign their root with the enclave key off chain.
 	#[pallet::storage]
 	#[pallet::getter(fn transaction_poc)]
 	pub type TransactionPoc<T: Config> = StorageMap<_, Twox64Concat, H256, Vec<u8>, ValueQuery>;
 
+	/// Address of the enclave key that signs PoCs off chain.
+	#[pallet::storage]
+	#[pallet::getter(fn poc_signer)]
+	pub type PocSigner<T: Config> = StorageValue<_, H160, ValueQuery>;
+
+	/// Hashes of the transactions that got a PoC in a block, used to prune expired PoCs.
+	#[pallet::storage]
+	pub type PocBlockIndex<T: Config> =
+		StorageMap<_, Twox64Concat, BlockNumberFor<T>, Vec<H256>, ValueQuery>;
+
+	/// The oldest block whose PoCs may still be in storage.
+	#[pallet::storage]
+	pub type PocPruneCursor<T: Config> = StorageValue<_, BlockNumberFor<T>, ValueQuery>;
+
+	/// Id of the state master key new state is encrypted under, the legacy global key when
+	/// unset.
+	#[pallet::storage]
+	pub type StateKeyId<T: Config> = StorageValue<_, u32, OptionQuery>;
+
+	/// The last storage key re-encrypted, while a state re-encryption is in progress.
+	#[pallet::storage]
+	pub type StateReencryptionCursor<T: Config> = StorageValue<_, Vec<u8>, OptionQuery>;
+
+	/// The state master key registered for re-encryption and the holders that have yet to
+	/// report it.
+	#[pallet::storage]
+	pub type PendingStateKey<T: Config> = StorageValue<_, (u32, Vec<T::AccountId>), OptionQuery>;
+
 	#[pallet::storage]
 	#[pallet::getter(fn account_public)]
 	pub type AccountPublic<T: Config> = StorageMap<_, Blake2_128Concat, H160, Vec<u8>, ValueQuery>;
 
+	/// Data of the non-perceptible logs of a transaction, by index of the log in its receipt.
+	/// Receipts only hold the keccak256 of the data, which stays in the encrypted state until
+	/// the receipt RPC seals it to an authenticated receiver.
+	#[pallet::storage]
+	pub type NonPerceptibleLogData<T: Config> =
+		StorageDoubleMap<_, Identity, H256, Twox64Concat, u32, Vec<u8>, OptionQuery>;
+
+	/// Commitments `keccak256(view_public_key)` of the view keys an account granted over its
+	/// confidential transactions.
+	#[pallet::storage]
+	#[pallet::getter(fn view_keys)]
+	pub type ViewKeys<T: Config> = StorageMap<_, Blake2_128Concat, H160, Vec<H256>, ValueQuery>;
+
+	/// Confidentiality policies declared by contracts, contracts without one being public.
+	#[pallet::storage]
+	#[pallet::getter(fn confidentiality_policy)]
+	pub type ConfidentialityPolicies<T: Config> =
+		StorageMap<_, Blake2_128Concat, H160, ConfidentialityPolicy, ValueQuery>;
+
+	/// Whether a PoC is generated for every executed transaction.
+	#[pallet::storage]
+	#[pallet::getter(fn enable_poc)]
+	pub type EnablePoc<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// Whether a PoM is generated for every executed call.
+	#[pallet::storage]
+	#[pallet::getter(fn enable_pom)]
+	pub type EnablePom<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// The id of the chain whose blocks are replayed as pre-logs. Pre-log transactions signed
+	/// for another chain than this one and this source are rejected.
+	#[pallet::storage]
+	pub type PreLogSourceChainId<T: Config> = StorageValue<_, u64, OptionQuery>;
+
+	/// Whether a state diff is captured for every executed transaction.
+	#[pallet::storage]
+	#[pallet::getter(fn enable_state_diff)]
+	pub type EnableStateDiff<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// The state diffs of the transactions of the current block, when captured.
+	#[pallet::storage]
+	pub type CurrentStateDiffs<T: Config> = StorageValue<_, Vec<StateDiff>, ValueQuery>;
+
 	#[pallet::genesis_config]
 	#[derive(frame_support::DefaultNoBound)]
 	pub struct GenesisConfig<T> {
+		pub enable_poc: bool,
+		pub enable_pom: bool,
+		pub enable_state_diff: bool,
+		pub poc_signer: H160,
 		#[serde(skip)]
 		pub _marker: PhantomData<T>,
 	}
@@ -344,6 +666,10 @@ pub mod pallet {
 	#[pallet::genesis_build]
 	impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
 		fn build(&self) {
+			EnablePoc::<T>::put(self.enable_poc);
+			EnablePom::<T>::put(self.enable_pom);
+			EnableStateDiff::<T>::put(self.enable_state_diff);
+			PocSigner::<T>::put(self.poc_signer);
 			<Pallet<T>>::store_block(None, U256::zero());
 			frame_support::storage::unhashed::put::<EthereumStorageSchema>(
 				PALLET_ETHEREUM_SCHEMA,
@@ -405,38 +731,222 @@ impl<T: Config> Pallet<T> {
 		Some(pubkey)
 	}
 
-	fn register_public(origin: H160, transaction: &Transaction) {
-		if Self::has_registered(origin) {
-			AccountPublic::<T>::remove(origin);
-		} else {
-			let pubkey = Self::recover_public(transaction).unwrap();
-			AccountPublic::<T>::insert(origin, pubkey.to_vec());
+	/// Ensure `public_key` is an uncompressed secp256k1 point without the `0x04` tag.
+	fn ensure_valid_public_key(public_key: &[u8]) -> DispatchResult {
+		frame_support::ensure!(
+			public_key.len() == 64
+				&& libsecp256k1::PublicKey::parse_slice(
+					public_key,
+					Some(libsecp256k1::PublicKeyFormat::Raw)
+				)
+				.is_ok(),
+			Error::<T>::InvalidPublicKey
+		);
+		Ok(())
+	}
 
-			Self::deposit_event(Event::RegisterPublic {
-				from: origin,
-				public_key: pubkey.to_vec(),
-			})
+	/// Register the public key the confidential data addressed to `account` is sealed to, which
+	/// must be the key of the account.
+	pub fn register_public_key(account: H160, public_key: Vec<u8>) -> DispatchResult {
+		Self::ensure_valid_public_key(&public_key)?;
+		frame_support::ensure!(
+			H160::from(H256::from(sp_io::hashing::keccak_256(&public_key))) == account,
+			Error::<T>::PublicKeyNotOfAccount
+		);
+		frame_support::ensure!(
+			!AccountPublic::<T>::contains_key(account),
+			Error::<T>::PublicKeyAlreadyRegistered
+		);
+		AccountPublic::<T>::insert(account, public_key.clone());
+		Self::deposit_event(Event::PublicKeyRegistered {
+			account,
+			public_key,
+		});
+		Ok(())
+	}
+
+	/// Replace the public key registered by `account`.
+	pub fn rotate_public_key(account: H160, public_key: Vec<u8>) -> DispatchResult {
+		Self::ensure_valid_public_key(&public_key)?;
+		AccountPublic::<T>::try_mutate_exists(account, |registered| {
+			let registered = registered
+				.as_mut()
+				.ok_or(Error::<T>::PublicKeyNotRegistered)?;
+			*registered = public_key.clone();
+			Ok::<_, Error<T>>(())
+		})?;
+		Self::deposit_event(Event::PublicKeyRotated {
+			account,
+			public_key,
+		});
+		Ok(())
+	}
+
+	/// Remove the public key registered by `account`.
+	pub fn revoke_public_key(account: H160) -> DispatchResult {
+		frame_support::ensure!(
+			AccountPublic::<T>::contains_key(account),
+			Error::<T>::PublicKeyNotRegistered
+		);
+		AccountPublic::<T>::remove(account);
+		Self::deposit_event(Event::PublicKeyRevoked { account });
+		Ok(())
+	}
+
+	/// Whether `account` registered a public key.
+	pub fn is_public_key_registered(account: H160) -> bool {
+		AccountPublic::<T>::contains_key(account)
+	}
+
+	/// The commitment under which the view key with the 64-byte public key `view_public` is
+	/// granted.
+	pub fn view_key_commitment(view_public: &[u8; 64]) -> H256 {
+		H256::from(sp_io::hashing::keccak_256(view_public))
+	}
+
+	/// Let the holder of the view key committed to by `commitment` read the inputs and
+	/// receipts of the confidential transactions sent by `owner`.
+	pub fn grant_view_key(owner: H160, commitment: H256) -> DispatchResult {
+		ViewKeys::<T>::try_mutate(owner, |commitments| {
+			frame_support::ensure!(
+				!commitments.contains(&commitment),
+				Error::<T>::ViewKeyAlreadyGranted
+			);
+			frame_support::ensure!(
+				(commitments.len() as u32) < T::MaxViewKeys::get(),
+				Error::<T>::TooManyViewKeys
+			);
+			commitments.push(commitment);
+			Ok::<_, Error<T>>(())
+		})?;
+		Self::deposit_event(Event::ViewKeyGranted { owner, commitment });
+		Ok(())
+	}
+
+	/// Revoke a view key granted by `owner`.
+	pub fn revoke_view_key(owner: H160, commitment: H256) -> DispatchResult {
+		ViewKeys::<T>::try_mutate_exists(owner, |commitments| {
+			let list = commitments.as_mut().ok_or(Error::<T>::UnknownViewKey)?;
+			let position = list
+				.iter()
+				.position(|c| *c == commitment)
+				.ok_or(Error::<T>::UnknownViewKey)?;
+			list.swap_remove(position);
+			if list.is_empty() {
+				*commitments = None;
+			}
+			Ok::<_, Error<T>>(())
+		})?;
+		Self::deposit_event(Event::ViewKeyRevoked { owner, commitment });
+		Ok(())
+	}
+
+	/// Run `f`, an EVM execution on behalf of `origin`, so that the contracts it creates can
+	/// declare their confidentiality policy.
+	pub fn with_transaction_origin<R>(mut origin: H160, f: impl FnOnce() -> R) -> R {
+		transaction_origin::using(&mut origin, f)
+	}
+
+	/// Whether the view key committed to by `commitment` is currently granted by `owner`.
+	pub fn is_view_key_granted(owner: H160, commitment: &H256) -> bool {
+		ViewKeys::<T>::get(owner).contains(commitment)
+	}
+
+	/// Whether the payload of `transaction` sent by `origin` is encrypted to the enclave key.
+	///
+	/// EIP-1559 transactions carry an encrypted payload of their own. The input of Legacy and
+	/// EIP-2930 transactions is sealed when their sender registered a public key, see
+	/// [`tp_io::envelope::sealed_input_aad`].
+	pub fn is_confidential(origin: H160, transaction: &Transaction) -> bool {
+		match transaction {
+			Transaction::EIP1559(_) => !transaction.is_universal(),
+			Transaction::Legacy(_) | Transaction::EIP2930(_) => {
+				AccountPublic::<T>::contains_key(origin)
+			}
 		}
 	}
 
-	fn has_registered(origin: H160) -> bool {
-		AccountPublic::<T>::contains_key(origin)
+	/// Declare the confidentiality policy of `contract`. Only contracts under deployment
+	/// without a policy yet can declare one, so a policy never changes. An account without
+	/// code is only under deployment when it is not the sender of the transaction, and only
+	/// within [`Self::with_transaction_origin`].
+	pub fn declare_confidentiality_policy(
+		contract: H160,
+		policy: ConfidentialityPolicy,
+	) -> DispatchResult {
+		frame_support::ensure!(
+			transaction_origin::with(|origin| *origin != contract).unwrap_or_default(),
+			Error::<T>::NotUnderConstruction
+		);
+		frame_support::ensure!(
+			pallet_evm::AccountCodes::<T>::get(contract).is_empty(),
+			Error::<T>::ContractAlreadyDeployed
+		);
+		frame_support::ensure!(
+			!ConfidentialityPolicies::<T>::contains_key(contract),
+			Error::<T>::PolicyAlreadyDeclared
+		);
+		ConfidentialityPolicies::<T>::insert(contract, policy);
+		Self::deposit_event(Event::ConfidentialityPolicyDeclared { contract, policy });
+		Ok(())
+	}
+
+	// Accounts that registered a public key only send encrypted transactions, and confidential
+	// contracts only accept encrypted calls.
+	fn ensure_confidentiality_policy(
+		origin: H160,
+		transaction: &Transaction,
+		action: ethereum::TransactionAction,
+	) -> Result<(), TransactionValidityError> {
+		if Self::is_confidential(origin, transaction) {
+			return Ok(());
+		}
+		if AccountPublic::<T>::contains_key(origin) {
+			return Err(InvalidTransaction::Custom(
+				tp_ethereum::PLAINTEXT_TRANSACTION_FROM_REGISTERED_ACCOUNT,
+			)
+			.into());
+		}
+		if let ethereum::TransactionAction::Call(target) = action {
+			if !ConfidentialityPolicies::<T>::get(target).is_public() {
+				return Err(InvalidTransaction::Custom(
+					tp_ethereum::PLAINTEXT_CALL_TO_CONFIDENTIAL_CONTRACT,
+				)
+				.into());
+			}
+		}
+		Ok(())
 	}
 
-	fn transact_essential(transaction: &Transaction) -> Option<ethereum::TransactionEssentials> {
-		if transaction.is_universal() {
-			return transaction.essentials().ok();
+	fn transact_essential(
+		origin: H160,
+		transaction: &Transaction,
+	) -> Result<ethereum::TransactionEssentials, InvalidTransaction> {
+		let invalid_signature =
+			InvalidTransaction::Custom(TransactionValidationError::InvalidSignature as u8);
+		if !Self::is_confidential(origin, transaction) {
+			return transaction.essentials().map_err(|_| invalid_signature);
 		}
 
 		let pubkey = transaction
 			.recover_public_key(sp_io::crypto::secp256k1_ecdsa_recover)
-			.ok()?;
-		transaction
-			.essentials_with_decrypt(|msg, aad| {
-				tp_io::crypto::decrypted(msg, aad.as_fixed_bytes(), &pubkey)
-					.map_err(|_| ethereum::Error::BadDecrypte)
-			})
-			.ok()
+			.map_err(|_| invalid_signature)?;
+		let decryption_failed = InvalidTransaction::Custom(tp_ethereum::DECRYPTION_FAILED);
+		if let Transaction::EIP1559(_) = transaction {
+			return transaction
+				.essentials_with_decrypt(|msg, aad| {
+					tp_io::crypto::decrypted(msg, aad.as_fixed_bytes(), &pubkey)
+						.map_err(|_| ethereum::Error::BadDecrypte)
+				})
+				.map_err(|_| decryption_failed);
+		}
+
+		let mut essential = transaction.essentials().map_err(|_| invalid_signature)?;
+		let aad =
+			tp_io::envelope::sealed_input_aad(transaction.chain_id(), essential.nonce.low_u128());
+		essential.input = tp_io::crypto::decrypted(&essential.input, &aad, &pubkey)
+			.map_err(|_| decryption_failed)?;
+		Ok(essential)
 	}
 
 	fn store_block(post_log: Option<PostLogContent>, block_number: U256) {
@@ -507,19 +1017,172 @@ impl<T: Config> Pallet<T> {
 		}
 	}
 
-	fn generate_poc(transaction: &Transaction, receipt: &Receipt) {
-		let seed = 0;
-		let private_key = H256::from_slice(&[(seed + 1) as u8; 32]);
-		let input_hash = tenet_app::TenetApi::generate_input_hash(transaction);
-		let output_hash = tenet_app::TenetApi::generate_output_hash(receipt);
-		let poc = fp_poc::generate_poc(
-			private_key,
-			&vec![fp_poc::IOHash {
-				input_hash,
-				output_hash,
-			}],
-		);
-		TransactionPoc::<T>::insert(transaction.hash(), rlp::encode(&poc).encode());
+	/// The root of the PoC generated for a transaction, if any, and the address expected to
+	/// sign it.
+	pub fn transaction_poc_root(transaction_hash: H256) -> Option<(H256, H160)> {
+		let poc = TransactionPoc::<T>::get(transaction_hash);
+		if poc.is_empty() {
+			return None;
+		}
+		let io_hash_list = fp_poc::decode_unsigned(&poc).ok()?;
+		Some((fp_poc::generate_root(&io_hash_list), PocSigner::<T>::get()))
+	}
+
+	/// Store the unsigned PoC of the transaction. The PoC only depends on the transaction and
+	/// its outcome, so every node stores the same one whatever keys it holds.
+	fn generate_poc(transaction: &Transaction, receipt: &Receipt, write_set_digest: Option<H256>) {
+		let io_hash_list = vec![fp_poc::IOHash {
+			input_hash: tenet_app::TenetApi::generate_input_hash(transaction),
+			output_hash: tenet_app::TenetApi::generate_output_hash(receipt, write_set_digest),
+		}];
+		let transaction_hash = transaction.hash();
+		TransactionPoc::<T>::insert(transaction_hash, fp_poc::encode_unsigned(&io_hash_list));
+		PocBlockIndex::<T>::append(frame_system::Pallet::<T>::block_number(), transaction_hash);
+	}
+
+	/// Migrate to storage version 1. The PoCs stored before held the SCALE encoding of a
+	/// signed RLP encoded PoC; only its list of input and output hashes is kept.
+	fn migrate_to_v1() -> Weight {
+		let mut translated = 0u64;
+		TransactionPoc::<T>::translate_values::<Vec<u8>, _>(|poc| {
+			translated += 1;
+			let poc = Vec::<u8>::decode(&mut &poc[..]).unwrap_or(poc);
+			let poc: fp_poc::PoC = rlp::decode(&poc).ok()?;
+			Some(fp_poc::encode_unsigned(poc.io_hash_list()))
+		});
+		StorageVersion::new(1).put::<Self>();
+		T::DbWeight::get().reads_writes(translated.saturating_add(1), translated.saturating_add(1))
+	}
+
+	/// Remove the PoCs that fell out of the retention window, spending at most
+	/// `MaxPocPruneWeight`. Blocks that cannot be fully pruned are resumed in the next block.
+	fn prune_pocs(now: BlockNumberFor<T>) -> Weight {
+		let db_weight = T::DbWeight::get();
+		let retention = T::PocRetentionPeriod::get();
+		if retention.is_zero() || now <= retention {
+			return Weight::zero();
+		}
+		let prune_until = now - retention;
+		let limit = T::MaxPocPruneWeight::get();
+
+		// read: PocPruneCursor
+		let mut weight = db_weight.reads(1);
+		let mut cursor = PocPruneCursor::<T>::get();
+		let start = cursor;
+		let mut pruned = Vec::new();
+		while cursor <= prune_until {
+			// read and write: PocBlockIndex
+			let next = weight.saturating_add(db_weight.reads_writes(1, 1));
+			if next.any_gt(limit) {
+				break;
+			}
+			weight = next;
+
+			let mut hashes = PocBlockIndex::<T>::get(cursor);
+			let mut removed = 0;
+			for hash in hashes.iter() {
+				// read and write: TransactionPoc
+				let next = weight.saturating_add(db_weight.reads_writes(1, 1));
+				if next.any_gt(limit) {
+					break;
+				}
+				weight = next;
+				pruned.push((*hash, TransactionPoc::<T>::take(hash)));
+				removed += 1;
+			}
+			if removed < hashes.len() {
+				PocBlockIndex::<T>::insert(cursor, hashes.split_off(removed));
+				break;
+			}
+			PocBlockIndex::<T>::remove(cursor);
+			cursor += One::one();
+		}
+
+		if cursor != start {
+			// write: PocPruneCursor
+			weight = weight.saturating_add(db_weight.writes(1));
+			PocPruneCursor::<T>::put(cursor);
+		}
+		if !pruned.is_empty() {
+			if T::ArchivePrunedPoc::get() {
+				sp_io::offchain_index::set(&poc_archive_key(now), &pruned.encode());
+			}
+			Self::deposit_event(Event::PocsPruned {
+				count: pruned.len() as u32,
+			});
+		}
+		weight
+	}
+
+	/// Rewrite state values in key order from the re-encryption cursor until `remaining_weight`
+	/// is spent. Rewriting a value unchanged makes the trie backend encrypt it again under the
+	/// current state key. Every value is charged by its length, the runtime code included.
+	fn reencrypt_state_step(remaining_weight: Weight) -> Weight {
+		let db_weight = T::DbWeight::get();
+		// read: StateReencryptionCursor
+		let mut weight = db_weight.reads(1);
+		if weight.any_gt(remaining_weight) {
+			return Weight::zero();
+		}
+		let Some(mut cursor) = StateReencryptionCursor::<T>::get() else {
+			return weight;
+		};
+
+		// write: StateReencryptionCursor
+		let remaining_weight = remaining_weight.saturating_sub(db_weight.writes(1));
+		let cursor_key = StateReencryptionCursor::<T>::hashed_key();
+		loop {
+			// read: next key
+			let next = weight.saturating_add(db_weight.reads(1));
+			if next.any_gt(remaining_weight) {
+				break;
+			}
+			weight = next;
+			let Some(key) = sp_io::storage::next_key(&cursor) else {
+				StateReencryptionCursor::<T>::kill();
+				Self::deposit_event(Event::StateReencryptionCompleted);
+				return weight.saturating_add(db_weight.writes(1));
+			};
+			if key == cursor_key {
+				cursor = key;
+				continue;
+			}
+
+			// read and write: value
+			let len = sp_io::storage::read(&key, &mut [], 0).unwrap_or_default();
+			let next = weight
+				.saturating_add(db_weight.reads_writes(1, 1))
+				.saturating_add(T::ReencryptionWeightPerByte::get().saturating_mul(len.into()));
+			if next.any_gt(remaining_weight) {
+				break;
+			}
+			weight = next;
+			if let Some(value) = sp_io::storage::get(&key) {
+				sp_io::storage::set(&key, &value);
+			}
+			cursor = key;
+		}
+		StateReencryptionCursor::<T>::put(cursor);
+		weight.saturating_add(db_weight.writes(1))
+	}
+
+	/// Convert the logs of a transaction, moving the data of its non-perceptible logs to
+	/// [`NonPerceptibleLogData`] and leaving its keccak256 in the log. Data with little entropy
+	/// should carry a salt, or the commitment reveals it to anyone guessing it.
+	fn commit_logs(transaction_hash: H256, logs: &[ethereum::Log]) -> Vec<Log> {
+		logs.iter()
+			.cloned()
+			.map(Log::from)
+			.enumerate()
+			.map(|(index, mut log)| {
+				if log.log_type == Some(LogType::NonPerceptible) {
+					let commitment = sp_io::hashing::keccak_256(&log.data).to_vec();
+					let data = core::mem::replace(&mut log.data, commitment);
+					NonPerceptibleLogData::<T>::insert(transaction_hash, index as u32, data);
+				}
+				log
+			})
+			.collect()
 	}
 
 	fn logs_bloom(logs: Vec<Log>, bloom: &mut Bloom) {
@@ -538,9 +1201,7 @@ impl<T: Config> Pallet<T> {
 		origin: H160,
 		transaction: &Transaction,
 	) -> TransactionValidity {
-		let essential = Self::transact_essential(&transaction).ok_or(
-			InvalidTransaction::Custom(TransactionValidationError::InvalidSignature as u8),
-		)?;
+		let essential = Self::transact_essential(origin, transaction)?;
 
 		let transaction_data = TransactionData::convert(transaction.chain_id(), &essential);
 		let transaction_nonce = transaction_data.nonce;
@@ -598,15 +1259,7 @@ impl<T: Config> Pallet<T> {
 			_ => return Err(InvalidTransaction::Payment.into()),
 		};
 
-		// check transac meet confidentiality requirements
-		if AccountPublic::<T>::contains_key(origin) && transaction.is_universal() {
-			if transaction.action() != ethereum::TransactionAction::Create {
-				return Err(InvalidTransaction::Custom(
-					TransactionValidationError::UnknownError as u8,
-				)
-				.into());
-			}
-		}
+		Self::ensure_confidentiality_policy(origin, transaction, essential.action)?;
 
 		// The tag provides and requires must be filled correctly according to the nonce.
 		let mut builder = ValidTransactionBuilder::default()
@@ -624,11 +1277,92 @@ impl<T: Config> Pallet<T> {
 		builder.build()
 	}
 
+	/// Execute the transactions of an Ethereum block imported as a pre-log. The block is
+	/// applied as a whole: if a transaction fails, every transaction of the block is rolled
+	/// back and `PreLogRejected` reports why, instead of the block failing to build.
+	fn apply_pre_log(block: tp_ethereum::BlockV2) -> Weight {
+		let without_base_extrinsic_weight = true;
+		let gas_weight = block
+			.transactions
+			.iter()
+			.fold(Weight::zero(), |weight, transaction| {
+				weight.saturating_add(<T as pallet_evm::Config>::GasWeightMapping::gas_to_weight(
+					transaction.gas_limit().unique_saturated_into(),
+					without_base_extrinsic_weight,
+				))
+			});
+		if gas_weight.any_gt(T::BlockWeights::get().max_block) {
+			Self::deposit_event(Event::PreLogRejected {
+				error: PreLogError::WeightExceeded,
+			});
+			return Weight::zero();
+		}
+
+		let transactions = block.transactions.len() as u32;
+		let mut weight = Weight::zero();
+		let mut rejection = None;
+		let _ = with_storage_layer(|| -> DispatchResult {
+			for (index, transaction) in block.transactions.into_iter().enumerate() {
+				match Self::apply_pre_log_transaction(index as u32, transaction) {
+					Ok(used) => weight = weight.saturating_add(used),
+					Err(error) => {
+						rejection = Some(error);
+						return Err(DispatchError::Other("pre-log rejected"));
+					}
+				}
+			}
+			Ok(())
+		});
+
+		Self::deposit_event(match rejection {
+			Some(error) => Event::PreLogRejected { error },
+			None => Event::PreLogApplied { transactions },
+		});
+		weight
+	}
+
+	fn apply_pre_log_transaction(
+		index: u32,
+		transaction: Transaction,
+	) -> Result<Weight, PreLogError> {
+		let source =
+			Self::recover_signer(&transaction).ok_or(PreLogError::InvalidSignature { index })?;
+		// Transactions of the pre-log source chain are signed for that chain's id. They still
+		// run with the id of this chain, which `CHAINID` returns.
+		let chain_id = transaction.chain_id();
+		let check_chain_id = chain_id.is_none() || chain_id == Some(T::ChainId::get());
+		if !check_chain_id && chain_id != PreLogSourceChainId::<T>::get() {
+			return Err(PreLogError::UnexpectedChainId { index });
+		}
+		Self::check_transaction_in_block(source, &transaction, check_chain_id)
+			.map_err(|_| PreLogError::InvalidTransaction { index })?;
+		let (info, _) = Self::apply_validated_transaction(source, transaction)
+			.map_err(|_| PreLogError::ApplyFailed { index })?;
+
+		Ok(info.actual_weight.unwrap_or_default())
+	}
+
 	fn apply_validated_transaction(
 		source: H160,
 		transaction: Transaction,
 	) -> Result<(PostDispatchInfo, CallOrCreateInfo), DispatchErrorWithPostInfo> {
-		let (to, _, info) = Self::execute(source, &transaction, None)?;
+		let (executed, state_diff, state_diff_weight) =
+			Self::with_state_diff(source, &transaction, || {
+				with_storage_layer(|| -> Result<_, DispatchErrorWithPostInfo> {
+					let (to, _, info) = Self::execute(source, &transaction, None)?;
+					// Read the write set while the execution still has its own storage layer.
+					let write_set_digest = if EnablePoc::<T>::get() && T::CommitWriteSet::get() {
+						tp_io::state::write_set_digest(
+							&pallet_evm::AccountStorages::<T>::final_prefix(),
+						)
+						.map(H256::from)
+					} else {
+						None
+					};
+					Ok((to, info, write_set_digest))
+				})
+			});
+		let (to, info, write_set_digest) = executed?;
 
 		let pending = Pending::<T>::get();
 		let transaction_hash = transaction.hash();
@@ -643,7 +1377,7 @@ impl<T: Config> Pallet<T> {
 					from: source,
 					to,
 					contract_address: None,
-					logs: info.logs.clone().into_iter().map(Log::from).collect(),
+					logs: Self::commit_logs(transaction_hash, &info.logs),
 					logs_bloom: {
 						let mut bloom: Bloom = Bloom::default();
 						Self::logs_bloom(
@@ -690,7 +1424,7 @@ impl<T: Config> Pallet<T> {
 					from: source,
 					to,
 					contract_address: Some(info.value),
-					logs: info.logs.clone().into_iter().map(Log::from).collect(),
+					logs: Self::commit_logs(transaction_hash, &info.logs),
 					logs_bloom: {
 						let mut bloom: Bloom = Bloom::default();
 						Self::logs_bloom(
@@ -746,11 +1480,11 @@ impl<T: Config> Pallet<T> {
 		};
 
 		// Generate poc
-		if ENABLE_POC {
-			Self::generate_poc(&transaction, &receipt);
+		if EnablePoc::<T>::get() {
+			Self::generate_poc(&transaction, &receipt, write_set_digest);
 		}
 
-		if ENABLE_POM {
+		if EnablePom::<T>::get() {
 			// Generate PoM struct
 			let pom = PoM {
 				challenge_id: transaction_hash,
@@ -773,6 +1507,9 @@ impl<T: Config> Pallet<T> {
 		}
 
 		Pending::<T>::append((transaction, status, receipt));
+		if let Some(state_diff) = state_diff {
+			CurrentStateDiffs::<T>::append(state_diff);
+		}
 
 		Self::deposit_event(Event::Executed {
 			from: source,
@@ -797,7 +1534,7 @@ impl<T: Config> Pallet<T> {
 							*gas_to_weight.proof_size_mut() = proof_size_usage;
 						}
 					}
-					Some(gas_to_weight)
+					Some(gas_to_weight.saturating_add(state_diff_weight))
 				},
 				pays_fee: Pays::No,
 			},
@@ -816,18 +1553,22 @@ impl<T: Config> Pallet<T> {
 		transaction: &Transaction,
 		config: Option<evm::Config>,
 	) -> Result<(Option<H160>, Option<H160>, CallOrCreateInfo), DispatchErrorWithPostInfo> {
-		let essential = Self::transact_essential(&transaction).unwrap_or_default();
+		// Validation rejects undecryptable transactions, so this only guards direct callers.
+		let essential =
+			Self::transact_essential(from, transaction).map_err(|_| DispatchErrorWithPostInfo {
+				post_info: PostDispatchInfo {
+					actual_weight: None,
+					pays_fee: Pays::Yes,
+				},
+				error: Error::<T>::UndecryptableTransaction.into(),
+			})?;
 		let transaction_data = TransactionData::convert(transaction.chain_id(), &essential);
 		let (weight_limit, proof_size_base_cost) = Self::transaction_weight(&transaction_data);
 		let is_transactional = true;
 		let validate = false;
 
-		match essential.action {
+		Self::with_transaction_origin(from, || match essential.action {
 			tp_ethereum::TransactionAction::Call(target) => {
-				if target == H160::from_low_u64_be(1026) {
-					Self::register_public(from, transaction);
-				}
-
 				let res = match T::Runner::call(
 					from,
 					target,
@@ -896,7 +1637,7 @@ impl<T: Config> Pallet<T> {
 
 				Ok((None, Some(res.value), CallOrCreateInfo::Create(res)))
 			}
-		}
+		})
 	}
 
 	/// Validate an Ethereum transaction already in block
@@ -907,9 +1648,15 @@ impl<T: Config> Pallet<T> {
 		origin: H160,
 		transaction: &Transaction,
 	) -> Result<(), TransactionValidityError> {
-		let essential = Self::transact_essential(&transaction).ok_or(
-			InvalidTransaction::Custom(TransactionValidationError::InvalidSignature as u8),
-		)?;
+		Self::check_transaction_in_block(origin, transaction, true)
+	}
+
+	fn check_transaction_in_block(
+		origin: H160,
+		transaction: &Transaction,
+		check_chain_id: bool,
+	) -> Result<(), TransactionValidityError> {
+		let essential = Self::transact_essential(origin, transaction)?;
 
 		let transaction_data = TransactionData::convert(transaction.chain_id(), &essential);
 		let (weight_limit, proof_size_base_cost) = Self::transaction_weight(&transaction_data);
@@ -929,20 +1676,18 @@ impl<T: Config> Pallet<T> {
 			proof_size_base_cost,
 		)
 		.validate_in_block_for(&who)
-		.and_then(|v| v.with_chain_id())
+		.and_then(|v| {
+			if check_chain_id {
+				v.with_chain_id()
+			} else {
+				Ok(v)
+			}
+		})
 		.and_then(|v| v.with_base_fee())
 		.and_then(|v| v.with_balance_for(&who))
 		.map_err(|e| TransactionValidityError::Invalid(e.0))?;
 
-		// check transac meet confidentiality requirements
-		if AccountPublic::<T>::contains_key(origin) && transaction.is_universal() {
-			if transaction.action() != ethereum::TransactionAction::Create {
-				return Err(InvalidTransaction::Custom(
-					TransactionValidationError::UnknownError as u8,
-				)
-				.into());
-			}
-		}
+		Self::ensure_confidentiality_policy(origin, transaction, essential.action)?;
 
 		Ok(())
 	}
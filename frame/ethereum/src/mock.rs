@@ -0,0 +1,298 @@
+//! Test utilities
+
+use ethereum_types::{H160, H256, U256};
+use frame_support::{
+	parameter_types,
+	traits::{ConstBool, ConstU32, ConstU64, Everything, FindAuthor},
+	weights::Weight,
+	ConsensusEngineId,
+};
+use pallet_evm::{EnsureAddressNever, FeeCalculator, IdentityAddressMapping};
+use sp_io::hashing::keccak_256;
+use sp_runtime::{
+	traits::{BlakeTwo256, IdentityLookup},
+	BuildStorage,
+};
+use tp_ethereum::{
+	LegacyTransaction, LegacyTransactionMessage, TransactionAction, TransactionSignature,
+};
+
+use super::*;
+
+pub type AccountId = H160;
+pub type Balance = u64;
+
+frame_support::construct_runtime! {
+	pub enum Test {
+		System: frame_system,
+		Balances: pallet_balances,
+		Timestamp: pallet_timestamp,
+		EVM: pallet_evm,
+		Ethereum: crate,
+	}
+}
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+}
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = frame_support::weights::constants::RocksDbWeight;
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type Nonce = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<AccountId>;
+	type Block = frame_system::mocking::MockBlock<Self>;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<Balance>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type WeightInfo = ();
+	type Balance = Balance;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = System;
+	type ReserveIdentifier = [u8; 8];
+	type RuntimeHoldReason = ();
+	type FreezeIdentifier = ();
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type MaxHolds = ();
+	type MaxFreezes = ();
+	type RuntimeFreezeReason = ();
+}
+
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = ConstU64<1>;
+	type WeightInfo = ();
+}
+
+pub struct FixedGasPrice;
+impl FeeCalculator for FixedGasPrice {
+	fn min_gas_price() -> (U256, Weight) {
+		(1.into(), Weight::zero())
+	}
+}
+
+pub struct FindAuthorTruncated;
+impl FindAuthor<H160> for FindAuthorTruncated {
+	fn find_author<'a, I>(_digests: I) -> Option<H160>
+	where
+		I: 'a + IntoIterator<Item = (ConsensusEngineId, &'a [u8])>,
+	{
+		Some(H160::repeat_byte(0xaa))
+	}
+}
+
+const BLOCK_GAS_LIMIT: u64 = 150_000_000;
+const MAX_POV_SIZE: u64 = 5 * 1024 * 1024;
+
+parameter_types! {
+	pub const ChainId: u64 = 42;
+	pub BlockGasLimit: U256 = U256::from(BLOCK_GAS_LIMIT);
+	pub const GasLimitPovSizeRatio: u64 = BLOCK_GAS_LIMIT.saturating_div(MAX_POV_SIZE);
+	pub const WeightPerGas: Weight = Weight::from_parts(20_000, 0);
+	pub SuicideQuickClearLimit: u32 = 0;
+}
+
+impl pallet_evm::Config for Test {
+	type FeeCalculator = FixedGasPrice;
+	type GasWeightMapping = pallet_evm::FixedGasWeightMapping<Self>;
+	type WeightPerGas = WeightPerGas;
+	type BlockHashMapping = crate::EthereumBlockHashMapping<Self>;
+	type CallOrigin = EnsureAddressNever<AccountId>;
+	type WithdrawOrigin = EnsureAddressNever<AccountId>;
+	type AddressMapping = IdentityAddressMapping;
+	type Currency = Balances;
+	type RuntimeEvent = RuntimeEvent;
+	type PrecompilesType = ();
+	type PrecompilesValue = ();
+	type ChainId = ChainId;
+	type BlockGasLimit = BlockGasLimit;
+	type Runner = pallet_evm::runner::stack::Runner<Self>;
+	type OnChargeTransaction = ();
+	type OnCreate = ();
+	type FindAuthor = FindAuthorTruncated;
+	type GasLimitPovSizeRatio = GasLimitPovSizeRatio;
+	type SuicideQuickClearLimit = SuicideQuickClearLimit;
+	type Timestamp = Timestamp;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const PostBlockAndTxnHashes: PostLogContent = PostLogContent::BlockAndTxnHashes;
+	pub static PocRetentionPeriod: u64 = 0;
+	pub static MaxPocPruneWeight: Weight = Weight::MAX;
+	pub static ReencryptionWeightPerByte: Weight = Weight::zero();
+}
+
+impl Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type StateRoot = IntermediateStateRoot<Self>;
+	type PostLogContent = PostBlockAndTxnHashes;
+	type ExtraDataLength = ConstU32<30>;
+	type CommitWriteSet = ConstBool<true>;
+	type ProofOrigin = frame_system::EnsureRoot<AccountId>;
+	type PocRetentionPeriod = PocRetentionPeriod;
+	type MaxPocPruneWeight = MaxPocPruneWeight;
+	type ArchivePrunedPoc = ConstBool<true>;
+	type StateKeyOrigin = frame_system::EnsureRoot<AccountId>;
+	type ReencryptionWeightPerByte = ReencryptionWeightPerByte;
+	type MaxViewKeys = ConstU32<4>;
+	type PreLogOrigin = frame_system::EnsureRoot<AccountId>;
+}
+
+/// An Ethereum account the tests sign transactions with.
+pub struct AccountInfo {
+	pub address: H160,
+	pub private_key: H256,
+}
+
+impl AccountInfo {
+	/// Uncompressed public key without the `0x04` tag.
+	pub fn public_key(&self) -> [u8; 64] {
+		let secret = libsecp256k1::SecretKey::parse_slice(&self.private_key[..]).unwrap();
+		let mut public_key = [0u8; 64];
+		public_key
+			.copy_from_slice(&libsecp256k1::PublicKey::from_secret_key(&secret).serialize()[1..]);
+		public_key
+	}
+}
+
+fn address_build(seed: u8) -> AccountInfo {
+	let private_key = H256::from_slice(&[(seed + 1); 32]);
+	let secret_key = libsecp256k1::SecretKey::parse_slice(&private_key[..]).unwrap();
+	let public_key = &libsecp256k1::PublicKey::from_secret_key(&secret_key).serialize()[1..65];
+	let address = H160::from(H256::from(keccak_256(public_key)));
+
+	AccountInfo {
+		address,
+		private_key,
+	}
+}
+
+/// Seed of the enclave key registered with the test externalities.
+pub const ENCLAVE_SEED: [u8; 32] = [7; 32];
+
+/// Build test externalities with `accounts` funded Ethereum accounts.
+pub fn new_test_ext(accounts: u8) -> (Vec<AccountInfo>, sp_io::TestExternalities) {
+	let (pairs, mut ext) = new_test_ext_without_enclave_key(accounts);
+	ext.register_extension(tp_io::EnclaveKeyExt::from(
+		tp_io::EnclaveKey::from_seed(&ENCLAVE_SEED).unwrap(),
+	));
+	(pairs, ext)
+}
+
+/// Build test externalities as [`new_test_ext`] does, on a node without the enclave key.
+pub fn new_test_ext_without_enclave_key(
+	accounts: u8,
+) -> (Vec<AccountInfo>, sp_io::TestExternalities) {
+	let pairs = (0..accounts).map(address_build).collect::<Vec<_>>();
+
+	let mut t = frame_system::GenesisConfig::<Test>::default()
+		.build_storage()
+		.unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: pairs
+			.iter()
+			.map(|account| (account.address, 10_000_000_000_000))
+			.collect(),
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	crate::GenesisConfig::<Test>::default()
+		.assimilate_storage(&mut t)
+		.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	(pairs, ext)
+}
+
+/// A legacy transaction before signing.
+pub struct LegacyUnsignedTransaction {
+	pub nonce: U256,
+	pub gas_price: U256,
+	pub gas_limit: U256,
+	pub action: TransactionAction,
+	pub value: U256,
+	pub input: Vec<u8>,
+}
+
+impl LegacyUnsignedTransaction {
+	pub fn sign(&self, key: &H256) -> Transaction {
+		self.sign_with_chain_id(key, ChainId::get())
+	}
+
+	/// Sign for the chain of id `chain_id`, such as a chain whose blocks are replayed.
+	pub fn sign_with_chain_id(&self, key: &H256, chain_id: u64) -> Transaction {
+		let message = LegacyTransactionMessage {
+			nonce: self.nonce,
+			gas_price: self.gas_price,
+			gas_limit: self.gas_limit,
+			action: self.action,
+			value: self.value,
+			input: self.input.clone(),
+			chain_id: Some(chain_id),
+		};
+		let signing_message = libsecp256k1::Message::parse_slice(&message.hash()[..]).unwrap();
+		let secret = libsecp256k1::SecretKey::parse_slice(&key[..]).unwrap();
+		let (signature, recid) = libsecp256k1::sign(&signing_message, &secret);
+		let rs = signature.serialize();
+
+		Transaction::Legacy(LegacyTransaction {
+			nonce: self.nonce,
+			gas_price: self.gas_price,
+			gas_limit: self.gas_limit,
+			action: self.action,
+			value: self.value,
+			input: self.input.clone(),
+			signature: TransactionSignature::new(
+				chain_id * 2 + 35 + recid.serialize() as u64,
+				H256::from_slice(&rs[0..32]),
+				H256::from_slice(&rs[32..64]),
+			)
+			.unwrap(),
+		})
+	}
+}
+
+/// A signed value transfer of `value` from `account`.
+pub fn transfer(account: &AccountInfo, nonce: u64, value: u64) -> Transaction {
+	LegacyUnsignedTransaction {
+		nonce: nonce.into(),
+		gas_price: 1.into(),
+		gas_limit: 21_000.into(),
+		action: TransactionAction::Call(H160::repeat_byte(0x11)),
+		value: value.into(),
+		input: Vec::new(),
+	}
+	.sign(&account.private_key)
+}
+
+/// Dispatch `transaction` as sent by `account`.
+pub fn transact(account: &AccountInfo, transaction: Transaction) -> DispatchResultWithPostInfo {
+	Ethereum::transact(
+		RawOrigin::EthereumTransaction(account.address).into(),
+		transaction,
+	)
+}
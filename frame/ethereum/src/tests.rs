@@ -0,0 +1,688 @@
+use frame_support::{assert_noop, assert_ok, traits::Hooks};
+use sp_runtime::DispatchError;
+
+use crate::{mock::*, *};
+
+fn last_event() -> RuntimeEvent {
+	System::events()
+		.pop()
+		.expect("an event was deposited")
+		.event
+}
+
+#[test]
+fn set_enable_poc_requires_proof_origin() {
+	let (alice, mut ext) = new_test_ext(1);
+	ext.execute_with(|| {
+		assert_noop!(
+			Ethereum::set_enable_poc(RuntimeOrigin::signed(alice[0].address), true),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(Ethereum::set_enable_poc(RuntimeOrigin::root(), true));
+		assert!(EnablePoc::<Test>::get());
+		assert_eq!(
+			last_event(),
+			RuntimeEvent::Ethereum(Event::PocEnabled { enabled: true })
+		);
+
+		assert_ok!(Ethereum::set_enable_poc(RuntimeOrigin::root(), false));
+		assert!(!EnablePoc::<Test>::get());
+	});
+}
+
+#[test]
+fn set_enable_pom_requires_proof_origin() {
+	let (alice, mut ext) = new_test_ext(1);
+	ext.execute_with(|| {
+		assert_noop!(
+			Ethereum::set_enable_pom(RuntimeOrigin::signed(alice[0].address), true),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(Ethereum::set_enable_pom(RuntimeOrigin::root(), true));
+		assert!(EnablePom::<Test>::get());
+		assert_eq!(
+			last_event(),
+			RuntimeEvent::Ethereum(Event::PomEnabled { enabled: true })
+		);
+	});
+}
+
+#[test]
+fn poc_is_only_generated_when_enabled() {
+	let (alice, mut ext) = new_test_ext(1);
+	ext.execute_with(|| {
+		let transaction = transfer(&alice[0], 0, 1);
+		assert_ok!(transact(&alice[0], transaction.clone()));
+		assert!(TransactionPoc::<Test>::get(transaction.hash()).is_empty());
+
+		assert_ok!(Ethereum::set_enable_poc(RuntimeOrigin::root(), true));
+		let transaction = transfer(&alice[0], 1, 1);
+		assert_ok!(transact(&alice[0], transaction.clone()));
+		assert!(!TransactionPoc::<Test>::get(transaction.hash()).is_empty());
+		assert_eq!(PocBlockIndex::<Test>::get(1), vec![transaction.hash()]);
+	});
+}
+
+#[test]
+fn poc_is_stored_unsigned_with_the_registered_signer() {
+	let (alice, mut ext) = new_test_ext(1);
+	ext.execute_with(|| {
+		let signer = H160::repeat_byte(0xee);
+		PocSigner::<Test>::put(signer);
+		assert_ok!(Ethereum::set_enable_poc(RuntimeOrigin::root(), true));
+		let transaction = transfer(&alice[0], 0, 1);
+		assert_ok!(transact(&alice[0], transaction.clone()));
+
+		let poc = TransactionPoc::<Test>::get(transaction.hash());
+		let io_hash_list = fp_poc::decode_unsigned(&poc).unwrap();
+		assert_eq!(
+			io_hash_list[0].input_hash,
+			tenet_app::TenetApi::generate_input_hash(&transaction)
+		);
+		assert_eq!(
+			Ethereum::transaction_poc_root(transaction.hash()),
+			Some((fp_poc::generate_root(&io_hash_list), signer))
+		);
+	});
+}
+
+#[test]
+fn poc_does_not_depend_on_the_enclave_key() {
+	let pocs = [new_test_ext(1), new_test_ext_without_enclave_key(1)].map(|(alice, mut ext)| {
+		ext.execute_with(|| {
+			assert_ok!(Ethereum::set_enable_poc(RuntimeOrigin::root(), true));
+			let transaction = transfer(&alice[0], 0, 1);
+			assert_ok!(transact(&alice[0], transaction.clone()));
+			TransactionPoc::<Test>::get(transaction.hash())
+		})
+	});
+	assert!(!pocs[0].is_empty());
+	assert_eq!(pocs[0], pocs[1]);
+}
+
+#[test]
+fn migration_to_v1_keeps_unsigned_pocs() {
+	let (_, mut ext) = new_test_ext(0);
+	ext.execute_with(|| {
+		let transaction_hash = H256::repeat_byte(1);
+		let poc = fp_poc::generate_poc(
+			H256::repeat_byte(2),
+			&vec![fp_poc::IOHash {
+				input_hash: H256::repeat_byte(3),
+				output_hash: H256::repeat_byte(4),
+			}],
+		);
+		let mut legacy = rlp::RlpStream::new_list(2);
+		legacy
+			.append_list(poc.io_hash_list())
+			.append(&poc.sign.serialize_compact().to_vec());
+		TransactionPoc::<Test>::insert(transaction_hash, legacy.out().to_vec().encode());
+		StorageVersion::new(0).put::<Ethereum>();
+
+		Ethereum::on_runtime_upgrade();
+
+		assert_eq!(StorageVersion::get::<Ethereum>(), 1);
+		assert_eq!(
+			TransactionPoc::<Test>::get(transaction_hash),
+			fp_poc::encode_unsigned(poc.io_hash_list())
+		);
+		assert_eq!(
+			Ethereum::transaction_poc_root(transaction_hash),
+			Some((poc.root(), H160::zero()))
+		);
+	});
+}
+
+fn run_to_block(n: u64) -> Weight {
+	System::set_block_number(n);
+	Ethereum::on_initialize(n)
+}
+
+#[test]
+fn expired_pocs_are_pruned() {
+	PocRetentionPeriod::set(2);
+	let (alice, mut ext) = new_test_ext(1);
+	ext.execute_with(|| {
+		assert_ok!(Ethereum::set_enable_poc(RuntimeOrigin::root(), true));
+		let transaction = transfer(&alice[0], 0, 1);
+		assert_ok!(transact(&alice[0], transaction.clone()));
+
+		run_to_block(2);
+		assert!(!TransactionPoc::<Test>::get(transaction.hash()).is_empty());
+
+		run_to_block(3);
+		assert!(TransactionPoc::<Test>::get(transaction.hash()).is_empty());
+		assert!(!PocBlockIndex::<Test>::contains_key(1));
+		assert_eq!(PocPruneCursor::<Test>::get(), 2);
+		assert_eq!(
+			last_event(),
+			RuntimeEvent::Ethereum(Event::PocsPruned { count: 1 })
+		);
+	});
+}
+
+#[test]
+fn pocs_are_kept_without_retention_period() {
+	let (alice, mut ext) = new_test_ext(1);
+	ext.execute_with(|| {
+		assert_ok!(Ethereum::set_enable_poc(RuntimeOrigin::root(), true));
+		let transaction = transfer(&alice[0], 0, 1);
+		assert_ok!(transact(&alice[0], transaction.clone()));
+
+		run_to_block(100);
+		assert!(!TransactionPoc::<Test>::get(transaction.hash()).is_empty());
+	});
+}
+
+#[test]
+fn poc_pruning_is_bounded_by_weight() {
+	let db_weight = <Test as frame_system::Config>::DbWeight::get();
+	PocRetentionPeriod::set(1);
+	// The cursor read, then the indexes of blocks 0 and 1 and a single PoC.
+	MaxPocPruneWeight::set(
+		db_weight
+			.reads(1)
+			.saturating_add(db_weight.reads_writes(3, 3)),
+	);
+	let (alice, mut ext) = new_test_ext(1);
+	ext.execute_with(|| {
+		assert_ok!(Ethereum::set_enable_poc(RuntimeOrigin::root(), true));
+		let first = transfer(&alice[0], 0, 1);
+		let second = transfer(&alice[0], 1, 1);
+		assert_ok!(transact(&alice[0], first.clone()));
+		assert_ok!(transact(&alice[0], second.clone()));
+
+		run_to_block(2);
+		assert!(TransactionPoc::<Test>::get(first.hash()).is_empty());
+		assert!(!TransactionPoc::<Test>::get(second.hash()).is_empty());
+		assert_eq!(PocBlockIndex::<Test>::get(1), vec![second.hash()]);
+		assert_eq!(PocPruneCursor::<Test>::get(), 1);
+
+		run_to_block(3);
+		assert!(TransactionPoc::<Test>::get(second.hash()).is_empty());
+		assert_eq!(PocPruneCursor::<Test>::get(), 3);
+	});
+}
+
+#[test]
+fn reencrypt_state_requires_state_key_origin() {
+	let (alice, mut ext) = new_test_ext(1);
+	ext.execute_with(|| {
+		assert_noop!(
+			Ethereum::register_state_key(RuntimeOrigin::signed(alice[0].address), 7, Vec::new()),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(Ethereum::register_state_key(
+			RuntimeOrigin::root(),
+			7,
+			Vec::new()
+		));
+		assert_noop!(
+			Ethereum::reencrypt_state(RuntimeOrigin::signed(alice[0].address), 7),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(Ethereum::reencrypt_state(RuntimeOrigin::root(), 7));
+		assert_eq!(StateKeyId::<Test>::get(), Some(7));
+		assert_eq!(PendingStateKey::<Test>::get(), None);
+		assert_eq!(StateReencryptionCursor::<Test>::get(), Some(Vec::new()));
+		assert_eq!(
+			last_event(),
+			RuntimeEvent::Ethereum(Event::StateReencryptionStarted { key_id: 7 })
+		);
+	});
+}
+
+#[test]
+fn state_key_switch_waits_for_its_holders() {
+	let (accounts, mut ext) = new_test_ext(3);
+	let (alice, bob, charlie) = (
+		accounts[0].address,
+		accounts[1].address,
+		accounts[2].address,
+	);
+	ext.execute_with(|| {
+		assert_noop!(
+			Ethereum::reencrypt_state(RuntimeOrigin::root(), 7),
+			Error::<Test>::StateKeyNotRegistered
+		);
+		assert_ok!(Ethereum::register_state_key(
+			RuntimeOrigin::root(),
+			7,
+			vec![alice, bob]
+		));
+		assert_eq!(
+			last_event(),
+			RuntimeEvent::Ethereum(Event::StateKeyRegistered { key_id: 7 })
+		);
+		assert_noop!(
+			Ethereum::reencrypt_state(RuntimeOrigin::root(), 8),
+			Error::<Test>::StateKeyNotRegistered
+		);
+		assert_noop!(
+			Ethereum::reencrypt_state(RuntimeOrigin::root(), 7),
+			Error::<Test>::StateKeyNotHeld
+		);
+
+		assert_noop!(
+			Ethereum::report_state_key(RuntimeOrigin::signed(alice), 8),
+			Error::<Test>::StateKeyNotRegistered
+		);
+		assert_noop!(
+			Ethereum::report_state_key(RuntimeOrigin::signed(charlie), 7),
+			Error::<Test>::NotAStateKeyHolder
+		);
+		assert_ok!(Ethereum::report_state_key(RuntimeOrigin::signed(alice), 7));
+		assert_eq!(
+			last_event(),
+			RuntimeEvent::Ethereum(Event::StateKeyReported {
+				key_id: 7,
+				remaining: 1
+			})
+		);
+		assert_noop!(
+			Ethereum::report_state_key(RuntimeOrigin::signed(alice), 7),
+			Error::<Test>::NotAStateKeyHolder
+		);
+		assert_noop!(
+			Ethereum::reencrypt_state(RuntimeOrigin::root(), 7),
+			Error::<Test>::StateKeyNotHeld
+		);
+
+		assert_ok!(Ethereum::report_state_key(RuntimeOrigin::signed(bob), 7));
+		assert_ok!(Ethereum::reencrypt_state(RuntimeOrigin::root(), 7));
+		assert_eq!(StateKeyId::<Test>::get(), Some(7));
+	});
+}
+
+#[test]
+fn blocks_select_an_installed_state_key() {
+	let master_key = [5u8; 32];
+	tp_io::state_key::install_master_keys([master_key]);
+	let (_, mut ext) = new_test_ext(1);
+	ext.execute_with(|| {
+		StateKeyId::<Test>::put(tp_io::state_key::key_id(&master_key));
+		Ethereum::on_initialize(1);
+	});
+}
+
+#[test]
+#[should_panic(expected = "the state master key selected on chain is not installed")]
+fn blocks_fail_without_the_state_key() {
+	let (_, mut ext) = new_test_ext(1);
+	ext.execute_with(|| {
+		// The id of a key no test installs.
+		StateKeyId::<Test>::put(7);
+		Ethereum::on_initialize(1);
+	});
+}
+
+#[test]
+fn state_reencryption_rewrites_every_value() {
+	let (alice, mut ext) = new_test_ext(1);
+	ext.commit_all().unwrap();
+	let account = frame_system::Account::<Test>::hashed_key_for(alice[0].address);
+	ext.execute_with(|| {
+		assert_ok!(Ethereum::register_state_key(
+			RuntimeOrigin::root(),
+			7,
+			Vec::new()
+		));
+		assert_ok!(Ethereum::reencrypt_state(RuntimeOrigin::root(), 7));
+		Ethereum::on_idle(1, Weight::MAX);
+		assert_eq!(StateReencryptionCursor::<Test>::get(), None);
+		assert_eq!(
+			last_event(),
+			RuntimeEvent::Ethereum(Event::StateReencryptionCompleted)
+		);
+	});
+	assert!(ext
+		.overlayed_changes()
+		.changes()
+		.any(|(key, _)| *key == account));
+}
+
+#[test]
+fn state_reencryption_is_bounded_by_weight() {
+	let db_weight = <Test as frame_system::Config>::DbWeight::get();
+	// The cursor read and write, then the first key and its value.
+	let remaining_weight = db_weight
+		.reads_writes(2, 1)
+		.saturating_add(db_weight.reads_writes(1, 1));
+	let (_, mut ext) = new_test_ext(1);
+	ext.execute_with(|| {
+		assert_ok!(Ethereum::register_state_key(
+			RuntimeOrigin::root(),
+			7,
+			Vec::new()
+		));
+		assert_ok!(Ethereum::reencrypt_state(RuntimeOrigin::root(), 7));
+		let cursor_key = StateReencryptionCursor::<Test>::hashed_key();
+		let first = sp_io::storage::next_key(&[])
+			.filter(|key| *key != cursor_key)
+			.unwrap();
+
+		// The value length is charged on top of its read and write.
+		ReencryptionWeightPerByte::set(Weight::from_parts(1, 0));
+		Ethereum::on_idle(1, remaining_weight);
+		assert_eq!(StateReencryptionCursor::<Test>::get(), Some(Vec::new()));
+
+		ReencryptionWeightPerByte::set(Weight::zero());
+		assert_eq!(Ethereum::on_idle(1, remaining_weight), remaining_weight);
+		assert_eq!(StateReencryptionCursor::<Test>::get(), Some(first));
+	});
+}
+
+#[test]
+fn non_perceptible_log_data_stays_in_state() {
+	let (_, mut ext) = new_test_ext(1);
+	ext.execute_with(|| {
+		let transaction_hash = H256::repeat_byte(0x22);
+		let receiver = H256::from(H160::repeat_byte(0x19));
+		let log = |marker: H256, data: &[u8]| ethereum::Log {
+			address: H160::repeat_byte(0xae),
+			topics: vec![H256::repeat_byte(0x8a), marker, receiver],
+			data: data.to_vec(),
+		};
+		let logs = Ethereum::commit_logs(
+			transaction_hash,
+			&[
+				log(tp_ethereum::PERCEPTIBLE, &[1; 32]),
+				log(tp_ethereum::NON_PERCEPTIBLE, &[2; 40]),
+			],
+		);
+
+		assert_eq!(logs[0].data, [1; 32]);
+		assert_eq!(logs[1].data, sp_io::hashing::keccak_256(&[2; 40]));
+		assert_eq!(
+			NonPerceptibleLogData::<Test>::get(transaction_hash, 0),
+			None
+		);
+		assert_eq!(
+			NonPerceptibleLogData::<Test>::get(transaction_hash, 1),
+			Some(vec![2; 40])
+		);
+	});
+}
+
+#[test]
+fn public_keys_must_be_valid_points() {
+	let (accounts, mut ext) = new_test_ext(2);
+	let public_key = |account: &AccountInfo| account.public_key().to_vec();
+	let (alice, bob) = (accounts[0].address, accounts[1].address);
+	ext.execute_with(|| {
+		assert_noop!(
+			Ethereum::register_public_key(alice, vec![0xff; 64]),
+			Error::<Test>::InvalidPublicKey
+		);
+		assert_noop!(
+			Ethereum::register_public_key(alice, public_key(&accounts[1])),
+			Error::<Test>::PublicKeyNotOfAccount
+		);
+		assert_ok!(Ethereum::register_public_key(
+			alice,
+			public_key(&accounts[0])
+		));
+
+		assert_noop!(
+			Ethereum::rotate_public_key(alice, vec![0xff; 64]),
+			Error::<Test>::InvalidPublicKey
+		);
+		// A rotated key is any valid key of the holder's choice.
+		assert_ok!(Ethereum::rotate_public_key(alice, public_key(&accounts[1])));
+		assert_eq!(AccountPublic::<Test>::get(alice), public_key(&accounts[1]));
+		assert!(!AccountPublic::<Test>::contains_key(bob));
+	});
+}
+
+#[test]
+fn registered_accounts_only_send_encrypted_transactions() {
+	let (alice, mut ext) = new_test_ext(1);
+	let alice = &alice[0];
+	ext.execute_with(|| {
+		assert_ok!(Ethereum::register_public_key(
+			alice.address,
+			alice.public_key().to_vec()
+		));
+		let invalid = |code| -> Result<(), TransactionValidityError> {
+			Err(InvalidTransaction::Custom(code).into())
+		};
+
+		// The input of a Legacy transaction of a registered account is sealed to the enclave.
+		let create = LegacyUnsignedTransaction {
+			nonce: U256::zero(),
+			gas_price: 1.into(),
+			gas_limit: 100_000.into(),
+			action: TransactionAction::Create,
+			value: U256::zero(),
+			input: vec![0x60, 0x01, 0x60, 0x00, 0xf3],
+		}
+		.sign(&alice.private_key);
+		assert_eq!(
+			Ethereum::validate_transaction_in_block(alice.address, &create),
+			invalid(tp_ethereum::DECRYPTION_FAILED)
+		);
+
+		let message = tp_ethereum::EIP1559TransactionMessage {
+			chain_id: ChainId::get(),
+			nonce: U256::zero(),
+			max_priority_fee_per_gas: U256::zero(),
+			max_fee_per_gas: 1.into(),
+			gas_limit: 100_000.into(),
+			action: TransactionAction::Create,
+			value: U256::zero(),
+			input: vec![0x60, 0x01, 0x60, 0x00, 0xf3],
+			access_list: Vec::new(),
+		};
+		let signing_message = libsecp256k1::Message::parse_slice(&message.hash()[..]).unwrap();
+		let secret = libsecp256k1::SecretKey::parse_slice(&alice.private_key[..]).unwrap();
+		let (signature, recid) = libsecp256k1::sign(&signing_message, &secret);
+		let rs = signature.serialize();
+		let create = Transaction::EIP1559(tp_ethereum::EIP1559Transaction {
+			chain_id: message.chain_id,
+			nonce: message.nonce,
+			method: ethereum::TransactionMethod::Universal(ethereum::UniversalTransaction {
+				max_priority_fee_per_gas: message.max_priority_fee_per_gas,
+				max_fee_per_gas: message.max_fee_per_gas,
+				gas_limit: message.gas_limit,
+				action: message.action,
+				value: message.value,
+				input: message.input,
+				access_list: message.access_list,
+			}),
+			odd_y_parity: recid.serialize() != 0,
+			r: H256::from_slice(&rs[0..32]),
+			s: H256::from_slice(&rs[32..64]),
+		});
+		assert_eq!(
+			Ethereum::validate_transaction_in_block(alice.address, &create),
+			invalid(tp_ethereum::PLAINTEXT_TRANSACTION_FROM_REGISTERED_ACCOUNT)
+		);
+	});
+}
+
+#[test]
+fn encrypted_create_reports_its_contract_address() {
+	let (alice, mut ext) = new_test_ext(1);
+	let alice = &alice[0];
+	ext.execute_with(|| {
+		assert_ok!(Ethereum::register_public_key(
+			alice.address,
+			alice.public_key().to_vec()
+		));
+		// Returns the one byte contract `STOP`.
+		let init_code = [0x60, 0x01, 0x60, 0x00, 0xf3];
+		let aad = tp_io::envelope::sealed_input_aad(Some(ChainId::get()), 0);
+		let sealed = tp_io::EnclaveKey::from_seed(&ENCLAVE_SEED)
+			.unwrap()
+			.encrypt(&init_code, &aad, &alice.public_key())
+			.unwrap();
+		let create = LegacyUnsignedTransaction {
+			nonce: U256::zero(),
+			gas_price: 1.into(),
+			gas_limit: 100_000.into(),
+			action: TransactionAction::Create,
+			value: U256::zero(),
+			input: sealed.clone(),
+		}
+		.sign(&alice.private_key);
+
+		assert_ok!(Ethereum::validate_transaction_in_block(
+			alice.address,
+			&create
+		));
+		assert_ok!(transact(alice, create));
+
+		let (transaction, status, _) = Pending::<Test>::get().pop().unwrap();
+		assert!(matches!(transaction, Transaction::Legacy(ref t) if t.input == sealed));
+		assert_eq!(status.to, None);
+		let contract = status
+			.contract_address
+			.expect("the receipt reports the contract");
+		assert_eq!(pallet_evm::AccountCodes::<Test>::get(contract), vec![0x00]);
+	});
+}
+
+#[test]
+fn only_contracts_under_construction_declare_a_policy() {
+	let (alice, mut ext) = new_test_ext(1);
+	ext.execute_with(|| {
+		let sender = alice[0].address;
+		let contract = H160::repeat_byte(0x11);
+		let policy = ConfidentialityPolicy::ConfidentialInputs;
+
+		assert_noop!(
+			Ethereum::declare_confidentiality_policy(contract, policy),
+			Error::<Test>::NotUnderConstruction
+		);
+		Ethereum::with_transaction_origin(sender, || {
+			assert_noop!(
+				Ethereum::declare_confidentiality_policy(sender, policy),
+				Error::<Test>::NotUnderConstruction
+			);
+			assert_ok!(Ethereum::declare_confidentiality_policy(contract, policy));
+		});
+		assert_eq!(ConfidentialityPolicies::<Test>::get(contract), policy);
+		assert_eq!(
+			last_event(),
+			RuntimeEvent::Ethereum(Event::ConfidentialityPolicyDeclared { contract, policy })
+		);
+	});
+}
+
+#[test]
+fn pre_log_replays_transactions_of_another_chain() {
+	let (alice, mut ext) = new_test_ext(1);
+	ext.execute_with(|| {
+		let source_chain_id = ChainId::get() + 1;
+		assert_noop!(
+			Ethereum::set_pre_log_source_chain_id(
+				RuntimeOrigin::signed(alice[0].address),
+				Some(source_chain_id)
+			),
+			DispatchError::BadOrigin
+		);
+		let transaction = LegacyUnsignedTransaction {
+			nonce: U256::zero(),
+			gas_price: U256::one(),
+			gas_limit: 21_000.into(),
+			action: TransactionAction::Call(H160::repeat_byte(0x11)),
+			value: 1.into(),
+			input: Vec::new(),
+		}
+		.sign_with_chain_id(&alice[0].private_key, source_chain_id);
+		let header = tp_ethereum::PartialHeader {
+			parent_hash: H256::zero(),
+			beneficiary: H160::zero(),
+			state_root: H256::zero(),
+			receipts_root: H256::zero(),
+			logs_bloom: Bloom::default(),
+			difficulty: U256::zero(),
+			number: U256::one(),
+			gas_limit: 21_000.into(),
+			gas_used: 21_000.into(),
+			timestamp: 0,
+			extra_data: Vec::new(),
+			mix_hash: H256::zero(),
+			nonce: H64::zero(),
+		};
+		let block = tp_ethereum::Block::new(header, vec![transaction], Vec::new());
+
+		// Transactions of another chain are only replayed once governance set it as the source.
+		Ethereum::apply_pre_log(block.clone());
+		assert_eq!(
+			last_event(),
+			RuntimeEvent::Ethereum(Event::PreLogRejected {
+				error: PreLogError::UnexpectedChainId { index: 0 }
+			})
+		);
+		assert!(Pending::<Test>::get().is_empty());
+
+		assert_ok!(Ethereum::set_pre_log_source_chain_id(
+			RuntimeOrigin::root(),
+			Some(source_chain_id)
+		));
+		Ethereum::apply_pre_log(block);
+		assert_eq!(
+			last_event(),
+			RuntimeEvent::Ethereum(Event::PreLogApplied { transactions: 1 })
+		);
+		assert_eq!(Pending::<Test>::get().len(), 1);
+	});
+}
+
+#[test]
+fn state_diff_capture_is_charged() {
+	let (alice, mut ext) = new_test_ext(1);
+	ext.execute_with(|| {
+		let weight = |nonce| {
+			transact(&alice[0], transfer(&alice[0], nonce, 1))
+				.expect("the transfer is applied")
+				.actual_weight
+				.expect("the weight is reported")
+		};
+		let without_diff = weight(0);
+		assert!(CurrentStateDiffs::<Test>::get().is_empty());
+
+		assert_ok!(Ethereum::set_enable_state_diff(RuntimeOrigin::root(), true));
+		let with_diff = weight(1);
+		let diffs = CurrentStateDiffs::<Test>::get();
+		assert_eq!(diffs.len(), 1);
+		assert!(diffs[0]
+			.accounts
+			.iter()
+			.any(|account| account.address == alice[0].address));
+		assert!(with_diff.ref_time() > without_diff.ref_time());
+
+		let gas_limit = 21_000;
+		let reserved = Ethereum::state_diff_weight_limit(gas_limit);
+		assert!(with_diff.ref_time() - without_diff.ref_time() <= reserved.ref_time());
+	});
+}
+
+#[test]
+fn state_diffs_only_cover_public_contracts() {
+	let (alice, mut ext) = new_test_ext(1);
+	ext.execute_with(|| {
+		let contract = H160::repeat_byte(0xcc);
+		pallet_evm::AccountCodes::<Test>::insert(contract, vec![0x00]);
+		assert_ok!(Ethereum::set_enable_state_diff(RuntimeOrigin::root(), true));
+		let call = |nonce: u64| {
+			LegacyUnsignedTransaction {
+				nonce: nonce.into(),
+				gas_price: 1.into(),
+				gas_limit: 100_000.into(),
+				action: TransactionAction::Call(contract),
+				value: 1.into(),
+				input: Vec::new(),
+			}
+			.sign(&alice[0].private_key)
+		};
+
+		assert_ok!(transact(&alice[0], call(0)));
+		assert!(CurrentStateDiffs::<Test>::get().is_empty());
+
+		ConfidentialityPolicies::<Test>::insert(contract, ConfidentialityPolicy::Public);
+		assert_ok!(transact(&alice[0], call(1)));
+		assert_eq!(CurrentStateDiffs::<Test>::get().len(), 1);
+	});
+}
@@ -0,0 +1,919 @@
+//! Re-execution of Ethereum transactions with an EVM event listener, behind the tracing
+//! runtime API, and capture of the state diffs of the transactions a block executes.
+
+use ethereum_types::{H160, H256, U256};
+use evm::{
+	tracing::{Event as EvmEvent, EventListener as EvmEventListener},
+	CreateScheme, ExitError, ExitReason,
+};
+use evm_gasometer::tracing::{Event as GasometerEvent, EventListener as GasometerEventListener};
+use evm_runtime::tracing::{Event as RuntimeEvent, EventListener as RuntimeEventListener};
+use frame_support::{
+	ensure,
+	storage::{with_transaction, TransactionOutcome},
+	traits::Get,
+	weights::Weight,
+};
+use sp_runtime::{traits::UniqueSaturatedInto, DispatchError};
+use sp_std::{
+	collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+	prelude::*,
+};
+// Frontier
+use fp_evm::CallOrCreateInfo;
+use pallet_evm::Runner;
+// Tenet
+use tp_rpc::{
+	AccountDiff, CallFrame, CallType, Change, ConfidentialityPolicy, PrestateAccount, StateDiff,
+	StructLog, TracerConfig, TransactionTrace,
+};
+
+use crate::{
+	ConfidentialityPolicies, Config, EnableStateDiff, Error, Pallet, Transaction,
+	TransactionAction, TransactionData,
+};
+
+environmental::environmental!(listener: Listener);
+
+// The `evm` crates each keep their own listener, these forward to the shared one.
+struct EvmProxy;
+struct RuntimeProxy;
+struct GasometerProxy;
+
+impl EvmEventListener for EvmProxy {
+	fn event(&mut self, event: EvmEvent) {
+		listener::with(|listener| listener.evm_event(event));
+	}
+}
+
+impl RuntimeEventListener for RuntimeProxy {
+	fn event(&mut self, event: RuntimeEvent) {
+		listener::with(|listener| listener.runtime_event(event));
+	}
+}
+
+impl GasometerEventListener for GasometerProxy {
+	fn event(&mut self, event: GasometerEvent) {
+		listener::with(|listener| listener.gasometer_event(event));
+	}
+}
+
+/// Balance, nonce and code of an account.
+type AccountState = (U256, U256, Vec<u8>);
+
+/// Records the state an execution touches before it changes it. The EVM writes through to
+/// storage, so the state is read the first time the execution touches it.
+struct Recorder {
+	read_account: fn(H160) -> AccountState,
+	read_slot: fn(H160, H256) -> H256,
+	accounts: BTreeMap<H160, AccountState>,
+	slots: BTreeMap<(H160, H256), H256>,
+}
+
+struct Frame {
+	call: CallFrame,
+	/// The account whose storage the frame executes against.
+	address: H160,
+	used_gas: u64,
+}
+
+/// Collects the struct logs, the call tree and the touched state of one execution.
+struct Listener {
+	tracer: TracerConfig,
+	struct_logs: Vec<StructLog>,
+	/// Whether the last struct log still waits for the gasometer to charge its opcode.
+	step_pending: bool,
+	/// Frames still executing, the outermost first.
+	frames: Vec<Frame>,
+	root: Option<CallFrame>,
+	/// Slots read or written so far, per contract.
+	storage: BTreeMap<H160, BTreeMap<H256, H256>>,
+	touched: BTreeMap<H160, BTreeSet<H256>>,
+	return_value: Vec<u8>,
+	failed: bool,
+	recorder: Option<Recorder>,
+}
+
+impl Listener {
+	fn new(tracer: TracerConfig) -> Self {
+		Self {
+			tracer,
+			struct_logs: Vec::new(),
+			step_pending: false,
+			frames: Vec::new(),
+			root: None,
+			storage: BTreeMap::new(),
+			touched: BTreeMap::new(),
+			return_value: Vec::new(),
+			failed: false,
+			recorder: None,
+		}
+	}
+
+	/// A listener recording the state the execution touches, for `into_state_diff`.
+	fn recording<T: Config>() -> Self {
+		let mut listener = Self::new(TracerConfig::PrestateTracer);
+		listener.recorder = Some(Recorder {
+			read_account: account_state::<T>,
+			read_slot: pallet_evm::AccountStorages::<T>::get,
+			accounts: BTreeMap::new(),
+			slots: BTreeMap::new(),
+		});
+		listener
+	}
+
+	fn using<R, F: FnOnce() -> R>(&mut self, f: F) -> R {
+		listener::using(self, || {
+			evm::tracing::using(&mut EvmProxy, || {
+				evm_runtime::tracing::using(&mut RuntimeProxy, || {
+					evm_gasometer::tracing::using(&mut GasometerProxy, f)
+				})
+			})
+		})
+	}
+
+	fn touch(&mut self, address: H160) {
+		self.touched.entry(address).or_default();
+		if let Some(recorder) = &mut self.recorder {
+			let read_account = recorder.read_account;
+			recorder
+				.accounts
+				.entry(address)
+				.or_insert_with(|| read_account(address));
+		}
+	}
+
+	fn touch_slot(&mut self, address: H160, index: H256) {
+		self.touch(address);
+		self.touched.entry(address).or_default().insert(index);
+		if let Some(recorder) = &mut self.recorder {
+			let read_slot = recorder.read_slot;
+			recorder
+				.slots
+				.entry((address, index))
+				.or_insert_with(|| read_slot(address, index));
+		}
+	}
+
+	fn remaining_gas(&self) -> u64 {
+		self.frames
+			.last()
+			.map(|frame| frame.call.gas.saturating_sub(frame.used_gas))
+			.unwrap_or_default()
+	}
+
+	fn enter(
+		&mut self,
+		call_type: CallType,
+		from: H160,
+		to: H160,
+		value: U256,
+		input: &[u8],
+		gas: u64,
+	) {
+		self.touch(from);
+		self.touch(to);
+		self.frames.push(Frame {
+			call: CallFrame {
+				call_type,
+				from,
+				to,
+				value,
+				gas,
+				gas_used: 0,
+				input: input.to_vec(),
+				output: Vec::new(),
+				error: None,
+				calls: Vec::new(),
+			},
+			address: to,
+			used_gas: 0,
+		});
+	}
+
+	fn evm_event(&mut self, event: EvmEvent) {
+		match event {
+			EvmEvent::TransactCall {
+				caller,
+				address,
+				value,
+				data,
+				gas_limit,
+			} => self.enter(CallType::Call, caller, address, value, data, gas_limit),
+			EvmEvent::TransactCreate {
+				caller,
+				value,
+				init_code,
+				gas_limit,
+				address,
+			} => self.enter(
+				CallType::Create,
+				caller,
+				address,
+				value,
+				init_code,
+				gas_limit,
+			),
+			EvmEvent::TransactCreate2 {
+				caller,
+				value,
+				init_code,
+				gas_limit,
+				address,
+				..
+			} => self.enter(
+				CallType::Create2,
+				caller,
+				address,
+				value,
+				init_code,
+				gas_limit,
+			),
+			EvmEvent::Call {
+				code_address,
+				input,
+				target_gas,
+				is_static,
+				context,
+				..
+			} => {
+				let parent = self
+					.frames
+					.last()
+					.map(|frame| frame.address)
+					.unwrap_or_default();
+				let call_type = if is_static {
+					CallType::StaticCall
+				} else if context.address == code_address {
+					CallType::Call
+				} else if context.caller == parent {
+					CallType::CallCode
+				} else {
+					CallType::DelegateCall
+				};
+				let remaining = self.remaining_gas();
+				let gas = target_gas.map_or(remaining, |gas| gas.min(remaining));
+				self.enter(
+					call_type,
+					parent,
+					code_address,
+					context.apparent_value,
+					input,
+					gas,
+				);
+				if let Some(frame) = self.frames.last_mut() {
+					frame.address = context.address;
+				}
+			}
+			EvmEvent::Create {
+				caller,
+				address,
+				scheme,
+				value,
+				init_code,
+				target_gas,
+			} => {
+				let call_type = match scheme {
+					CreateScheme::Create2 { .. } => CallType::Create2,
+					_ => CallType::Create,
+				};
+				let remaining = self.remaining_gas();
+				let gas = target_gas.map_or(remaining, |gas| gas.min(remaining));
+				self.enter(call_type, caller, address, value, init_code, gas);
+			}
+			EvmEvent::Suicide {
+				address,
+				target,
+				balance,
+			} => {
+				self.touch(target);
+				if let Some(frame) = self.frames.last_mut() {
+					frame.call.calls.push(CallFrame {
+						call_type: CallType::SelfDestruct,
+						from: address,
+						to: target,
+						value: balance,
+						gas: 0,
+						gas_used: 0,
+						input: Vec::new(),
+						output: Vec::new(),
+						error: None,
+						calls: Vec::new(),
+					});
+				}
+			}
+			EvmEvent::Exit {
+				reason,
+				return_value,
+			} => {
+				let Some(Frame {
+					mut call, used_gas, ..
+				}) = self.frames.pop()
+				else {
+					return;
+				};
+				call.output = return_value.to_vec();
+				call.error = exit_error(reason).map(|error| error.as_bytes().to_vec());
+				call.gas_used = match reason {
+					ExitReason::Succeed(_) | ExitReason::Revert(_) => used_gas,
+					_ => call.gas,
+				};
+				match self.frames.last_mut() {
+					Some(parent) => parent.call.calls.push(call),
+					None => {
+						self.return_value = call.output.clone();
+						self.failed = !matches!(reason, ExitReason::Succeed(_));
+						self.root = Some(call);
+					}
+				}
+			}
+			_ => {}
+		}
+	}
+
+	fn runtime_event(&mut self, event: RuntimeEvent) {
+		match event {
+			RuntimeEvent::Step {
+				context,
+				opcode,
+				position,
+				stack,
+				memory,
+			} => {
+				let TracerConfig::StructLogs {
+					disable_storage,
+					disable_memory,
+					disable_stack,
+				} = self.tracer
+				else {
+					return;
+				};
+				let log = StructLog {
+					pc: position.as_ref().map_or(0, |pc| *pc as u64),
+					op: opcode.as_u8(),
+					gas: self.remaining_gas(),
+					gas_cost: 0,
+					depth: self.frames.len() as u32,
+					stack: (!disable_stack).then(|| stack.data().clone()),
+					memory: (!disable_memory).then(|| memory.data().clone()),
+					storage: (!disable_storage).then(|| self.slots(context.address)),
+				};
+				self.struct_logs.push(log);
+				self.step_pending = true;
+			}
+			RuntimeEvent::SLoad {
+				address,
+				index,
+				value,
+			}
+			| RuntimeEvent::SStore {
+				address,
+				index,
+				value,
+			} => {
+				// Storage events come before the write.
+				self.touch_slot(address, index);
+				self.storage
+					.entry(address)
+					.or_default()
+					.insert(index, value);
+				if let TracerConfig::StructLogs {
+					disable_storage: false,
+					..
+				} = self.tracer
+				{
+					let slots = self.slots(address);
+					if let Some(log) = self.struct_logs.last_mut() {
+						log.storage = Some(slots);
+					}
+				}
+			}
+			_ => {}
+		}
+	}
+
+	fn gasometer_event(&mut self, event: GasometerEvent) {
+		// Snapshots are taken before the cost is recorded.
+		let (cost, snapshot) = match event {
+			GasometerEvent::RecordCost { cost, snapshot }
+			| GasometerEvent::RecordTransaction { cost, snapshot } => (cost, snapshot),
+			GasometerEvent::RecordDynamicCost {
+				gas_cost,
+				memory_gas,
+				snapshot,
+				..
+			} => {
+				let memory_cost = snapshot.map_or(memory_gas, |snapshot| {
+					memory_gas.saturating_sub(snapshot.memory_gas)
+				});
+				(gas_cost.saturating_add(memory_cost), snapshot)
+			}
+			_ => return,
+		};
+		let Some(snapshot) = snapshot else {
+			return;
+		};
+		let Some(frame) = self.frames.last_mut() else {
+			return;
+		};
+		let used_gas = snapshot.used_gas.saturating_add(snapshot.memory_gas);
+		frame.used_gas = used_gas.saturating_add(cost);
+
+		if self.step_pending {
+			if let Some(log) = self.struct_logs.last_mut() {
+				log.gas = snapshot.gas_limit.saturating_sub(used_gas);
+				log.gas_cost = cost;
+			}
+			self.step_pending = false;
+		}
+	}
+
+	fn slots(&self, address: H160) -> Vec<(H256, H256)> {
+		self.storage
+			.get(&address)
+			.map(|slots| slots.iter().map(|(key, value)| (*key, *value)).collect())
+			.unwrap_or_default()
+	}
+
+	/// Whether every contract the execution touched explicitly declared a public policy.
+	/// Traces and state diffs expose the intermediate values and the storage of the contracts
+	/// they touch, which a contract only discloses by declaring itself public.
+	fn touched_public_contracts_only<T: Config>(&self) -> bool {
+		self.touched.keys().all(|address| {
+			pallet_evm::AccountCodes::<T>::decode_len(address).unwrap_or_default() == 0
+				|| ConfidentialityPolicies::<T>::try_get(address)
+					== Ok(ConfidentialityPolicy::Public)
+		})
+	}
+
+	fn into_trace<T: Config>(self, used_gas: U256) -> Result<TransactionTrace, DispatchError> {
+		let used_gas = used_gas.unique_saturated_into();
+
+		Ok(match self.tracer {
+			TracerConfig::StructLogs { .. } => TransactionTrace::StructLogs {
+				gas: used_gas,
+				failed: self.failed,
+				return_value: self.return_value,
+				struct_logs: self.struct_logs,
+			},
+			TracerConfig::CallTracer => {
+				let mut root = self
+					.root
+					.ok_or(DispatchError::Other("Execution did not start"))?;
+				root.gas_used = used_gas;
+				TransactionTrace::CallTracer(root)
+			}
+			TracerConfig::PrestateTracer => TransactionTrace::PrestateTracer(
+				self.touched
+					.into_iter()
+					.map(|(address, slots)| {
+						let (account, _) = pallet_evm::Pallet::<T>::account_basic(&address);
+						PrestateAccount {
+							address,
+							balance: (!Pallet::<T>::is_public_key_registered(address))
+								.then_some(account.balance),
+							nonce: account.nonce,
+							code: pallet_evm::AccountCodes::<T>::get(address),
+							storage: slots
+								.into_iter()
+								.map(|slot| {
+									(slot, pallet_evm::AccountStorages::<T>::get(address, slot))
+								})
+								.collect(),
+						}
+					})
+					.collect(),
+			),
+		})
+	}
+
+	/// Compare the recorded state with the current one, once the execution is committed.
+	fn into_state_diff<T: Config>(self, transaction_hash: H256) -> Option<StateDiff> {
+		if !self.touched_public_contracts_only::<T>() {
+			return None;
+		}
+		let recorder = self.recorder?;
+
+		let mut storage = BTreeMap::<H160, Vec<(H256, Change<H256>)>>::new();
+		for ((address, index), before) in recorder.slots {
+			let after = pallet_evm::AccountStorages::<T>::get(address, index);
+			if let Some(change) = Change::new(before, after) {
+				storage.entry(address).or_default().push((index, change));
+			}
+		}
+
+		let accounts = recorder
+			.accounts
+			.into_iter()
+			.filter_map(|(address, (balance, nonce, code))| {
+				let (balance_after, nonce_after, code_after) = account_state::<T>(address);
+				let diff = AccountDiff {
+					address,
+					balance: Change::new(balance, balance_after)
+						.filter(|_| !Pallet::<T>::is_public_key_registered(address)),
+					nonce: Change::new(nonce, nonce_after),
+					code: Change::new(code, code_after),
+					storage: storage.remove(&address).unwrap_or_default(),
+				};
+				let changed = diff.balance.is_some()
+					|| diff.nonce.is_some()
+					|| diff.code.is_some()
+					|| !diff.storage.is_empty();
+				changed.then_some(diff)
+			})
+			.collect();
+
+		Some(StateDiff {
+			transaction_hash,
+			accounts,
+		})
+	}
+}
+
+fn account_state<T: Config>(address: H160) -> AccountState {
+	let (account, _) = pallet_evm::Pallet::<T>::account_basic(&address);
+	(
+		account.balance,
+		account.nonce,
+		pallet_evm::AccountCodes::<T>::get(address),
+	)
+}
+
+fn exit_error(reason: &ExitReason) -> Option<&'static str> {
+	Some(match reason {
+		ExitReason::Succeed(_) => return None,
+		ExitReason::Revert(_) => "execution reverted",
+		ExitReason::Error(ExitError::OutOfGas) => "out of gas",
+		ExitReason::Error(ExitError::InvalidJump) => "invalid jump destination",
+		ExitReason::Error(ExitError::StackUnderflow) => "stack underflow",
+		ExitReason::Error(ExitError::StackOverflow) => "stack limit reached",
+		ExitReason::Error(ExitError::CallTooDeep) => "max call depth exceeded",
+		ExitReason::Error(ExitError::OutOfFund) => "insufficient balance for transfer",
+		ExitReason::Error(ExitError::CreateCollision) => "contract address collision",
+		ExitReason::Error(ExitError::DesignatedInvalid | ExitError::InvalidCode(_)) => {
+			"invalid opcode"
+		}
+		ExitReason::Error(_) => "execution error",
+		ExitReason::Fatal(_) => "fatal error",
+	})
+}
+
+/// Lowest gas an execution pays the first time it touches an account or a storage slot. A
+/// cold access costs 2600 gas to an account and 2100 gas to a slot under EIP-2929, which an
+/// access list lowers to 2400 and 1900 gas, the 1900 gas of a slot being the lowest.
+const MIN_GAS_PER_TOUCHED_ITEM: u64 = 1900;
+
+impl<T: Config> Pallet<T> {
+	/// Trace `transaction` on top of the current state, then roll its changes back.
+	/// Confidential transactions and executions touching a contract that did not declare
+	/// itself public are not traced.
+	pub fn trace_transaction(
+		transaction: &Transaction,
+		tracer: TracerConfig,
+	) -> Result<TransactionTrace, DispatchError> {
+		let source = Self::recover_signer(transaction).ok_or(Error::<T>::InvalidSignature)?;
+		ensure!(
+			!Self::is_confidential(source, transaction),
+			Error::<T>::UntraceableConfidentialExecution
+		);
+
+		let mut listener = Listener::new(tracer);
+		let used_gas = Self::traced(&mut listener, || {
+			let (_, _, info) = Self::execute(source, transaction, None).map_err(|err| err.error)?;
+			Ok(match info {
+				CallOrCreateInfo::Call(info) => info.used_gas.standard,
+				CallOrCreateInfo::Create(info) => info.used_gas.standard,
+			})
+		})?;
+		listener.into_trace::<T>(used_gas)
+	}
+
+	/// Trace a call on top of the current state, as `eth_call` runs it. Calls touching a
+	/// contract that did not declare itself public are not traced.
+	pub fn trace_call(
+		from: H160,
+		to: Option<H160>,
+		data: Vec<u8>,
+		value: U256,
+		gas_limit: U256,
+		tracer: TracerConfig,
+	) -> Result<TransactionTrace, DispatchError> {
+		let gas_limit = gas_limit.min(u64::MAX.into());
+		let action = to.map_or(TransactionAction::Create, TransactionAction::Call);
+		let transaction_data = TransactionData::new(
+			action,
+			data.clone(),
+			U256::zero(),
+			gas_limit,
+			None,
+			None,
+			None,
+			value,
+			Some(<T as pallet_evm::Config>::ChainId::get()),
+			Vec::new(),
+		);
+		let (weight_limit, proof_size_base_cost) = Self::transaction_weight(&transaction_data);
+		let gas_limit = gas_limit.unique_saturated_into();
+
+		let mut listener = Listener::new(tracer);
+		let used_gas = Self::traced(&mut listener, || {
+			Self::with_transaction_origin(from, || match to {
+				Some(to) => T::Runner::call(
+					from,
+					to,
+					data,
+					value,
+					gas_limit,
+					None,
+					None,
+					None,
+					Vec::new(),
+					false,
+					true,
+					weight_limit,
+					proof_size_base_cost,
+					T::config(),
+				)
+				.map(|info| info.used_gas.standard)
+				.map_err(|err| err.error.into()),
+				None => T::Runner::create(
+					from,
+					data,
+					value,
+					gas_limit,
+					None,
+					None,
+					None,
+					Vec::new(),
+					false,
+					true,
+					weight_limit,
+					proof_size_base_cost,
+					T::config(),
+				)
+				.map(|info| info.used_gas.standard)
+				.map_err(|err| err.error.into()),
+			})
+		})?;
+		listener.into_trace::<T>(used_gas)
+	}
+
+	/// Run `execute`, the execution of `transaction` by `source`, and capture its state diff
+	/// once it is committed if state diffs are enabled. Confidential transactions and
+	/// executions touching a contract that did not declare itself public get no state diff.
+	/// The weight of the capture is returned along.
+	pub(crate) fn with_state_diff<R>(
+		source: H160,
+		transaction: &Transaction,
+		execute: impl FnOnce() -> R,
+	) -> (R, Option<StateDiff>, Weight) {
+		// read: EnableStateDiff
+		let weight = T::DbWeight::get().reads(1);
+		if !EnableStateDiff::<T>::get() || Self::is_confidential(source, transaction) {
+			return (execute(), None, weight);
+		}
+
+		let mut listener = Listener::recording::<T>();
+		// Fees are paid outside the EVM, before and after the execution.
+		listener.touch(source);
+		listener.touch(pallet_evm::Pallet::<T>::find_author());
+		let result = listener.using(execute);
+		let weight = weight.saturating_add(listener.recorder.as_ref().map_or(
+			Weight::zero(),
+			|recorder| {
+				Self::state_diff_weight(recorder.accounts.len() as u64, recorder.slots.len() as u64)
+			},
+		));
+		(
+			result,
+			listener.into_state_diff::<T>(transaction.hash()),
+			weight,
+		)
+	}
+
+	/// Weight of capturing the state diff of an execution touching `accounts` accounts and
+	/// `slots` storage slots, on top of the execution. Each account is read before and after
+	/// the execution, its balance and code, and checked for a public key and a confidential
+	/// state, each slot is read before and after, and the diff is appended to
+	/// `CurrentStateDiffs`, which does not read it.
+	fn state_diff_weight(accounts: u64, slots: u64) -> Weight {
+		let reads = accounts
+			.saturating_mul(6)
+			.saturating_add(slots.saturating_mul(2));
+		T::DbWeight::get().reads_writes(reads, 1)
+	}
+
+	/// Bound of the weight `with_state_diff` returns for a transaction of `gas_limit`, whether
+	/// state diffs are enabled or not, so that the dispatch weight reads no storage. But for the
+	/// sender and the block author, every account or slot touched costs
+	/// [`MIN_GAS_PER_TOUCHED_ITEM`] at least. Warm precompiles are not accounted.
+	pub(crate) fn state_diff_weight_limit(gas_limit: u64) -> Weight {
+		// read: EnableStateDiff
+		let weight = T::DbWeight::get().reads(1);
+		let touched = (gas_limit / MIN_GAS_PER_TOUCHED_ITEM).saturating_add(2);
+		weight.saturating_add(Self::state_diff_weight(touched, 0))
+	}
+
+	/// Run `execute` under `listener`, then roll its changes back. The policies are checked
+	/// before the rollback, so that contracts created by the execution are covered.
+	fn traced<R>(
+		listener: &mut Listener,
+		execute: impl FnOnce() -> Result<R, DispatchError>,
+	) -> Result<R, DispatchError> {
+		with_transaction(|| {
+			let result = listener.using(execute).and_then(|result| {
+				ensure!(
+					listener.touched_public_contracts_only::<T>(),
+					Error::<T>::UntraceableConfidentialExecution
+				);
+				Ok(result)
+			});
+			TransactionOutcome::Rollback(result)
+		})
+	}
+}
+
+#[cfg(all(feature = "std", test))]
+mod tests {
+	use super::*;
+	use crate::mock::*;
+
+	/// Store 42 in slot 0, read it back and return it.
+	const CODE: [u8; 16] = [
+		0x60, 0x2a, 0x60, 0x00, 0x55, // SSTORE(0, 42)
+		0x60, 0x00, 0x54, // SLOAD(0)
+		0x60, 0x00, 0x52, // MSTORE(0, _)
+		0x60, 0x20, 0x60, 0x00, 0xf3, // RETURN(0, 32)
+	];
+	const GAS_LIMIT: u64 = 100_000;
+
+	/// A contract running `CODE` that declared itself public.
+	fn contract() -> H160 {
+		let contract = H160::repeat_byte(0xcc);
+		pallet_evm::AccountCodes::<Test>::insert(contract, CODE.to_vec());
+		ConfidentialityPolicies::<Test>::insert(contract, ConfidentialityPolicy::Public);
+		contract
+	}
+
+	fn try_trace(
+		from: H160,
+		to: H160,
+		tracer: TracerConfig,
+	) -> Result<TransactionTrace, DispatchError> {
+		Ethereum::trace_call(
+			from,
+			Some(to),
+			Vec::new(),
+			U256::zero(),
+			GAS_LIMIT.into(),
+			tracer,
+		)
+	}
+
+	fn trace(from: H160, to: H160, tracer: TracerConfig) -> TransactionTrace {
+		try_trace(from, to, tracer).expect("the call is traced")
+	}
+
+	fn word(value: u64) -> Vec<u8> {
+		H256::from_low_u64_be(value).as_bytes().to_vec()
+	}
+
+	#[test]
+	fn call_tracer_reports_the_call() {
+		let (alice, mut ext) = new_test_ext(1);
+		ext.execute_with(|| {
+			let contract = contract();
+			let TransactionTrace::CallTracer(call) =
+				trace(alice[0].address, contract, TracerConfig::CallTracer)
+			else {
+				panic!("a call tracer trace is returned");
+			};
+
+			assert_eq!(call.call_type, CallType::Call);
+			assert_eq!(call.from, alice[0].address);
+			assert_eq!(call.to, contract);
+			assert_eq!(call.gas, GAS_LIMIT);
+			assert!(call.gas_used > 21_000 && call.gas_used < GAS_LIMIT);
+			assert_eq!(call.output, word(42));
+			assert_eq!(call.error, None);
+			assert!(call.calls.is_empty());
+			// The traced call is rolled back.
+			assert_eq!(
+				pallet_evm::AccountStorages::<Test>::get(contract, H256::zero()),
+				H256::zero()
+			);
+		});
+	}
+
+	#[test]
+	fn struct_logs_report_every_opcode() {
+		let (alice, mut ext) = new_test_ext(1);
+		ext.execute_with(|| {
+			let contract = contract();
+			let tracer = TracerConfig::StructLogs {
+				disable_storage: false,
+				disable_memory: true,
+				disable_stack: false,
+			};
+			let TransactionTrace::StructLogs {
+				failed,
+				return_value,
+				struct_logs,
+				..
+			} = trace(alice[0].address, contract, tracer)
+			else {
+				panic!("a struct logs trace is returned");
+			};
+
+			assert!(!failed);
+			assert_eq!(return_value, word(42));
+			let steps: Vec<_> = struct_logs.iter().map(|log| (log.pc, log.op)).collect();
+			assert_eq!(
+				steps,
+				vec![
+					(0, 0x60),
+					(2, 0x60),
+					(4, 0x55),
+					(5, 0x60),
+					(7, 0x54),
+					(8, 0x60),
+					(10, 0x52),
+					(11, 0x60),
+					(13, 0x60),
+					(15, 0xf3),
+				]
+			);
+			assert!(struct_logs.iter().all(|log| log.depth == 1));
+			assert!(struct_logs.iter().all(|log| log.memory.is_none()));
+			// PUSH1 costs 3 gas, taken from the gas left before the opcode.
+			assert_eq!(struct_logs[0].gas_cost, 3);
+			assert_eq!(struct_logs[1].gas, struct_logs[0].gas - 3);
+			assert_eq!(
+				struct_logs[2].stack,
+				Some(vec![H256::from_low_u64_be(42), H256::zero()])
+			);
+			assert_eq!(
+				struct_logs[2].storage,
+				Some(vec![(H256::zero(), H256::from_low_u64_be(42))])
+			);
+		});
+	}
+
+	#[test]
+	fn prestate_tracer_reports_the_state_before_the_call() {
+		let (alice, mut ext) = new_test_ext(1);
+		ext.execute_with(|| {
+			let contract = contract();
+			let TransactionTrace::PrestateTracer(accounts) =
+				trace(alice[0].address, contract, TracerConfig::PrestateTracer)
+			else {
+				panic!("a prestate tracer trace is returned");
+			};
+
+			assert_eq!(accounts.len(), 2);
+			let sender = accounts
+				.iter()
+				.find(|account| account.address == alice[0].address)
+				.expect("the sender is touched");
+			assert!(sender.balance.is_some());
+			assert!(sender.code.is_empty());
+			assert!(sender.storage.is_empty());
+
+			let called = accounts
+				.iter()
+				.find(|account| account.address == contract)
+				.expect("the contract is touched");
+			assert_eq!(called.code, CODE.to_vec());
+			assert_eq!(called.storage, vec![(H256::zero(), H256::zero())]);
+		});
+	}
+
+	#[test]
+	fn only_public_contracts_are_traced() {
+		let (alice, mut ext) = new_test_ext(1);
+		ext.execute_with(|| {
+			let undeclared = H160::repeat_byte(0xdd);
+			pallet_evm::AccountCodes::<Test>::insert(undeclared, CODE.to_vec());
+			let confidential = H160::repeat_byte(0xee);
+			pallet_evm::AccountCodes::<Test>::insert(confidential, CODE.to_vec());
+			ConfidentialityPolicies::<Test>::insert(
+				confidential,
+				ConfidentialityPolicy::ConfidentialInputs,
+			);
+
+			for contract in [undeclared, confidential] {
+				assert_eq!(
+					try_trace(alice[0].address, contract, TracerConfig::PrestateTracer),
+					Err(Error::<Test>::UntraceableConfidentialExecution.into())
+				);
+			}
+			// A transfer to an account without code is traced.
+			assert!(try_trace(
+				alice[0].address,
+				H160::repeat_byte(0x11),
+				TracerConfig::CallTracer
+			)
+			.is_ok());
+		});
+	}
+}
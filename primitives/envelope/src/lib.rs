@@ -0,0 +1,132 @@
+//! Versioned envelope of the AES-GCM ciphertexts produced for confidential transactions.
+//!
+//! An envelope is serialized as `version ++ nonce ++ ciphertext ++ tag`. Ciphertexts produced
+//! before envelopes were introduced carry no header and use `aad[20..]` as nonce; they are
+//! still accepted by the decryption path of the node.
+//!
+//! The crate is shared by the node and by clients sealing payloads to it, so it only depends
+//! on `aes-gcm` and leaves nonce generation to its callers.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use aes_gcm::{
+	aead::{Aead, Payload},
+	Aes256Gcm, KeyInit, Nonce,
+};
+use alloc::vec::Vec;
+
+/// Version of the envelopes produced by this crate.
+pub const ENVELOPE_VERSION: u8 = 1;
+/// Length of the AES-GCM nonce.
+pub const NONCE_LEN: usize = 12;
+/// Length of the AES-GCM authentication tag.
+pub const TAG_LEN: usize = 16;
+
+/// A versioned AES-GCM ciphertext carrying its own nonce.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Envelope {
+	pub version: u8,
+	pub nonce: [u8; NONCE_LEN],
+	pub ciphertext: Vec<u8>,
+	pub tag: [u8; TAG_LEN],
+}
+
+impl Envelope {
+	/// Build an envelope of the current version from the `ciphertext ++ tag` output of
+	/// AES-GCM.
+	pub fn new(nonce: [u8; NONCE_LEN], sealed: &[u8]) -> Option<Self> {
+		let split = sealed.len().checked_sub(TAG_LEN)?;
+		let mut tag = [0u8; TAG_LEN];
+		tag.copy_from_slice(&sealed[split..]);
+		Some(Self {
+			version: ENVELOPE_VERSION,
+			nonce,
+			ciphertext: sealed[..split].to_vec(),
+			tag,
+		})
+	}
+
+	/// Parse an envelope of the current version. Legacy ciphertexts return `None`.
+	pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+		let (&version, rest) = bytes.split_first()?;
+		if version != ENVELOPE_VERSION || rest.len() < NONCE_LEN + TAG_LEN {
+			return None;
+		}
+		let (nonce, rest) = rest.split_at(NONCE_LEN);
+		let mut envelope = Self::new([0u8; NONCE_LEN], rest)?;
+		envelope.nonce.copy_from_slice(nonce);
+		Some(envelope)
+	}
+
+	pub fn to_bytes(&self) -> Vec<u8> {
+		let mut bytes = Vec::with_capacity(1 + NONCE_LEN + self.ciphertext.len() + TAG_LEN);
+		bytes.push(self.version);
+		bytes.extend_from_slice(&self.nonce);
+		bytes.extend_from_slice(&self.ciphertext);
+		bytes.extend_from_slice(&self.tag);
+		bytes
+	}
+
+	/// The `ciphertext ++ tag` input expected by AES-GCM.
+	pub fn sealed(&self) -> Vec<u8> {
+		[&self.ciphertext[..], &self.tag[..]].concat()
+	}
+}
+
+/// Additional data of the input of a confidential Legacy or EIP-2930 transaction.
+///
+/// The signature of these transactions covers their input, so their sender seals the input
+/// before signing them, under `b"tenet-in" ++ chain_id ++ nonce` in big endian, where a
+/// transaction without chain id has a zero chain id.
+pub fn sealed_input_aad(chain_id: Option<u64>, nonce: u128) -> [u8; 32] {
+	let mut aad = [0u8; 32];
+	aad[..8].copy_from_slice(b"tenet-in");
+	aad[8..16].copy_from_slice(&chain_id.unwrap_or_default().to_be_bytes());
+	aad[16..].copy_from_slice(&nonce.to_be_bytes());
+	aad
+}
+
+/// Seal `msg` under `key` into an envelope. `nonce` must never be reused with the same key.
+pub fn seal(key: &[u8; 32], nonce: [u8; NONCE_LEN], msg: &[u8], aad: &[u8]) -> Option<Envelope> {
+	let sealed = Aes256Gcm::new_from_slice(key)
+		.ok()?
+		.encrypt(Nonce::from_slice(&nonce), Payload { msg, aad })
+		.ok()?;
+	Envelope::new(nonce, &sealed)
+}
+
+/// Open a serialized envelope sealed under `key`. Legacy ciphertexts return `None`.
+pub fn open(key: &[u8; 32], bytes: &[u8], aad: &[u8]) -> Option<Vec<u8>> {
+	let envelope = Envelope::from_bytes(bytes)?;
+	Aes256Gcm::new_from_slice(key)
+		.ok()?
+		.decrypt(
+			Nonce::from_slice(&envelope.nonce),
+			Payload {
+				msg: &envelope.sealed(),
+				aad,
+			},
+		)
+		.ok()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn seal_and_open() {
+		let key = [7u8; 32];
+		let envelope = seal(&key, [1u8; NONCE_LEN], b"hello world", b"aad").unwrap();
+		let bytes = envelope.to_bytes();
+		assert_eq!(Envelope::from_bytes(&bytes), Some(envelope));
+		assert_eq!(
+			open(&key, &bytes, b"aad").as_deref(),
+			Some(&b"hello world"[..])
+		);
+		assert_eq!(open(&key, &bytes, b"other"), None);
+		assert_eq!(open(&[8u8; 32], &bytes, b"aad"), None);
+	}
+}
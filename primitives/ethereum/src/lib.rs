@@ -11,5 +11,23 @@ pub use ethereum::{
 	EnvelopedEncodable, Header, LegacyTransaction, LegacyTransactionMessage, PartialHeader,
 	TransactionAction, TransactionSignature, TransactionV0, TransactionV1, TransactionV2,
 };
-pub use receipt::{EIP1559ReceiptData, EIP2930ReceiptData, EIP658ReceiptData, Log, Receipt};
-pub use trie::{generate_proof, order_generate_proof, order_verify_proof};
+pub use receipt::{
+	receivers_root, verify_receiver_proof, EIP1559ReceiptData, EIP2930ReceiptData,
+	EIP658ReceiptData, Log, LogType, Receipt, NON_PERCEPTIBLE, PERCEPTIBLE,
+};
+pub use trie::{
+	evm_code_key, evm_storage_key, generate_proof, order_generate_proof, order_verify_proof,
+	system_account_key, verify_receipt_proof, verify_state_proof, verify_transaction_proof,
+};
+
+/// `InvalidTransaction::Custom` code of a confidential transaction whose payload cannot be
+/// decrypted, outside the codes of `fp_evm::TransactionValidationError`.
+pub const DECRYPTION_FAILED: u8 = 128;
+
+/// `InvalidTransaction::Custom` code of a plaintext transaction calling a contract whose
+/// confidentiality policy requires encrypted calls.
+pub const PLAINTEXT_CALL_TO_CONFIDENTIAL_CONTRACT: u8 = 129;
+
+/// `InvalidTransaction::Custom` code of a plaintext transaction sent by an account that
+/// registered a public key.
+pub const PLAINTEXT_TRANSACTION_FROM_REGISTERED_ACCOUNT: u8 = 130;
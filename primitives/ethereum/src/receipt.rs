@@ -4,7 +4,11 @@ use rlp::{Decodable, DecoderError, Encodable, Rlp};
 type Bytes = alloc::vec::Vec<u8>;
 use bytes::BytesMut;
 use hex_literal::hex;
+use sp_core::KeccakHasher;
 use sp_std::vec::Vec;
+
+use crate::trie;
+
 pub const NON_PERCEPTIBLE: H256 = H256(hex!(
 	"b142c3cfa9b0930e084b17693e39e134b43acaa477193956ecad5597db3919a3"
 ));
@@ -159,6 +163,29 @@ impl Log {
 	pub fn filter(&self, target: H256) -> bool {
 		self.topics.iter().any(|topic| topic == &target)
 	}
+
+	/// The accounts a perceptible or non-perceptible log is addressed to: the topics after
+	/// the event signature holding a left-padded address.
+	pub fn receivers(&self) -> Vec<H160> {
+		if self.log_type.is_none() {
+			return Vec::new();
+		}
+		receivers_of(&self.topics)
+	}
+
+	/// Proof that `receiver` is at the returned index of the trie committed to by
+	/// `receivers_root`, for [`verify_receiver_proof`].
+	pub fn receiver_proof(&self, receiver: H160) -> Option<(usize, Vec<Vec<u8>>)> {
+		self.receivers_root?;
+		let receivers = self.receivers();
+		let index = receivers.iter().position(|account| *account == receiver)?;
+		let (_, proof) = trie::order_generate_proof::<KeccakHasher, _, _>(
+			receivers.iter().map(rlp::encode),
+			index,
+		)
+		.ok()?;
+		Some((index, proof))
+	}
 }
 
 impl Encodable for Log {
@@ -222,7 +249,7 @@ fn parse_event(topics: &[H256], data: &Bytes) -> (Option<LogType>, Option<H256>)
 	match parse_log_type(topics, data) {
 		Some(log_type) => {
 			let root = if log_type == LogType::NonPerceptible {
-				Some(H256::from_low_u64_be(0xff))
+				Some(receivers_root(&receivers_of(topics)))
 			} else {
 				None
 			};
@@ -233,6 +260,38 @@ fn parse_event(topics: &[H256], data: &Bytes) -> (Option<LogType>, Option<H256>)
 	}
 }
 
+fn receivers_of(topics: &[H256]) -> Vec<H160> {
+	topics
+		.iter()
+		.skip(1)
+		.filter(|topic| {
+			**topic != PERCEPTIBLE
+				&& **topic != NON_PERCEPTIBLE
+				&& topic[..12].iter().all(|byte| *byte == 0)
+		})
+		.map(|topic| H160::from(*topic))
+		.collect()
+}
+
+/// Root of the ordered trie of the RLP encoded `receivers`, committed to by a
+/// non-perceptible log.
+pub fn receivers_root(receivers: &[H160]) -> H256 {
+	ethereum::util::ordered_trie_root(receivers.iter().map(rlp::encode))
+}
+
+/// Check a [`Log::receiver_proof`] that `receiver` is at `index` under `root`.
+pub fn verify_receiver_proof(
+	root: H256,
+	index: usize,
+	receiver: H160,
+	proof: Vec<Vec<u8>>,
+) -> bool {
+	matches!(
+		trie::order_verify_proof::<KeccakHasher>(proof, root, index),
+		Ok(Some(value)) if value == rlp::encode(&receiver).to_vec()
+	)
+}
+
 fn parse_log_type(topics: &[H256], data: &Bytes) -> Option<LogType> {
 	let get_id = |data: &Vec<u8>| -> Option<LogType> {
 		if data.is_empty() {
@@ -281,8 +340,6 @@ mod tests {
 	use ethereum_types::{Address, BloomInput};
 	use hash_db::Hasher;
 
-	use crate::trie;
-
 	use super::*;
 	fn logs_bloom(logs: Vec<Log>, bloom: &mut Bloom) {
 		for log in logs {
@@ -395,4 +452,32 @@ mod tests {
 		println!("empty_log {:?}", empty_log);
 		println!("non_empty {:?}", non_empty);
 	}
+
+	#[test]
+	fn receivers_root_commits_to_receivers() {
+		let receivers = [H160::repeat_byte(0x19), H160::repeat_byte(0x20)];
+		let log = Log::from(ethereum::Log {
+			address: H160::repeat_byte(0xae),
+			topics: vec![
+				H256::repeat_byte(0x8a),
+				NON_PERCEPTIBLE,
+				H256::from(receivers[0]),
+				H256::from(receivers[1]),
+			],
+			data: vec![0; 32],
+		});
+		let root = log.receivers_root.unwrap();
+		assert_eq!(root, receivers_root(&receivers));
+
+		let (index, proof) = log.receiver_proof(receivers[1]).unwrap();
+		assert_eq!(index, 1);
+		assert!(verify_receiver_proof(
+			root,
+			index,
+			receivers[1],
+			proof.clone()
+		));
+		assert!(!verify_receiver_proof(root, index, receivers[0], proof));
+		assert_eq!(log.receiver_proof(H160::repeat_byte(0x21)), None);
+	}
 }
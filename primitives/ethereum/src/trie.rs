@@ -1,8 +1,16 @@
 use sp_std::sync::Arc;
 
 use eth_trie::{EthTrie, MemoryDB, Trie, TrieError, DB};
-use ethereum_types::H256;
+use ethereum_types::{H160, H256};
 use hash_db::Hasher;
+use sp_core::{
+	hashing::{blake2_128, twox_128},
+	KeccakHasher,
+};
+use sp_runtime::traits::BlakeTwo256;
+use sp_std::vec::Vec;
+
+use crate::{EnvelopedEncodable, Receipt, TransactionV2};
 
 pub fn order_verify_proof<H>(
 	proof: Vec<Vec<u8>>,
@@ -73,3 +81,151 @@ where
 
 	Ok((trie.root_hash()?, trie.get_proof(key.as_ref())?))
 }
+
+/// Check a proof from `tenet_getTransactionProof` that `transaction` is at `index` under
+/// `transactions_root`, the transactions root of the Ethereum block header.
+pub fn verify_transaction_proof(
+	transactions_root: H256,
+	index: usize,
+	transaction: &TransactionV2,
+	proof: Vec<Vec<u8>>,
+) -> bool {
+	verify_ordered_value(transactions_root, index, &transaction.encode(), proof)
+}
+
+/// Check a proof from `tenet_getReceiptProof` that `receipt` is at `index` under
+/// `receipts_root`, the receipts root of the Ethereum block header.
+pub fn verify_receipt_proof(
+	receipts_root: H256,
+	index: usize,
+	receipt: &Receipt,
+	proof: Vec<Vec<u8>>,
+) -> bool {
+	verify_ordered_value(receipts_root, index, &receipt.encode(), proof)
+}
+
+fn verify_ordered_value(root: H256, index: usize, value: &[u8], proof: Vec<Vec<u8>>) -> bool {
+	matches!(
+		order_verify_proof::<KeccakHasher>(proof, root, index),
+		Ok(Some(proven)) if proven == value
+	)
+}
+
+/// Substrate storage key of the `System.Account` entry of `address`, holding its nonce and
+/// balance.
+pub fn system_account_key(address: H160) -> Vec<u8> {
+	blake2_128_concat_key(
+		&[&twox_128(b"System")[..], &twox_128(b"Account")],
+		address.as_bytes(),
+	)
+}
+
+/// Substrate storage key of the code of `address` in `EVM.AccountCodes`.
+pub fn evm_code_key(address: H160) -> Vec<u8> {
+	blake2_128_concat_key(
+		&[&twox_128(b"EVM")[..], &twox_128(b"AccountCodes")],
+		address.as_bytes(),
+	)
+}
+
+/// Substrate storage key of the storage slot `index` of `address` in `EVM.AccountStorages`.
+pub fn evm_storage_key(address: H160, index: H256) -> Vec<u8> {
+	let mut key = blake2_128_concat_key(
+		&[&twox_128(b"EVM")[..], &twox_128(b"AccountStorages")],
+		address.as_bytes(),
+	);
+	key.extend(blake2_128(index.as_bytes()));
+	key.extend(index.as_bytes());
+	key
+}
+
+fn blake2_128_concat_key(prefix: &[&[u8]], item: &[u8]) -> Vec<u8> {
+	let mut key = prefix.concat();
+	key.extend(blake2_128(item));
+	key.extend(item);
+	key
+}
+
+/// Check the Substrate trie `proof` of `key` returned by `eth_getProof` against `state_root`,
+/// the state root of the block header. Returns the value under `key` as stored in the trie,
+/// or `None` if the proof shows that `key` is absent.
+///
+/// Trie values are encrypted with the state key, so a valid proof only shows which ciphertext
+/// is committed under `key`. It does not prove a plaintext balance, nonce or storage value.
+pub fn verify_state_proof(
+	state_root: H256,
+	proof: Vec<Vec<u8>>,
+	key: &[u8],
+) -> Result<Option<Vec<u8>>, TrieError> {
+	let db = sp_trie::StorageProof::new(proof).into_memory_db::<BlakeTwo256>();
+	sp_trie::read_trie_value::<sp_trie::LayoutV1<BlakeTwo256>, _>(&db, &state_root, key, None, None)
+		.map_err(|_| TrieError::InvalidProof)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_trie::{trie_types::TrieDBMutBuilderV1, TrieMut};
+
+	use crate::EIP658ReceiptData;
+
+	#[test]
+	fn receipt_proof_checks_receipts_root() {
+		let receipt = |used_gas: u64| {
+			Receipt::EIP1559(EIP658ReceiptData {
+				status_code: 1,
+				used_gas: used_gas.into(),
+				logs_bloom: Default::default(),
+				logs: vec![],
+			})
+		};
+		let receipts = vec![receipt(21_000), receipt(42_000), receipt(63_000)];
+		let receipts_root =
+			crate::util::ordered_trie_root(receipts.iter().map(EnvelopedEncodable::encode));
+		let (root, proof) = order_generate_proof::<KeccakHasher, _, _>(
+			receipts.iter().map(EnvelopedEncodable::encode),
+			1,
+		)
+		.unwrap();
+
+		assert_eq!(root, receipts_root);
+		assert!(verify_receipt_proof(
+			receipts_root,
+			1,
+			&receipts[1],
+			proof.clone()
+		));
+		assert!(!verify_receipt_proof(
+			receipts_root,
+			1,
+			&receipts[2],
+			proof.clone()
+		));
+		assert!(!verify_receipt_proof(receipts_root, 2, &receipts[1], proof));
+	}
+
+	#[test]
+	fn state_proof_returns_stored_value() {
+		let address = H160::repeat_byte(0x19);
+		let key = evm_storage_key(address, H256::repeat_byte(1));
+		let mut db = sp_trie::MemoryDB::<BlakeTwo256>::default();
+		let mut root = H256::default();
+		{
+			let mut trie = TrieDBMutBuilderV1::<BlakeTwo256>::new(&mut db, &mut root).build();
+			trie.insert(&key, &[7u8; 40]).unwrap();
+			trie.insert(&system_account_key(address), &[8u8; 2])
+				.unwrap();
+		}
+		let proof: Vec<Vec<u8>> = db.drain().into_values().map(|(node, _)| node).collect();
+
+		assert_eq!(
+			verify_state_proof(root, proof.clone(), &key).unwrap(),
+			Some(vec![7u8; 40])
+		);
+		assert_eq!(
+			verify_state_proof(root, proof.clone(), &evm_code_key(address)).unwrap(),
+			None
+		);
+		assert!(verify_state_proof(H256::repeat_byte(2), proof, &key).is_err());
+	}
+}
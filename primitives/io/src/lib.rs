@@ -3,14 +3,41 @@
 #![cfg_attr(not(feature = "std"), feature(alloc_error_handler))]
 #[cfg(feature = "std")]
 use aes_gcm::{
-	aead::{AeadMut, Payload},
+	aead::{AeadCore, AeadMut, OsRng, Payload},
 	Aes256Gcm, KeyInit, Nonce,
 };
 use scale_codec::{Decode, Encode};
 #[cfg(feature = "std")]
-use secp256k1::{ecdh::SharedSecret, PublicKey, SecretKey};
+use secp256k1::{ecdh::SharedSecret, Message, PublicKey, SecretKey};
+use sp_core::crypto::KeyTypeId;
+#[cfg(feature = "std")]
+use sp_externalities::{decl_extension, Externalities, ExternalitiesExt};
 use sp_runtime_interface::runtime_interface;
 use sp_std::vec::Vec;
+
+#[cfg(feature = "std")]
+pub mod state_key;
+
+pub use tp_envelope as envelope;
+
+pub use envelope::Envelope;
+
+/// Key type of the node key confidential transactions are encrypted to.
+pub const ENCLAVE_KEY_TYPE: KeyTypeId = KeyTypeId(*b"tenc");
+
+/// Key type of the master key the state encryption subkeys are derived from.
+pub const STATE_KEY_TYPE: KeyTypeId = KeyTypeId(*b"tsta");
+
+/// Application crypto of the enclave key, a secp256k1 key kept in the node keystore.
+pub mod enclave {
+	sp_application_crypto::app_crypto!(ecdsa, crate::ENCLAVE_KEY_TYPE);
+}
+
+/// Application crypto of the state master key. Only the seed of the pair is used.
+pub mod state_master {
+	sp_application_crypto::app_crypto!(ecdsa, crate::STATE_KEY_TYPE);
+}
+
 /// Error aes gcm
 #[derive(Debug, Encode, Decode)]
 pub enum AesError {
@@ -27,6 +54,9 @@ pub enum AesError {
 	BadDecrypted,
 
 	BadKeyLength,
+
+	/// The state master key selected on chain is not installed
+	UnknownStateKey,
 }
 
 /// Error verifying ECDSA signature
@@ -42,88 +72,376 @@ pub enum EcdsaVerifyError {
 	BadSharedSecret,
 }
 
-#[runtime_interface]
-pub trait Crypto {
-	/// encrypted
-	fn encrypted(msg: &[u8], aad: &[u8; 32], pubkey: &[u8; 64]) -> Result<Vec<u8>, AesError> {
-		let shared_key = shared_secret(pubkey).map_err(|_| AesError::BadSharedKey)?;
-		Aes256Gcm::new_from_slice(&shared_key)
-			.map_err(|_| AesError::BadSharedKeyLength)?
-			.encrypt(Nonce::from_slice(&aad[20..]), Payload { aad, msg })
-			.map_err(|_| AesError::BadEncrypted)
-	}
-
-	/// decrypted
-	fn decrypted(msg: &[u8], aad: &[u8; 32], pubkey: &[u8; 64]) -> Result<Vec<u8>, AesError> {
-		let shared_key = shared_secret(pubkey).map_err(|_| AesError::BadSharedKey)?;
-		Aes256Gcm::new_from_slice(&shared_key)
-			.map_err(|_| AesError::BadSharedKeyLength)?
-			.decrypt(Nonce::from_slice(&aad[20..]), Payload { aad, msg })
-			.map_err(|_| AesError::BadDecrypted)
+/// The secret half of the enclave key, loaded from the node keystore.
+#[cfg(feature = "std")]
+#[derive(Clone)]
+pub struct EnclaveKey(SecretKey);
+
+#[cfg(feature = "std")]
+impl EnclaveKey {
+	/// Build the key from the 32-byte seed of a keystore ecdsa pair.
+	pub fn from_seed(seed: &[u8; 32]) -> Option<Self> {
+		SecretKey::from_slice(seed).ok().map(Self)
 	}
 
-	/// ecdh
-	fn shared_secret(pubkey: &[u8; 64]) -> Result<[u8; 32], EcdsaVerifyError> {
+	/// Uncompressed public key without the `0x04` tag, as wallets encrypt to it.
+	pub fn public(&self) -> [u8; 64] {
+		let mut public = [0u8; 64];
+		public.copy_from_slice(
+			&PublicKey::from_secret_key_global(&self.0).serialize_uncompressed()[1..],
+		);
+		public
+	}
+
+	/// Recoverable signature `r || s || v` of the 32-byte `message`. The signature nonce is
+	/// derived from the key and the message (RFC 6979), so the output is deterministic.
+	pub fn sign(&self, message: &[u8; 32]) -> [u8; 65] {
+		let (recovery_id, compact) = secp256k1::SECP256K1
+			.sign_ecdsa_recoverable(
+				&Message::from_slice(message).expect("32 bytes; qed"),
+				&self.0,
+			)
+			.serialize_compact();
+		let mut signature = [0u8; 65];
+		signature[..64].copy_from_slice(&compact);
+		signature[64] = recovery_id.to_i32() as u8;
+		signature
+	}
+
+	/// ECDH between the enclave key and the sender's public key.
+	pub fn shared_secret(&self, pubkey: &[u8; 64]) -> Result<[u8; 32], EcdsaVerifyError> {
 		let mut tagged_full = [0u8; 65];
 		tagged_full[0] = 0x04;
 		tagged_full[1..].copy_from_slice(pubkey);
-		let sk = <SecretKey as core::str::FromStr>::from_str(
-			"57f0148f94d13095cfda539d0da0d1541304b678d8b36e243980aab4e1b7cead",
-		)
-		.map_err(|_| EcdsaVerifyError::BadSecretKey)?;
 		Ok(SharedSecret::new(
 			&PublicKey::from_slice(&tagged_full).map_err(|_| EcdsaVerifyError::BadPublicKey)?,
-			&sk,
+			&self.0,
 		)
 		.secret_bytes())
 	}
 
-	// encrypt
+	/// Encrypt `msg` for the sender identified by `pubkey` under a fresh random nonce,
+	/// returning an [`Envelope`].
+	pub fn encrypt(
+		&self,
+		msg: &[u8],
+		aad: &[u8; 32],
+		pubkey: &[u8; 64],
+	) -> Result<Vec<u8>, AesError> {
+		let shared_key = self
+			.shared_secret(pubkey)
+			.map_err(|_| AesError::BadSharedKey)?;
+		let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+		envelope::seal(&shared_key, nonce.into(), msg, aad)
+			.map(|envelope| envelope.to_bytes())
+			.ok_or(AesError::BadEncrypted)
+	}
+
+	/// Decrypt `msg` sent by the sender identified by `pubkey`. Accepts both envelopes and
+	/// legacy headerless ciphertexts.
+	pub fn decrypt(
+		&self,
+		msg: &[u8],
+		aad: &[u8; 32],
+		pubkey: &[u8; 64],
+	) -> Result<Vec<u8>, AesError> {
+		let shared_key = self
+			.shared_secret(pubkey)
+			.map_err(|_| AesError::BadSharedKey)?;
+		if let Some(plaintext) = envelope::open(&shared_key, msg, aad) {
+			return Ok(plaintext);
+		}
+		// A legacy ciphertext may start with the envelope version byte by chance, so it is
+		// tried whenever the envelope does not authenticate.
+		self.cipher(pubkey)?
+			.decrypt(Nonce::from_slice(&aad[20..]), Payload { aad, msg })
+			.map_err(|_| AesError::BadDecrypted)
+	}
+
+	fn cipher(&self, pubkey: &[u8; 64]) -> Result<Aes256Gcm, AesError> {
+		let shared_key = self
+			.shared_secret(pubkey)
+			.map_err(|_| AesError::BadSharedKey)?;
+		Aes256Gcm::new_from_slice(&shared_key).map_err(|_| AesError::BadSharedKeyLength)
+	}
+}
+
+#[cfg(feature = "std")]
+decl_extension! {
+	/// The enclave key made available to the confidential transaction host functions.
+	pub struct EnclaveKeyExt(EnclaveKey);
+}
+
+#[cfg(feature = "std")]
+decl_extension! {
+	/// The state master key selected on chain for the call, the legacy global key when `None`.
+	///
+	/// The state machine encrypts the storage root of the call under it.
+	pub struct StateKeyExt(Option<state_key::KeyId>);
+}
+
+#[cfg(feature = "std")]
+decl_extension! {
+	/// The top storage writes made by the innermost open storage transaction, ordered by key,
+	/// where a deleted key has no value.
+	///
+	/// The state machine answers this extension from its overlay on every request instead of
+	/// registering it, so the host functions always see the current transaction.
+	pub struct TransactionWriteSet(Vec<(Vec<u8>, Option<Vec<u8>>)>);
+}
+
+#[cfg(feature = "std")]
+impl TransactionWriteSet {
+	/// Keccak-256 digest of the writes made under `prefix`.
+	///
+	/// The digest is taken over the SCALE encoded list of `(key, value)` pairs.
+	pub fn digest(&self, prefix: &[u8]) -> [u8; 32] {
+		let writes = self
+			.0
+			.iter()
+			.filter(|(key, _)| key.starts_with(prefix))
+			.map(|(key, value)| (key.as_slice(), value.as_deref()))
+			.collect::<Vec<_>>();
+		sp_core::hashing::keccak_256(&writes.encode())
+	}
+}
+
+#[runtime_interface]
+pub trait Crypto {
+	/// Encrypt `msg` into an [`Envelope`]. The nonce is random, so the output must not
+	/// feed consensus-critical state.
+	fn encrypted(
+		&mut self,
+		msg: &[u8],
+		aad: &[u8; 32],
+		pubkey: &[u8; 64],
+	) -> Result<Vec<u8>, AesError> {
+		self.extension::<EnclaveKeyExt>()
+			.ok_or(AesError::BadSharedKey)?
+			.encrypt(msg, aad, pubkey)
+	}
+
+	/// Decrypt an [`Envelope`] or a legacy headerless ciphertext.
+	fn decrypted(
+		&mut self,
+		msg: &[u8],
+		aad: &[u8; 32],
+		pubkey: &[u8; 64],
+	) -> Result<Vec<u8>, AesError> {
+		self.extension::<EnclaveKeyExt>()
+			.ok_or(AesError::BadSharedKey)?
+			.decrypt(msg, aad, pubkey)
+	}
+
+	/// ecdh
+	fn shared_secret(&mut self, pubkey: &[u8; 64]) -> Result<[u8; 32], EcdsaVerifyError> {
+		self.extension::<EnclaveKeyExt>()
+			.ok_or(EcdsaVerifyError::BadSecretKey)?
+			.shared_secret(pubkey)
+	}
+
+	/// Public key of the enclave key, if the node has one.
+	fn enclave_public_key(&mut self) -> Option<Vec<u8>> {
+		self.extension::<EnclaveKeyExt>()
+			.map(|key| key.public().to_vec())
+	}
+
+	/// Encrypt a state value stored under `nonce`, the storage key, with the current state
+	/// master key.
 	fn encrypt(msg: &[u8], nonce: &[u8]) -> Result<Vec<u8>, AesError> {
-		Aes256Gcm::new_from_slice(&sp_core::hashing::keccak_256(b"12"))
-			.map_err(|_| AesError::BadKeyLength)?
-			.encrypt(
-				Nonce::from_slice(&sp_core::hashing::keccak_256(nonce)[20..]),
-				msg,
-			)
-			.map_err(|_| AesError::BadEncrypted)
+		state_key::encrypt(nonce, msg)
 	}
 
-	// decrypt
+	/// Decrypt a state value stored under `nonce`, the storage key, under whichever master key
+	/// it names. Values still encrypted under the legacy global key are accepted.
 	fn decrypt(msg: &[u8], nonce: &[u8]) -> Result<Vec<u8>, AesError> {
-		Aes256Gcm::new_from_slice(&sp_core::hashing::keccak_256(b"12"))
-			.map_err(|_| AesError::BadKeyLength)?
-			.decrypt(
-				Nonce::from_slice(&sp_core::hashing::keccak_256(nonce)[20..]),
-				msg,
-			)
-			.map_err(|_| AesError::BadDecrypted)
+		state_key::decrypt(nonce, msg)
+	}
+}
+
+#[runtime_interface]
+pub trait State {
+	/// Keccak-256 digest of the storage writes made under `prefix` by the innermost open
+	/// storage transaction.
+	///
+	/// Returns `None` if the externalities do not track the write set.
+	fn write_set_digest(&mut self, prefix: &[u8]) -> Option<[u8; 32]> {
+		self.extension::<TransactionWriteSet>()
+			.map(|write_set| write_set.digest(prefix))
+	}
+
+	/// Select the state master key new state values are encrypted with for the rest of the
+	/// call, the legacy global key when `None`. Returns `false`, keeping the previous key, if
+	/// the node does not hold it.
+	fn set_state_key(&mut self, id: Option<u32>) -> bool {
+		if id.map_or(false, |id| !state_key::is_installed(id)) {
+			return false;
+		}
+		match self.extension::<StateKeyExt>() {
+			Some(current) => current.0 = id,
+			None => return self.register_extension(StateKeyExt(id)).is_ok(),
+		}
+		true
 	}
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use sp_io::hashing;
+	use sp_io::{hashing, TestExternalities};
+
+	fn enclave_ext() -> TestExternalities {
+		let key = EnclaveKey::from_seed(&array_bytes::hex2array_unchecked(
+			"57f0148f94d13095cfda539d0da0d1541304b678d8b36e243980aab4e1b7cead",
+		))
+		.unwrap();
+		let mut ext = TestExternalities::default();
+		ext.register_extension(EnclaveKeyExt::from(key));
+		ext
+	}
 
 	#[test]
 	fn test_aes_gcm() {
 		let pk = array_bytes::hex2array_unchecked("8db55b05db86c0b1786ca49f095d76344c9e6056b2f02701a7e7f3c20aabfd913ebbe148dd17c56551a52952371071a6c604b3f3abe8f2c8fa742158ea6dd7d4");
 
-		assert!(crypto::shared_secret(&pk).is_ok());
+		enclave_ext().execute_with(|| {
+			assert!(crypto::shared_secret(&pk).is_ok());
+
+			let msg = b"hello world";
+			let aad = hashing::keccak_256(msg);
+			let ciphertext = match crypto::encrypted(msg, &aad, &pk) {
+				Ok(cipher) => cipher,
+				Err(_) => Default::default(),
+			};
+
+			let plaintext = match crypto::decrypted(&ciphertext, &aad, &pk) {
+				Ok(plain) => plain,
+				Err(_) => Default::default(),
+			};
+
+			assert_eq!(&plaintext, msg);
+		});
+	}
+
+	#[test]
+	fn envelope_nonces_are_fresh() {
+		let pk = array_bytes::hex2array_unchecked("8db55b05db86c0b1786ca49f095d76344c9e6056b2f02701a7e7f3c20aabfd913ebbe148dd17c56551a52952371071a6c604b3f3abe8f2c8fa742158ea6dd7d4");
+
+		enclave_ext().execute_with(|| {
+			let msg = b"hello world";
+			let aad = hashing::keccak_256(msg);
+			let first = crypto::encrypted(msg, &aad, &pk).unwrap();
+			let second = crypto::encrypted(msg, &aad, &pk).unwrap();
+			assert_ne!(first, second);
+
+			let envelope = Envelope::from_bytes(&first).unwrap();
+			assert_eq!(envelope.version, envelope::ENVELOPE_VERSION);
+			assert_eq!(envelope.ciphertext.len(), msg.len());
+			assert_eq!(envelope.to_bytes(), first);
+
+			assert_eq!(crypto::decrypted(&first, &aad, &pk).unwrap(), msg);
+			assert_eq!(crypto::decrypted(&second, &aad, &pk).unwrap(), msg);
+		});
+	}
+
+	#[test]
+	fn legacy_ciphertext_decrypts() {
+		let pk = array_bytes::hex2array_unchecked("8db55b05db86c0b1786ca49f095d76344c9e6056b2f02701a7e7f3c20aabfd913ebbe148dd17c56551a52952371071a6c604b3f3abe8f2c8fa742158ea6dd7d4");
+		let key = EnclaveKey::from_seed(&array_bytes::hex2array_unchecked(
+			"57f0148f94d13095cfda539d0da0d1541304b678d8b36e243980aab4e1b7cead",
+		))
+		.unwrap();
 
 		let msg = b"hello world";
 		let aad = hashing::keccak_256(msg);
-		let ciphertext = match crypto::encrypted(msg, &aad, &pk) {
-			Ok(cipher) => cipher,
-			Err(_) => Default::default(),
+		let legacy = key
+			.cipher(&pk)
+			.unwrap()
+			.encrypt(Nonce::from_slice(&aad[20..]), Payload { aad: &aad, msg })
+			.unwrap();
+		assert_eq!(legacy.len(), msg.len() + envelope::TAG_LEN);
+		assert_eq!(key.decrypt(&legacy, &aad, &pk).unwrap(), msg);
+	}
+
+	#[test]
+	fn state_values_use_per_contract_keys() {
+		let contract = |address: u8| {
+			[
+				hashing::twox_128(b"EVM").to_vec(),
+				hashing::twox_128(b"AccountStorages").to_vec(),
+				[address; 36].to_vec(),
+				[7u8; 48].to_vec(),
+			]
+			.concat()
 		};
+		let (first, second) = (contract(1), contract(2));
+		assert_eq!(state_key::key_domain(&first), &first[..68]);
+
+		let legacy = crypto::encrypt(b"value", &first).unwrap();
+		let (old, new) = ([9u8; 32], [10u8; 32]);
+		let (old_id, new_id) = (state_key::key_id(&old), state_key::key_id(&new));
+		state_key::install_master_keys([old]);
+		let value = state_key::with_current_key_id(Some(old_id), || {
+			crypto::encrypt(b"value", &first).unwrap()
+		});
+		assert_ne!(value, legacy);
+		assert_eq!(state_key::value_key_id(&value), Some(old_id));
+		assert_eq!(state_key::current_key_id(), None);
+		assert_eq!(crypto::decrypt(&value, &first).unwrap(), b"value");
+		assert_eq!(crypto::decrypt(&legacy, &first).unwrap(), b"value");
+		state_key::with_current_key_id(Some(old_id), || {
+			assert_ne!(value, crypto::encrypt(b"value", &second).unwrap());
+		});
+
+		// After a rotation values under the old key stay readable until re-encrypted.
+		state_key::install_master_keys([old, new]);
+		let rotated = state_key::with_current_key_id(Some(new_id), || {
+			crypto::encrypt(b"value", &first).unwrap()
+		});
+		assert_eq!(state_key::value_key_id(&rotated), Some(new_id));
+		assert_eq!(crypto::decrypt(&value, &first).unwrap(), b"value");
+		assert_eq!(crypto::decrypt(&rotated, &first).unwrap(), b"value");
+
+		// Nothing is encrypted under a key the node does not hold.
+		state_key::install_master_keys([old]);
+		assert!(matches!(
+			state_key::with_current_key_id(Some(new_id), || crypto::encrypt(b"value", &first)),
+			Err(AesError::UnknownStateKey)
+		));
+		state_key::install_master_keys([]);
+	}
 
-		let plaintext = match crypto::decrypted(&ciphertext, &aad, &pk) {
-			Ok(plain) => plain,
-			Err(_) => Default::default(),
+	#[test]
+	fn state_key_is_selected_per_call() {
+		let selected = || {
+			sp_externalities::with_externalities(|ext| {
+				ext.extension::<StateKeyExt>().map(|selected| selected.0)
+			})
+			.flatten()
 		};
+		let mut ext = TestExternalities::default();
+		ext.execute_with(|| {
+			assert_eq!(selected(), None);
+			assert!(state::set_state_key(None));
+			assert_eq!(selected(), Some(None));
+			// A key the node does not hold cannot be selected.
+			assert!(!state::set_state_key(Some(state_key::key_id(&[13u8; 32]))));
+			assert_eq!(selected(), Some(None));
+		});
+		// Every call selects its own key.
+		TestExternalities::default().execute_with(|| assert_eq!(selected(), None));
+	}
+
+	#[test]
+	fn missing_enclave_key_fails() {
+		let pk = array_bytes::hex2array_unchecked("8db55b05db86c0b1786ca49f095d76344c9e6056b2f02701a7e7f3c20aabfd913ebbe148dd17c56551a52952371071a6c604b3f3abe8f2c8fa742158ea6dd7d4");
 
-		assert_eq!(&plaintext, msg);
+		TestExternalities::default().execute_with(|| {
+			assert!(crypto::shared_secret(&pk).is_err());
+			assert!(crypto::enclave_public_key().is_none());
+		});
+		enclave_ext().execute_with(|| {
+			assert_eq!(crypto::enclave_public_key().map(|key| key.len()), Some(64));
+		});
 	}
 }
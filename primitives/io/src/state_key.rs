@@ -0,0 +1,230 @@
+//! Key hierarchy of the encrypted state.
+//!
+//! Storage values are encrypted under subkeys derived by HKDF-SHA256 from a node master key,
+//! one per contract for EVM account storage and one per storage prefix otherwise. Every
+//! master key is identified by a [`KeyId`] which is embedded in the values it encrypts, so
+//! that the master key can be rotated: new values are written under the current key while
+//! values written under any installed key, or under the legacy global key, stay readable
+//! until they are re-encrypted.
+//!
+//! Values are sealed in an [`Envelope`](crate::Envelope) carrying their nonce, with the
+//! storage key as associated data so that a value cannot be moved under another key.
+//!
+//! The trie backend reads state outside of any externalities, so the master keys are installed
+//! process-wide by the node at startup. The current one is chosen on chain: the runtime
+//! selects it at the start of every block through [`set_state_key`](crate::state::set_state_key),
+//! which records it in the [`StateKeyExt`](crate::StateKeyExt) of that call, and the state
+//! machine encrypts the storage root of the call under it with [`with_current_key_id`].
+
+use std::{cell::Cell, collections::BTreeMap, sync::RwLock};
+
+use aes_gcm::{aead::Aead, Aes256Gcm, KeyInit, Nonce};
+use hkdf::Hkdf;
+use sha2::Sha256;
+use sp_core::hashing::{blake2_256, keccak_256, twox_128};
+
+use crate::{
+	envelope::{self, NONCE_LEN},
+	AesError,
+};
+
+/// Identifier of a state master key.
+pub type KeyId = u32;
+
+/// First byte of a value carrying the id of the master key it is encrypted under.
+pub const KEY_ID_TAG: u8 = 0xe5;
+/// Length of the key id header of an encrypted value.
+const HEADER_LEN: usize = 1 + 4;
+
+/// HKDF salt of the state subkeys.
+const SUBKEY_SALT: &[u8] = b"tenet-state-subkey";
+/// HKDF salt of the nonces of state values.
+const NONCE_SALT: &[u8] = b"tenet-state-nonce";
+/// Length of the storage prefix (`twox128(pallet) ++ twox128(item)`).
+const PREFIX_LEN: usize = 32;
+/// Length of the prefix of an `AccountStorages` key identifying one contract
+/// (`prefix ++ blake2_128(address) ++ address`).
+const CONTRACT_PREFIX_LEN: usize = PREFIX_LEN + 16 + 20;
+
+static MASTER_KEYS: RwLock<BTreeMap<KeyId, [u8; 32]>> = RwLock::new(BTreeMap::new());
+
+thread_local! {
+	/// The master key selected for the storage root being computed on this thread.
+	static CURRENT_KEY_ID: Cell<Option<KeyId>> = const { Cell::new(None) };
+}
+
+/// The id of `master_key`.
+pub fn key_id(master_key: &[u8; 32]) -> KeyId {
+	let hash = blake2_256(&[b"tenet-state-key-id".as_slice(), master_key].concat());
+	KeyId::from_be_bytes([hash[0], hash[1], hash[2], hash[3]])
+}
+
+/// Install the master keys values may be encrypted under.
+pub fn install_master_keys(master_keys: impl IntoIterator<Item = [u8; 32]>) {
+	*MASTER_KEYS.write().expect("state key lock poisoned") = master_keys
+		.into_iter()
+		.map(|master_key| (key_id(&master_key), master_key))
+		.collect();
+}
+
+/// Whether the master key `id` is installed.
+pub fn is_installed(id: KeyId) -> bool {
+	MASTER_KEYS
+		.read()
+		.expect("state key lock poisoned")
+		.contains_key(&id)
+}
+
+/// Run `f` with new values encrypted under the master key `id`, the legacy global key when
+/// `None`.
+pub fn with_current_key_id<R>(id: Option<KeyId>, f: impl FnOnce() -> R) -> R {
+	struct Restore(Option<KeyId>);
+	impl Drop for Restore {
+		fn drop(&mut self) {
+			CURRENT_KEY_ID.with(|current| current.set(self.0));
+		}
+	}
+	let _restore = Restore(CURRENT_KEY_ID.with(|current| current.replace(id)));
+	f()
+}
+
+/// The id of the master key new values are encrypted with.
+pub fn current_key_id() -> Option<KeyId> {
+	CURRENT_KEY_ID.with(Cell::get)
+}
+
+/// The global key all state was encrypted under before the key hierarchy.
+pub fn legacy_key() -> [u8; 32] {
+	keccak_256(b"12")
+}
+
+/// The part of `key` that selects its subkey: the contract for EVM account storage, the
+/// storage prefix otherwise.
+pub fn key_domain(key: &[u8]) -> &[u8] {
+	let account_storages = [twox_128(b"EVM"), twox_128(b"AccountStorages")].concat();
+	if key.len() >= CONTRACT_PREFIX_LEN && key.starts_with(&account_storages) {
+		&key[..CONTRACT_PREFIX_LEN]
+	} else {
+		&key[..key.len().min(PREFIX_LEN)]
+	}
+}
+
+/// Derive the subkey of `domain` from `master_key`.
+pub fn derive_subkey(master_key: &[u8; 32], domain: &[u8]) -> [u8; 32] {
+	let mut subkey = [0u8; 32];
+	Hkdf::<Sha256>::new(Some(SUBKEY_SALT), master_key)
+		.expand(domain, &mut subkey)
+		.expect("32 bytes is a valid HKDF-SHA256 output length; qed");
+	subkey
+}
+
+/// The id of the master key `value` is encrypted under, if it carries one.
+pub fn value_key_id(value: &[u8]) -> Option<KeyId> {
+	match value {
+		[KEY_ID_TAG, a, b, c, d, ..] => Some(KeyId::from_be_bytes([*a, *b, *c, *d])),
+		_ => None,
+	}
+}
+
+/// Encrypt the value stored under `key` with the current master key. Fails if the current
+/// key is not installed.
+pub fn encrypt(key: &[u8], value: &[u8]) -> Result<Vec<u8>, AesError> {
+	let Some(id) = current_key_id() else {
+		return seal(&legacy_key(), key, value);
+	};
+	let keys = MASTER_KEYS.read().expect("state key lock poisoned");
+	let master_key = keys.get(&id).ok_or(AesError::UnknownStateKey)?;
+	let sealed = seal(&derive_subkey(master_key, key_domain(key)), key, value)?;
+	let mut encrypted = Vec::with_capacity(HEADER_LEN + sealed.len());
+	encrypted.push(KEY_ID_TAG);
+	encrypted.extend_from_slice(&id.to_be_bytes());
+	encrypted.extend(sealed);
+	Ok(encrypted)
+}
+
+/// Decrypt the value stored under `key`, whichever installed master key it was encrypted
+/// under.
+pub fn decrypt(key: &[u8], value: &[u8]) -> Result<Vec<u8>, AesError> {
+	let keys = MASTER_KEYS.read().expect("state key lock poisoned");
+	let domain = key_domain(key);
+	if let Some(master_key) = value_key_id(value).and_then(|id| keys.get(&id)) {
+		let subkey = derive_subkey(master_key, domain);
+		if let Ok(plaintext) = open(&subkey, key, &value[HEADER_LEN..]) {
+			return Ok(plaintext);
+		}
+	}
+	// Headerless values were written under the legacy key, or under a master key before key
+	// ids were embedded.
+	core::iter::once(legacy_key())
+		.chain(
+			keys.values()
+				.map(|master_key| derive_subkey(master_key, domain)),
+		)
+		.find_map(|cipher_key| open(&cipher_key, key, value).ok())
+		.ok_or(AesError::BadDecrypted)
+}
+
+/// State values are encrypted deterministically so that every node computes the same root.
+/// The nonce is derived by HKDF from the cipher key, the storage key and the value, so every
+/// distinct value written under a key gets a fresh nonce and a nonce is only ever repeated for
+/// the very same plaintext.
+fn nonce(cipher_key: &[u8; 32], key: &[u8], value: &[u8]) -> [u8; NONCE_LEN] {
+	let mut nonce = [0u8; NONCE_LEN];
+	Hkdf::<Sha256>::new(Some(NONCE_SALT), cipher_key)
+		.expand_multi_info(&[key, value], &mut nonce)
+		.expect("12 bytes is a valid HKDF-SHA256 output length; qed");
+	nonce
+}
+
+/// The nonce of values encrypted before nonces were carried in an envelope, derived from the
+/// storage key alone.
+fn legacy_nonce(key: &[u8]) -> [u8; NONCE_LEN] {
+	let mut nonce = [0u8; NONCE_LEN];
+	nonce.copy_from_slice(&keccak_256(key)[20..]);
+	nonce
+}
+
+/// Seal `value` into an [`Envelope`](crate::Envelope) bound to its storage key.
+fn seal(cipher_key: &[u8; 32], key: &[u8], value: &[u8]) -> Result<Vec<u8>, AesError> {
+	envelope::seal(cipher_key, nonce(cipher_key, key, value), value, key)
+		.map(|envelope| envelope.to_bytes())
+		.ok_or(AesError::BadEncrypted)
+}
+
+fn open(cipher_key: &[u8; 32], key: &[u8], value: &[u8]) -> Result<Vec<u8>, AesError> {
+	if let Some(plaintext) = envelope::open(cipher_key, value, key) {
+		return Ok(plaintext);
+	}
+	// A legacy value may start with the envelope version byte by chance, so it is tried
+	// whenever the envelope does not authenticate.
+	Aes256Gcm::new_from_slice(cipher_key)
+		.map_err(|_| AesError::BadKeyLength)?
+		.decrypt(Nonce::from_slice(&legacy_nonce(key)), value)
+		.map_err(|_| AesError::BadDecrypted)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Envelope;
+
+	#[test]
+	fn values_get_a_fresh_nonce_per_value() {
+		let (cipher_key, key) = ([7u8; 32], b"storage key");
+		let first = seal(&cipher_key, key, b"first").unwrap();
+		let second = seal(&cipher_key, key, b"second").unwrap();
+		let nonce = |value: &[u8]| Envelope::from_bytes(value).unwrap().nonce;
+		assert_ne!(nonce(&first), nonce(&second));
+		// Encryption stays deterministic, so every node computes the same root.
+		assert_eq!(seal(&cipher_key, key, b"first").unwrap(), first);
+		assert_eq!(open(&cipher_key, key, &second).unwrap(), b"second");
+		assert!(open(&cipher_key, b"other key", &second).is_err());
+
+		// Values sealed under the nonce derived from the storage key alone still decrypt.
+		let legacy = Aes256Gcm::new_from_slice(&cipher_key)
+			.unwrap()
+			.encrypt(Nonce::from_slice(&legacy_nonce(key)), &b"legacy"[..])
+			.unwrap();
+		assert_eq!(open(&cipher_key, key, &legacy).unwrap(), b"legacy");
+	}
+}
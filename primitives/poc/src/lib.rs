@@ -1,39 +1,165 @@
 #![cfg_attr(not(feature = "std"), no_std)]
-use rlp::{Encodable, RlpEncodable, RlpStream};
-use secp256k1::{ecdsa::Signature, Message, Secp256k1, SecretKey};
-use sp_core::H256;
+use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpDecodable, RlpEncodable, RlpStream};
+use secp256k1::{ecdsa::Signature, Message, PublicKey, Secp256k1, SecretKey};
+use sp_core::{H160, H256};
 use sp_std::{collections::vec_deque::VecDeque, vec::Vec};
+/// Version of the PoC encoding.
+///
+/// Version 0 PoCs are the two item list `[io_hash_list, signature]` with a 64-byte signature
+/// whose signer cannot be recovered. Version 1 PoCs are the three item list
+/// `[version, io_hash_list, signature]` with a 65-byte recoverable signature `r || s || v`.
+pub const POC_VERSION: u8 = 1;
+
 #[derive(Clone)]
 pub struct PoC {
 	io_hash_list: Vec<IOHash>,
 	pub sign: Signature,
+	/// Recovery id of `sign`, absent for version 0 PoCs.
+	recovery_id: Option<u8>,
+}
+impl PoC {
+	/// A PoC over `io_hash_list` from the recoverable signature `r || s || v` of its root.
+	pub fn from_signature(io_hash_list: Vec<IOHash>, signature: &[u8; 65]) -> Option<Self> {
+		if signature[64] > 3 {
+			return None;
+		}
+		Some(Self {
+			io_hash_list,
+			sign: Signature::from_compact(&signature[..64]).ok()?,
+			recovery_id: Some(signature[64]),
+		})
+	}
+
+	pub fn io_hash_list(&self) -> &[IOHash] {
+		&self.io_hash_list
+	}
+
+	/// Encoding version of the PoC.
+	pub fn version(&self) -> u8 {
+		if self.recovery_id.is_some() {
+			POC_VERSION
+		} else {
+			0
+		}
+	}
+
+	/// The merkle root over all input and output hashes, which is what gets signed.
+	pub fn root(&self) -> H256 {
+		generate_root(&self.io_hash_list)
+	}
+
+	/// The Ethereum address of the key that signed the PoC, recovered from the signature.
+	/// `None` for version 0 PoCs.
+	pub fn signer(&self) -> Option<H160> {
+		let mut signature = [0u8; 65];
+		signature[..64].copy_from_slice(&self.sign.serialize_compact());
+		signature[64] = self.recovery_id?;
+		let public_key =
+			sp_io::crypto::secp256k1_ecdsa_recover(&signature, self.root().as_fixed_bytes())
+				.ok()?;
+		Some(H160::from_slice(
+			&sp_io::hashing::keccak_256(&public_key)[12..],
+		))
+	}
+
+	/// Check the signature over the root against a serialized secp256k1 public key.
+	pub fn verify(&self, public_key: &[u8]) -> bool {
+		let Ok(public_key) = PublicKey::from_slice(public_key) else {
+			return false;
+		};
+		Secp256k1::verification_only()
+			.verify_ecdsa(
+				&Message::from_digest(*self.root().as_fixed_bytes()),
+				&self.sign,
+				&public_key,
+			)
+			.is_ok()
+	}
 }
+
 impl Encodable for PoC {
 	fn rlp_append(&self, s: &mut RlpStream) {
-		s.append_list(&self.io_hash_list);
-		// s.append(&self.sign.to_string().as_bytes());
+		let signature = self.sign.serialize_compact().to_vec();
+		match self.recovery_id {
+			Some(recovery_id) => {
+				s.begin_list(3);
+				s.append(&POC_VERSION);
+				s.append_list(&self.io_hash_list);
+				s.append(&[signature, sp_std::vec![recovery_id]].concat());
+			}
+			None => {
+				s.begin_list(2);
+				s.append_list(&self.io_hash_list);
+				s.append(&signature);
+			}
+		}
+	}
+}
+
+impl Decodable for PoC {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		match rlp.item_count()? {
+			2 => {
+				let sign: Vec<u8> = rlp.val_at(1)?;
+				Ok(Self {
+					io_hash_list: rlp.list_at(0)?,
+					sign: Signature::from_compact(&sign)
+						.map_err(|_| DecoderError::Custom("invalid poc signature"))?,
+					recovery_id: None,
+				})
+			}
+			3 => {
+				if rlp.val_at::<u8>(0)? != POC_VERSION {
+					return Err(DecoderError::Custom("unknown poc version"));
+				}
+				let sign: Vec<u8> = rlp.val_at(2)?;
+				let sign: [u8; 65] = sign
+					.try_into()
+					.map_err(|_| DecoderError::Custom("invalid poc signature"))?;
+				Self::from_signature(rlp.list_at(1)?, &sign)
+					.ok_or(DecoderError::Custom("invalid poc signature"))
+			}
+			_ => Err(DecoderError::RlpIncorrectListLen),
+		}
 	}
 }
 
-#[derive(Clone, RlpEncodable)]
+#[derive(Clone, RlpEncodable, RlpDecodable)]
 pub struct IOHash {
 	pub input_hash: H256,
 	pub output_hash: H256,
 }
 
+/// RLP encoding of an unsigned PoC, the list of input and output hashes its root is taken
+/// over.
+pub fn encode_unsigned(io_list: &[IOHash]) -> Vec<u8> {
+	rlp::encode_list::<IOHash, _>(io_list).to_vec()
+}
+
+/// Decode an unsigned PoC encoded by [`encode_unsigned`].
+pub fn decode_unsigned(bytes: &[u8]) -> Result<Vec<IOHash>, DecoderError> {
+	Rlp::new(bytes).as_list()
+}
+
+/// Sign a version 1 PoC over `io_list` with `private_key`.
 pub fn generate_poc(private_key: H256, io_list: &Vec<IOHash>) -> PoC {
 	let root = generate_root(io_list);
+	let (recovery_id, signature) = Secp256k1::new()
+		.sign_ecdsa_recoverable(
+			&Message::from_digest(*root.as_fixed_bytes()),
+			&SecretKey::from_slice(private_key.as_bytes()).unwrap(),
+		)
+		.serialize_compact();
 
 	PoC {
 		io_hash_list: io_list.clone(),
-		sign: Secp256k1::new().sign_ecdsa(
-			&Message::from_digest(*root.as_fixed_bytes()),
-			&SecretKey::from_slice(private_key.as_bytes()).unwrap(),
-		),
+		sign: Signature::from_compact(&signature).unwrap(),
+		recovery_id: Some(recovery_id.to_i32() as u8),
 	}
 }
 
-fn generate_root(io_list: &Vec<IOHash>) -> H256 {
+/// The merkle root over the input and output hashes of `io_list`, which a PoC signs.
+pub fn generate_root(io_list: &Vec<IOHash>) -> H256 {
 	if io_list.is_empty() {
 		return H256::zero();
 	}
@@ -8,9 +8,13 @@ use sp_std::vec::Vec;
 // Substrate
 use ethereum_types::Bloom;
 use sp_core::{H160, H256, U256};
-use sp_runtime::{traits::Block as BlockT, Permill, RuntimeDebug};
+use sp_runtime::{traits::Block as BlockT, DispatchError, Permill, RuntimeDebug};
 use tp_ethereum::Log;
 
+mod state_diff;
+mod tracing;
+pub use self::{state_diff::*, tracing::*};
+
 #[derive(Clone, Eq, PartialEq, Default, RuntimeDebug, Encode, Decode, TypeInfo)]
 pub struct TransactionStatus {
 	pub transaction_hash: H256,
@@ -22,9 +26,40 @@ pub struct TransactionStatus {
 	pub logs_bloom: Bloom,
 }
 
+/// How a contract treats the confidentiality of the transactions calling it, declared by the
+/// contract when it is deployed.
+#[derive(Clone, Copy, Eq, PartialEq, Default, RuntimeDebug)]
+#[derive(Encode, Decode, TypeInfo)]
+pub enum ConfidentialityPolicy {
+	/// Plaintext and encrypted calls are both accepted.
+	#[default]
+	Public,
+	/// Calls must be encrypted.
+	ConfidentialInputs,
+	/// Calls must be encrypted and the state is only read through encrypted calls.
+	ConfidentialState,
+}
+
+impl ConfidentialityPolicy {
+	/// The policy of the ABI encoded `uint8` used by the policy precompile.
+	pub fn from_u8(value: u8) -> Option<Self> {
+		match value {
+			0 => Some(Self::Public),
+			1 => Some(Self::ConfidentialInputs),
+			2 => Some(Self::ConfidentialState),
+			_ => None,
+		}
+	}
+
+	/// Whether plaintext calls are accepted.
+	pub fn is_public(&self) -> bool {
+		*self == Self::Public
+	}
+}
+
 sp_api::decl_runtime_apis! {
 	/// API necessary for Ethereum-compatibility layer.
-	#[api_version(5)]
+	#[api_version(9)]
 	pub trait EthereumRuntimeRPCApi {
 		/// Returns runtime defined pallet_evm::ChainId.
 		fn chain_id() -> u64;
@@ -36,6 +71,17 @@ sp_api::decl_runtime_apis! {
 		fn account_code_at(address: H160) -> Vec<u8>;
 		fn has_account_public_key(address: H160) -> bool;
 		fn account_public(address: H160) -> Vec<u8>;
+		/// Returns the public key confidential transactions are encrypted to, if the node has
+		/// an enclave key.
+		fn enclave_public_key() -> Option<Vec<u8>>;
+		/// Returns the data of the non-perceptible log at `log_index` in the receipt of
+		/// `transaction_hash`, which the receipt only commits to.
+		fn non_perceptible_log_data(transaction_hash: H256, log_index: u32) -> Option<Vec<u8>>;
+		/// Returns whether `owner` currently grants the view key committed to by `commitment`
+		/// over its confidential transactions.
+		fn is_view_key_granted(owner: H160, commitment: H256) -> bool;
+		/// Returns the confidentiality policy declared by the contract at `address`.
+		fn confidentiality_policy(address: H160) -> ConfidentialityPolicy;
 		/// Returns the converted FindAuthor::find_author authority id.
 		fn author() -> H160;
 
@@ -142,6 +188,10 @@ sp_api::decl_runtime_apis! {
 		fn current_receipts() -> Option<Vec<tp_ethereum::Receipt>>;
 		/// Return the current transaction status.
 		fn current_transaction_statuses() -> Option<Vec<TransactionStatus>>;
+		/// Return the state diffs of the current block's transactions, if state diffs are
+		/// captured.
+		#[api_version(9)]
+		fn current_state_diffs() -> Vec<StateDiff>;
 		/// Return all the current data for a block in a single runtime call. Legacy.
 		#[changed_in(2)]
 		fn current_all() -> (
@@ -186,4 +236,30 @@ sp_api::decl_runtime_apis! {
 		#[changed_in(2)]
 		fn convert_transaction(transaction: tp_ethereum::TransactionV0) -> <Block as BlockT>::Extrinsic;
 	}
+
+	/// Re-executes Ethereum transactions with an EVM event listener attached.
+	pub trait EthereumTracingRuntimeApi {
+		/// Applies `extrinsics` on top of an initialized block until the Ethereum transaction
+		/// `transaction_hash`, and returns its trace.
+		fn trace_transaction(
+			extrinsics: Vec<<Block as BlockT>::Extrinsic>,
+			transaction_hash: H256,
+			tracer: TracerConfig,
+		) -> Result<TransactionTrace, DispatchError>;
+		/// Applies `extrinsics` on top of an initialized block and traces every Ethereum
+		/// transaction among them, in order.
+		fn trace_block(
+			extrinsics: Vec<<Block as BlockT>::Extrinsic>,
+			tracer: TracerConfig,
+		) -> Vec<(H256, Result<TransactionTrace, DispatchError>)>;
+		/// Traces a call on top of the state, without changing it.
+		fn trace_call(
+			from: H160,
+			to: Option<H160>,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			tracer: TracerConfig,
+		) -> Result<TransactionTrace, DispatchError>;
+	}
 }
@@ -0,0 +1,38 @@
+use scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use sp_core::{H160, H256, U256};
+use sp_runtime::RuntimeDebug;
+use sp_std::vec::Vec;
+
+/// A value before and after a transaction.
+#[derive(Clone, Eq, PartialEq, RuntimeDebug, Encode, Decode, TypeInfo)]
+pub struct Change<T> {
+	pub before: T,
+	pub after: T,
+}
+
+impl<T: PartialEq> Change<T> {
+	/// The change from `before` to `after`, `None` if the value did not change.
+	pub fn new(before: T, after: T) -> Option<Self> {
+		(before != after).then_some(Self { before, after })
+	}
+}
+
+/// How a transaction changed an account, unchanged fields being `None`.
+#[derive(Clone, Eq, PartialEq, RuntimeDebug, Encode, Decode, TypeInfo)]
+pub struct AccountDiff {
+	pub address: H160,
+	/// Also `None` for accounts with a registered public key, whose balance is confidential.
+	pub balance: Option<Change<U256>>,
+	pub nonce: Option<Change<U256>>,
+	pub code: Option<Change<Vec<u8>>>,
+	/// The changed slots only.
+	pub storage: Vec<(H256, Change<H256>)>,
+}
+
+/// The accounts an Ethereum transaction changed, captured while the block executes it.
+#[derive(Clone, Eq, PartialEq, RuntimeDebug, Encode, Decode, TypeInfo)]
+pub struct StateDiff {
+	pub transaction_hash: H256,
+	pub accounts: Vec<AccountDiff>,
+}
@@ -0,0 +1,175 @@
+use scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use sp_core::{H160, H256, U256};
+use sp_runtime::RuntimeDebug;
+use sp_std::vec::Vec;
+
+/// Which trace the runtime collects while re-executing a transaction.
+#[derive(Clone, Copy, Eq, PartialEq, RuntimeDebug, Encode, Decode, TypeInfo)]
+pub enum TracerConfig {
+	/// One entry per executed opcode, as the default Geth tracer.
+	StructLogs {
+		disable_storage: bool,
+		disable_memory: bool,
+		disable_stack: bool,
+	},
+	/// The tree of calls, as the Geth `callTracer`.
+	CallTracer,
+	/// The accounts touched by the transaction before it runs, as the Geth `prestateTracer`.
+	PrestateTracer,
+}
+
+#[derive(Clone, Eq, PartialEq, RuntimeDebug, Encode, Decode, TypeInfo)]
+pub struct StructLog {
+	pub pc: u64,
+	pub op: u8,
+	/// Gas remaining before the opcode runs.
+	pub gas: u64,
+	pub gas_cost: u64,
+	pub depth: u32,
+	pub stack: Option<Vec<H256>>,
+	pub memory: Option<Vec<u8>>,
+	/// Slots of the executing contract touched so far.
+	pub storage: Option<Vec<(H256, H256)>>,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, RuntimeDebug, Encode, Decode, TypeInfo)]
+pub enum CallType {
+	Call,
+	StaticCall,
+	DelegateCall,
+	CallCode,
+	Create,
+	Create2,
+	SelfDestruct,
+}
+
+#[derive(Clone, Eq, PartialEq, RuntimeDebug, Encode, Decode, TypeInfo)]
+pub struct CallFrame {
+	pub call_type: CallType,
+	pub from: H160,
+	pub to: H160,
+	pub value: U256,
+	pub gas: u64,
+	pub gas_used: u64,
+	pub input: Vec<u8>,
+	pub output: Vec<u8>,
+	pub error: Option<Vec<u8>>,
+	pub calls: Vec<CallFrame>,
+}
+
+#[derive(Clone, Eq, PartialEq, RuntimeDebug, Encode, Decode, TypeInfo)]
+pub struct PrestateAccount {
+	pub address: H160,
+	/// `None` for accounts with a registered public key, whose balance is confidential.
+	pub balance: Option<U256>,
+	pub nonce: U256,
+	pub code: Vec<u8>,
+	pub storage: Vec<(H256, H256)>,
+}
+
+#[derive(Clone, Eq, PartialEq, RuntimeDebug, Encode, Decode, TypeInfo)]
+pub enum TransactionTrace {
+	StructLogs {
+		gas: u64,
+		failed: bool,
+		return_value: Vec<u8>,
+		struct_logs: Vec<StructLog>,
+	},
+	CallTracer(CallFrame),
+	PrestateTracer(Vec<PrestateAccount>),
+}
+
+/// A call of a `CallFrame` tree, flattened as the Parity `trace` module reports it.
+#[derive(Clone, Eq, PartialEq, RuntimeDebug, Encode, Decode, TypeInfo)]
+pub struct FlatCallFrame {
+	/// Indices of the calls leading from the root call to this one.
+	pub trace_address: Vec<u32>,
+	/// Number of direct inner calls.
+	pub subtraces: u32,
+	pub call_type: CallType,
+	pub from: H160,
+	pub to: H160,
+	pub value: U256,
+	pub gas: u64,
+	pub gas_used: u64,
+	pub input: Vec<u8>,
+	pub output: Vec<u8>,
+	pub error: Option<Vec<u8>>,
+}
+
+impl CallFrame {
+	/// The calls of the tree in depth-first order, the root call first.
+	pub fn flatten(self) -> Vec<FlatCallFrame> {
+		let mut flat = Vec::new();
+		self.flatten_into(Vec::new(), &mut flat);
+		flat
+	}
+
+	fn flatten_into(self, trace_address: Vec<u32>, flat: &mut Vec<FlatCallFrame>) {
+		flat.push(FlatCallFrame {
+			trace_address: trace_address.clone(),
+			subtraces: self.calls.len() as u32,
+			call_type: self.call_type,
+			from: self.from,
+			to: self.to,
+			value: self.value,
+			gas: self.gas,
+			gas_used: self.gas_used,
+			input: self.input,
+			output: self.output,
+			error: self.error,
+		});
+		for (index, call) in self.calls.into_iter().enumerate() {
+			let mut address = trace_address.clone();
+			address.push(index as u32);
+			call.flatten_into(address, flat);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn frame(to: u64, calls: Vec<CallFrame>) -> CallFrame {
+		CallFrame {
+			call_type: CallType::Call,
+			from: H160::zero(),
+			to: H160::from_low_u64_be(to),
+			value: U256::zero(),
+			gas: 0,
+			gas_used: 0,
+			input: Vec::new(),
+			output: Vec::new(),
+			error: None,
+			calls,
+		}
+	}
+
+	#[test]
+	fn flatten_numbers_calls_depth_first() {
+		let root = frame(1, vec![frame(2, vec![frame(3, vec![])]), frame(4, vec![])]);
+
+		let flat = root.flatten();
+		let summary: Vec<_> = flat
+			.iter()
+			.map(|call| {
+				(
+					call.to.to_low_u64_be(),
+					call.trace_address.clone(),
+					call.subtraces,
+				)
+			})
+			.collect();
+		assert_eq!(
+			summary,
+			vec![
+				(1, vec![], 2),
+				(2, vec![0], 1),
+				(3, vec![0, 0], 0),
+				(4, vec![1], 0),
+			]
+		);
+	}
+}
@@ -105,6 +105,10 @@ where
 	/// Extensions registered with this instance.
 	#[cfg(feature = "std")]
 	extensions: Option<OverlayedExtensions<'a>>,
+	/// Write set of the innermost open storage transaction, taken from the overlay whenever
+	/// the [`tp_io::TransactionWriteSet`] extension is requested.
+	#[cfg(feature = "std")]
+	write_set: tp_io::TransactionWriteSet,
 }
 
 impl<'a, H, B> Ext<'a, H, B>
@@ -134,6 +138,7 @@ where
 			backend,
 			id: rand::random(),
 			extensions: extensions.map(OverlayedExtensions::new),
+			write_set: Vec::new().into(),
 		}
 	}
 }
@@ -558,6 +563,15 @@ where
 	fn storage_root(&mut self, state_version: StateVersion) -> Vec<u8> {
 		let _guard = guard();
 
+		#[cfg(feature = "std")]
+		if let Some(state_key) = self
+			.extension_by_type_id(TypeId::of::<tp_io::StateKeyExt>())
+			.and_then(|state_key| state_key.downcast_ref::<tp_io::StateKeyExt>())
+			.map(|state_key| state_key.0)
+		{
+			self.overlay.set_state_key_id(state_key);
+		}
+
 		let (root, _cached) = self.overlay.storage_root(self.backend, state_version);
 
 		trace!(
@@ -836,6 +850,10 @@ where
 	B: 'a + Backend<H>,
 {
 	fn extension_by_type_id(&mut self, type_id: TypeId) -> Option<&mut dyn Any> {
+		if type_id == TypeId::of::<tp_io::TransactionWriteSet>() {
+			self.write_set = self.overlay.transaction_write_set().into();
+			return Some(&mut self.write_set);
+		}
 		self.extensions
 			.as_mut()
 			.and_then(|exts| exts.get_mut(type_id))
@@ -879,10 +897,81 @@ mod tests {
 		storage::{Storage, StorageChild},
 		Blake2Hasher,
 	};
+	use sp_trie::TrieConfiguration;
 
 	type TestBackend = InMemoryBackend<Blake2Hasher>;
 	type TestExt<'a> = Ext<'a, Blake2Hasher, TestBackend>;
 
+	#[test]
+	fn storage_root_matches_host_encrypted_trie() {
+		// Through the state machine a Wasm runtime encrypts values with the
+		// `tp_io::crypto::encrypt` host function. The native backend must produce the same trie.
+		let values = vec![
+			(vec![10], vec![1; 40]),
+			(vec![20], vec![2; 3]),
+			(vec![30], vec![]),
+		];
+		let backend: TestBackend = (Storage::default(), StateVersion::V1).into();
+
+		let delta = values.iter().map(|(k, v)| (&k[..], Some(&v[..])));
+		let (root, _) = backend.storage_root(delta, StateVersion::V1);
+		let expected = sp_trie::LayoutV1::<Blake2Hasher>::trie_root(
+			values
+				.iter()
+				.map(|(k, v)| (k.clone(), tp_io::crypto::encrypt(v, k).unwrap())),
+		);
+		assert_eq!(root, expected);
+
+		let backend: TestBackend = (
+			Storage {
+				top: values.iter().cloned().collect(),
+				children_default: map![],
+			},
+			StateVersion::V1,
+		)
+			.into();
+		assert_eq!(
+			backend.storage_root(std::iter::empty(), StateVersion::V1).0,
+			expected
+		);
+		for (key, value) in values {
+			assert_eq!(backend.storage(&key).unwrap(), Some(value));
+		}
+	}
+
+	#[test]
+	fn transaction_write_set_extension_tracks_innermost_transaction() {
+		use sp_externalities::ExternalitiesExt;
+
+		let mut overlay = OverlayedChanges::default();
+		overlay.set_storage(b"pre:a".to_vec(), Some(vec![1]));
+		overlay.start_transaction();
+		overlay.set_storage(b"pre:b".to_vec(), Some(vec![2]));
+		overlay.set_storage(b"pre:a".to_vec(), None);
+		overlay.set_storage(b"other".to_vec(), Some(vec![3]));
+		let backend = (Storage::default(), StateVersion::default()).into();
+
+		let mut ext = TestExt::new(&mut overlay, &backend, None);
+		let digest = ext
+			.extension::<tp_io::TransactionWriteSet>()
+			.map(|write_set| write_set.digest(b"pre:"));
+		let expected: Vec<(&[u8], Option<&[u8]>)> =
+			vec![(&b"pre:a"[..], None), (&b"pre:b"[..], Some(&[2][..]))];
+		assert_eq!(
+			digest,
+			Some(sp_core::hashing::keccak_256(&expected.encode()))
+		);
+
+		ext.storage_start_transaction();
+		assert_eq!(
+			ext.extension::<tp_io::TransactionWriteSet>()
+				.map(|write_set| write_set.digest(b"pre:")),
+			Some(sp_core::hashing::keccak_256(
+				&Vec::<(Vec<u8>, Option<Vec<u8>>)>::new().encode()
+			))
+		);
+	}
+
 	#[test]
 	fn next_storage_key_works() {
 		let mut overlay = OverlayedChanges::default();
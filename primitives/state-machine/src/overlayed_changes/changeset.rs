@@ -29,6 +29,7 @@ use smallvec::SmallVec;
 use sp_std::{
 	collections::{btree_map::BTreeMap, btree_set::BTreeSet},
 	hash::Hash,
+	vec::Vec,
 };
 
 const PROOF_OVERLAY_NON_EMPTY: &str = "\
@@ -477,6 +478,31 @@ impl OverlayedChangeSet {
 		count
 	}
 
+	/// Get the writes made under `prefix` by the innermost open transaction, ordered by key.
+	///
+	/// Returns an empty list if no transaction is open.
+	pub fn transaction_write_set(&self, prefix: &[u8]) -> Vec<(&[u8], Option<&[u8]>)> {
+		let mut keys = self
+			.dirty_keys
+			.last()
+			.map(|dirty| {
+				dirty
+					.iter()
+					.filter(|key| key.starts_with(prefix))
+					.collect::<Vec<_>>()
+			})
+			.unwrap_or_default();
+		keys.sort();
+
+		keys.into_iter()
+			.filter_map(|key| {
+				self.changes
+					.get(key)
+					.map(|val| (key.as_slice(), val.value().map(AsRef::as_ref)))
+			})
+			.collect()
+	}
+
 	/// Get the iterator over all changes that follow the supplied `key`.
 	pub fn changes_after(&self, key: &[u8]) -> impl Iterator<Item = (&[u8], &OverlayedValue)> {
 		use sp_std::ops::Bound;
@@ -611,6 +637,36 @@ mod test {
 		assert_drained_changes(changeset, rolled_back);
 	}
 
+	#[test]
+	fn transaction_write_set_works() {
+		let mut changeset = OverlayedChangeSet::default();
+		assert!(changeset.transaction_write_set(b"").is_empty());
+
+		changeset.set(b"key0".to_vec(), Some(b"val0".to_vec()), None);
+		changeset.start_transaction();
+		changeset.set(b"pre:b".to_vec(), Some(b"val-b".to_vec()), None);
+		changeset.set(b"other".to_vec(), Some(b"val".to_vec()), None);
+		changeset.set(b"pre:a".to_vec(), None, None);
+
+		changeset.start_transaction();
+		changeset.set(b"pre:c".to_vec(), Some(b"val-c".to_vec()), None);
+		assert_eq!(
+			changeset.transaction_write_set(b"pre:"),
+			vec![(&b"pre:c"[..], Some(&b"val-c"[..]))],
+		);
+		changeset.commit_transaction().unwrap();
+
+		assert_eq!(
+			changeset.transaction_write_set(b"pre:"),
+			vec![
+				(&b"pre:a"[..], None),
+				(&b"pre:b"[..], Some(&b"val-b"[..])),
+				(&b"pre:c"[..], Some(&b"val-c"[..])),
+			],
+		);
+		assert_eq!(changeset.transaction_write_set(b"").len(), 4);
+	}
+
 	#[test]
 	fn transaction_commit_then_rollback_works() {
 		let mut changeset = OverlayedChangeSet::default();
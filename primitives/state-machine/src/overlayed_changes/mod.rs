@@ -106,6 +106,9 @@ pub struct OverlayedChanges<H: Hasher> {
 	///
 	/// This transaction can be applied to the backend to persist the state changes.
 	storage_transaction_cache: Option<StorageTransactionCache<H>>,
+	/// The state master key the storage root is encrypted under, the legacy global key when
+	/// `None`.
+	state_key_id: Option<u32>,
 }
 
 impl<H: Hasher> Default for OverlayedChanges<H> {
@@ -118,6 +121,7 @@ impl<H: Hasher> Default for OverlayedChanges<H> {
 			collect_extrinsics: Default::default(),
 			stats: Default::default(),
 			storage_transaction_cache: None,
+			state_key_id: None,
 		}
 	}
 }
@@ -132,6 +136,7 @@ impl<H: Hasher> Clone for OverlayedChanges<H> {
 			collect_extrinsics: self.collect_extrinsics,
 			stats: self.stats.clone(),
 			storage_transaction_cache: self.storage_transaction_cache.clone(),
+			state_key_id: self.state_key_id,
 		}
 	}
 }
@@ -146,6 +151,7 @@ impl<H: Hasher> sp_std::fmt::Debug for OverlayedChanges<H> {
 			.field("collect_extrinsics", &self.collect_extrinsics)
 			.field("stats", &self.stats)
 			.field("storage_transaction_cache", &self.storage_transaction_cache)
+			.field("state_key_id", &self.state_key_id)
 			.finish()
 	}
 }
@@ -305,6 +311,14 @@ impl<H: Hasher> OverlayedChanges<H> {
 		self.storage_transaction_cache = None;
 	}
 
+	/// Select the state master key the storage root is encrypted under.
+	pub fn set_state_key_id(&mut self, id: Option<u32>) {
+		if self.state_key_id != id {
+			self.state_key_id = id;
+			self.mark_dirty();
+		}
+	}
+
 	/// Returns mutable reference to current value.
 	/// If there is no value in the overlay, the given callback is used to initiate the value.
 	/// Warning this function registers a change, so the mutable reference MUST be modified.
@@ -660,6 +674,11 @@ impl<H: Hasher> OverlayedChanges<H> {
 			)
 		});
 
+		#[cfg(feature = "std")]
+		let (root, transaction) = tp_io::state_key::with_current_key_id(self.state_key_id, || {
+			backend.full_storage_root(delta, child_delta, state_version)
+		});
+		#[cfg(not(feature = "std"))]
 		let (root, transaction) = backend.full_storage_root(delta, child_delta, state_version);
 
 		self.storage_transaction_cache = Some(StorageTransactionCache {
@@ -758,6 +777,16 @@ impl<H: Hasher> OverlayedChanges<H> {
 			.flatten()
 	}
 
+	/// Top storage writes made by the innermost open transaction, ordered by key, where a
+	/// deleted key has no value.
+	pub fn transaction_write_set(&self) -> StorageCollection {
+		self.top
+			.transaction_write_set(&[])
+			.into_iter()
+			.map(|(key, value)| (key.to_vec(), value.map(<[u8]>::to_vec)))
+			.collect()
+	}
+
 	/// Read only access ot offchain overlay.
 	pub fn offchain(&self) -> &OffchainOverlayedChanges {
 		&self.offchain
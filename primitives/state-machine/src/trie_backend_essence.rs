@@ -540,21 +540,18 @@ where
 	/// Get the value of storage at given key.
 	pub fn storage(&self, key: &[u8]) -> Result<Option<StorageValue>> {
 		let map_e = |e| format!("Trie lookup error: {}", e);
-		let decrypt = |key, v: &[u8]| {
-			#[cfg(feature = "std")]
-			{
-				tp_io::crypto::decrypt(v, key).unwrap()
-			}
-
-			#[cfg(not(feature = "std"))]
-			v.to_vec()
-		};
 
-		self.with_recorder_and_cache(None, |recorder, cache| {
-			read_trie_value::<Layout<H>, _>(self, &self.root, key, recorder, cache)
-				.map(|x| x.as_ref().map(|v| decrypt(key, v.as_ref())))
-				.map_err(map_e)
-		})
+		let value = self.with_recorder_and_cache(None, |recorder, cache| {
+			read_trie_value::<Layout<H>, _>(self, &self.root, key, recorder, cache).map_err(map_e)
+		})?;
+		// Values are encrypted through the host function in both native and Wasm, so both
+		// read the same plaintext.
+		value
+			.map(|value| {
+				tp_io::crypto::decrypt(&value, key)
+					.map_err(|e| format!("Storage decryption error: {:?}", e))
+			})
+			.transpose()
 	}
 
 	/// Returns the hash value
@@ -691,15 +688,17 @@ where
 		let mut write_overlay = PrefixedMemoryDB::default();
 		let mut deltas: Vec<_> = Default::default();
 		for (key, val) in delta {
-			if let Some(val) = val {
-				#[cfg(feature = "std")]
-				deltas.push((key, Some(tp_io::crypto::encrypt(val, key).unwrap())));
-
-				#[cfg(not(feature = "std"))]
-				deltas.push((key, Some(val)));
-			} else {
-				deltas.push((key, None));
-			}
+			// The nonce is derived from the key and the value, so native and Wasm encrypt a
+			// value to the same bytes and compute the same root.
+			let val = match val.map(|val| tp_io::crypto::encrypt(val, key)).transpose() {
+				Ok(val) => val,
+				// The unchanged root does not match the one of the block, which fails to import.
+				Err(e) => {
+					warn!(target: "trie", "Failed to encrypt state value: {:?}", e);
+					return (self.root, write_overlay);
+				}
+			};
+			deltas.push((key, val));
 		}
 
 		let delta = deltas.iter().map(|(k, v)| (*k, v.as_ref().map(|v| &v[..])));
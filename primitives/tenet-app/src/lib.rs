@@ -41,26 +41,57 @@ impl Encodable for TxInput {
 	}
 }
 
+/// Leading byte of the output hash preimage of outputs without a write set digest, the
+/// original format.
+pub const OUTPUT_VERSION_V1: u8 = 2;
+/// Leading byte of the output hash preimage of outputs that commit to a write set digest.
+pub const OUTPUT_VERSION_V2: u8 = 3;
+
 pub struct TxOutput {
 	pub status_code: u8,
 	pub used_gas: U256,
 	pub logs: Vec<Log>,
+	/// Digest of the transaction's EVM storage write set, if committed to.
+	pub write_set_digest: Option<H256>,
 }
 impl TxOutput {
+	/// Format version of the output, which selects the leading byte of the hash preimage so
+	/// outputs of both formats never collide.
+	pub fn version(&self) -> u8 {
+		if self.write_set_digest.is_some() {
+			OUTPUT_VERSION_V2
+		} else {
+			OUTPUT_VERSION_V1
+		}
+	}
+
 	pub fn hash(&self) -> H256 {
 		let encoded = rlp::encode(self);
 		let mut out = alloc::vec![0; 1 + encoded.len()];
-		out[0] = 2;
+		out[0] = self.version();
 		out[1..].copy_from_slice(&encoded);
 		H256::from(sp_io::hashing::keccak_256(&out))
 	}
 }
 impl Encodable for TxOutput {
 	fn rlp_append(&self, s: &mut RlpStream) {
-		s.begin_list(2)
-			.append(&self.status_code)
-			.append(&self.used_gas)
-			.append_list(&self.logs);
+		match &self.write_set_digest {
+			Some(digest) => {
+				s.begin_list(4)
+					.append(&self.status_code)
+					.append(&self.used_gas)
+					.append_list(&self.logs)
+					.append(digest);
+			}
+			// Version 1 outputs keep their original encoding, which closes the list before
+			// the logs, so that already issued output hashes stay valid.
+			None => {
+				s.begin_list(2)
+					.append(&self.status_code)
+					.append(&self.used_gas)
+					.append_list(&self.logs);
+			}
+		}
 	}
 }
 pub struct TenetApi {}
@@ -69,22 +100,27 @@ impl TenetApi {
 		transaction.hash()
 	}
 
-	pub fn generate_output_hash(receipt: &Receipt) -> H256 {
+	/// Commit to the receipt and, when given, the digest of the storage written by the
+	/// transaction, so the PoC binds the state transition and not only its logs.
+	pub fn generate_output_hash(receipt: &Receipt, write_set_digest: Option<H256>) -> H256 {
 		let tenet_app_output = match receipt {
 			Receipt::Legacy(t) => TxOutput {
 				status_code: t.status_code,
 				used_gas: t.used_gas,
 				logs: t.logs.clone(),
+				write_set_digest,
 			},
 			Receipt::EIP2930(t) => TxOutput {
 				status_code: t.status_code,
 				used_gas: t.used_gas,
 				logs: t.logs.clone(),
+				write_set_digest,
 			},
 			Receipt::EIP1559(t) => TxOutput {
 				status_code: t.status_code,
 				used_gas: t.used_gas,
 				logs: t.logs.clone(),
+				write_set_digest,
 			},
 		};
 		tenet_app_output.hash()
@@ -0,0 +1,188 @@
+//! Verification of tenet proofs of computation outside of a node.
+//!
+//! A [`PoC`] signs the input and output hashes of the transactions it covers. Given the
+//! transaction and its receipt, [`verify`] recomputes both hashes, checks the signature
+//! and checks any inclusion proofs of the receipt's logs, reporting each check on its own.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use fp_poc::PoC;
+use sp_core::{KeccakHasher, H256};
+use sp_std::vec::Vec;
+use tenet_app::TenetApi;
+use tp_ethereum::{EIP658ReceiptData, Receipt, TransactionV2 as Transaction};
+
+/// A single check performed by [`verify`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Check {
+	/// The PoC commits to the hash of the transaction.
+	Input,
+	/// The PoC commits to the output hash recomputed from the receipt for that transaction.
+	Output,
+	/// The PoC root is signed by the expected signer.
+	Signature,
+	/// The log at the given index is included in the receipt's logs root.
+	LogProof(usize),
+}
+
+/// Pass or fail for every check performed, in the order they ran.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+	pub checks: Vec<(Check, bool)>,
+}
+
+impl Report {
+	/// Whether every check passed.
+	pub fn is_valid(&self) -> bool {
+		self.checks.iter().all(|(_, passed)| *passed)
+	}
+}
+
+/// Merkle proof that a log is part of a receipt's logs trie.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogProof {
+	/// Index of the log within the receipt.
+	pub index: usize,
+	/// Trie nodes from the logs root down to the log.
+	pub proof: Vec<Vec<u8>>,
+}
+
+/// Verify `poc` for `transaction` and its `receipt`.
+///
+/// `signer` is the serialized secp256k1 public key of the node that produced the PoC, and
+/// `write_set_digest` must be given if the node committed to the transaction's storage writes.
+pub fn verify(
+	poc: &PoC,
+	signer: &[u8],
+	transaction: &Transaction,
+	receipt: &Receipt,
+	write_set_digest: Option<H256>,
+	log_proofs: &[LogProof],
+) -> Report {
+	let mut report = Report::default();
+
+	let input_hash = TenetApi::generate_input_hash(transaction);
+	let io_hash = poc
+		.io_hash_list()
+		.iter()
+		.find(|io| io.input_hash == input_hash);
+	report.checks.push((Check::Input, io_hash.is_some()));
+
+	let output_hash = TenetApi::generate_output_hash(receipt, write_set_digest);
+	report.checks.push((
+		Check::Output,
+		io_hash.map_or(false, |io| io.output_hash == output_hash),
+	));
+
+	report.checks.push((Check::Signature, poc.verify(signer)));
+
+	let data = receipt_data(receipt);
+	for log_proof in log_proofs {
+		report.checks.push((
+			Check::LogProof(log_proof.index),
+			verify_log_proof(data, log_proof),
+		));
+	}
+
+	report
+}
+
+/// Check that `log_proof` proves the receipt's log at `log_proof.index` against its logs root.
+pub fn verify_log_proof(receipt: &EIP658ReceiptData, log_proof: &LogProof) -> bool {
+	let Some(log) = receipt.logs.get(log_proof.index) else {
+		return false;
+	};
+	let logs_root = tp_ethereum::util::ordered_trie_root(receipt.logs.iter().map(rlp::encode));
+
+	matches!(
+		tp_ethereum::order_verify_proof::<KeccakHasher>(
+			log_proof.proof.clone(),
+			logs_root,
+			log_proof.index,
+		),
+		Ok(Some(value)) if value == rlp::encode(log).to_vec()
+	)
+}
+
+fn receipt_data(receipt: &Receipt) -> &EIP658ReceiptData {
+	match receipt {
+		Receipt::Legacy(d) | Receipt::EIP2930(d) | Receipt::EIP1559(d) => d,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use fp_poc::IOHash;
+	use sp_core::{H160, U256};
+	use tp_ethereum::Log;
+
+	fn sample_receipt() -> EIP658ReceiptData {
+		let log = |data: u8| Log {
+			address: H160::repeat_byte(0xae),
+			topics: vec![H256::repeat_byte(data)],
+			data: vec![data],
+			log_type: None,
+			receivers_root: None,
+		};
+		EIP658ReceiptData {
+			status_code: 1,
+			used_gas: U256::from(21_000),
+			logs_bloom: Default::default(),
+			logs: vec![log(1), log(2), log(3)],
+		}
+	}
+
+	#[test]
+	fn log_proof_works() {
+		let receipt = sample_receipt();
+		let (_, proof) = tp_ethereum::order_generate_proof::<KeccakHasher, _, _>(
+			receipt.logs.iter().map(rlp::encode),
+			1,
+		)
+		.unwrap();
+
+		assert!(verify_log_proof(
+			&receipt,
+			&LogProof {
+				index: 1,
+				proof: proof.clone(),
+			}
+		));
+		assert!(!verify_log_proof(&receipt, &LogProof { index: 2, proof }));
+		assert!(!verify_log_proof(
+			&receipt,
+			&LogProof {
+				index: 7,
+				proof: vec![],
+			}
+		));
+	}
+
+	#[test]
+	fn poc_signature_works() {
+		let private_key = H256::repeat_byte(1);
+		let public_key = secp256k1::PublicKey::from_secret_key(
+			&secp256k1::Secp256k1::new(),
+			&secp256k1::SecretKey::from_slice(private_key.as_bytes()).unwrap(),
+		);
+		let poc = fp_poc::generate_poc(
+			private_key,
+			&vec![IOHash {
+				input_hash: H256::repeat_byte(2),
+				output_hash: H256::repeat_byte(3),
+			}],
+		);
+
+		let decoded: PoC = rlp::decode(&rlp::encode(&poc)).unwrap();
+		assert_eq!(decoded.root(), poc.root());
+		assert_eq!(
+			decoded.signer(),
+			Some(H160::from_slice(
+				&sp_core::hashing::keccak_256(&public_key.serialize_uncompressed()[1..])[12..]
+			))
+		);
+		assert!(decoded.verify(&public_key.serialize()));
+		assert!(!decoded.verify(&[0u8; 33]));
+	}
+}